@@ -30,25 +30,22 @@ impl Default for X402Client<FirstMatch> {
 }
 
 impl<TSelector> X402Client<TSelector> {
-    /// Register a scheme client for specific chains.
+    /// Register a scheme client.
+    ///
+    /// A scheme client accepts the payment requirements of its own scheme on any chain of its namespace.
     ///
     /// # Arguments
-    /// * `pattern` - Chain pattern to match (can be exact, wildcard, or set)
     /// * `scheme` - The scheme client implementation
     ///
     /// # Examples
     /// ```rust,ignore
-    /// // Register for all EIP-155 chains
-    /// let client = X402Client::new()
-    ///     .register(ChainIdPattern::wildcard("eip155".into()), V2Eip155ExactClient::new(signer));
-    ///
-    /// // Register for specific chain
-    /// let client = X402Client::new()
-    ///     .register(ChainId::new("eip155", "84532"), V2Eip155ExactClient::new(signer));
+    /// // Pay with ERC-3009 tokens on EIP-155 chains
+    /// let client = X402Client::new().register(V2Eip155ExactClient::new(signer));
     ///
-    /// // Register for multiple chains using pattern parsing
+    /// // Also pay with ERC-20 tokens lacking ERC-3009, through Permit2
     /// let client = X402Client::new()
-    ///     .register("eip155:{1,8453,84532}".parse::<ChainIdPattern>().unwrap(), V2Eip155ExactClient::new(signer));
+    ///     .register(V2Eip155ExactClient::new(signer.clone()))
+    ///     .register(V2Eip155Permit2Client::new(signer));
     /// ```
     pub fn register<S>(mut self, scheme: S) -> Self
     where
@@ -9,6 +9,7 @@ use x402_reqwest::{ReqwestWithPayments, ReqwestWithPaymentsBuild, X402Client};
 use x402_rs::scheme::v1_eip155_exact::client::V1Eip155ExactClient;
 use x402_rs::scheme::v1_solana_exact::client::V1SolanaExactClient;
 use x402_rs::scheme::v2_eip155_exact::client::V2Eip155ExactClient;
+use x402_rs::scheme::v2_eip155_permit2::client::V2Eip155Permit2Client;
 use x402_rs::scheme::v2_solana_exact::client::V2SolanaExactClient;
 
 #[tokio::main]
@@ -16,7 +17,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
 
     let mut x402_client = X402Client::new();
-    // Register eip155 "exact" scheme
+    // Register eip155 "exact" and "permit2" schemes
     {
         let signer: Option<PrivateKeySigner> = env::var("EVM_PRIVATE_KEY")
             .ok()
@@ -26,8 +27,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let signer = Arc::new(signer);
             x402_client = x402_client
                 .register(V1Eip155ExactClient::new(signer.clone()))
-                .register(V2Eip155ExactClient::new(signer));
-            println!("Enabled eip155 exact scheme")
+                .register(V2Eip155ExactClient::new(signer.clone()))
+                // ERC-20 tokens without ERC-3009, once Permit2 is approved on the token
+                .register(V2Eip155Permit2Client::new(signer));
+            println!("Enabled eip155 exact and permit2 schemes")
         }
     };
 
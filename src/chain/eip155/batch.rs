@@ -0,0 +1,271 @@
+//! Batching of settlement transactions through Multicall3.
+//!
+//! Settlements that arrive within a short window are combined into one `aggregate3` call,
+//! each with `allowFailure: true`, so that they share the base cost of a transaction.
+//! Right before sending, the batch is simulated: the return data tells which settlements
+//! succeed, and those that fail are answered right away and left out of the batch.
+//! Once the batch is mined, each settlement gets the receipt of the shared transaction
+//! if its token emitted the `AuthorizationUsed` event of its authorization, and an error otherwise.
+//!
+//! Only calls whose outcome does not depend on `msg.sender` can be batched,
+//! as Multicall3 becomes the sender.
+
+use alloy_network::TransactionBuilder;
+use alloy_provider::bindings::IMulticall3;
+use alloy_provider::{MULTICALL3_ADDRESS, Provider};
+use alloy_rpc_types_eth::{Log, TransactionRequest};
+use alloy_sol_types::{SolCall, sol};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::Instrument;
+
+use crate::chain::eip155::{
+    Eip155ChainProvider, Eip155MetaTransactionProvider, Eip3009Authorization, MetaTransaction,
+    MetaTransactionReceipt, MetaTransactionSendError,
+};
+use crate::config::BatchSettlementConfig;
+
+type BatchResult = Result<MetaTransactionReceipt, MetaTransactionSendError>;
+
+sol! {
+    /// Emitted by EIP-3009 tokens for every executed authorization.
+    event AuthorizationUsed(address indexed authorizer, bytes32 indexed nonce);
+}
+
+/// A settlement waiting for its batch.
+struct BatchItem {
+    tx: MetaTransaction,
+    /// Authorization the settlement executes, to find its outcome in the batch receipt.
+    authorization: Eip3009Authorization,
+    respond: oneshot::Sender<BatchResult>,
+}
+
+/// Collects settlements into batches, sent by a background task.
+#[derive(Debug)]
+pub struct SettlementBatcher {
+    items: mpsc::UnboundedSender<BatchItem>,
+}
+
+impl SettlementBatcher {
+    /// Starts batching settlements sent through `provider`, which should not batch itself.
+    pub fn spawn(provider: Eip155ChainProvider, config: &BatchSettlementConfig) -> Self {
+        let window = Duration::from_millis(config.window_ms);
+        let max_items = config.max_items.max(1);
+        let (items, mut receiver) = mpsc::unbounded_channel::<BatchItem>();
+        tokio::spawn(async move {
+            while let Some(first) = receiver.recv().await {
+                let deadline = tokio::time::Instant::now() + window;
+                let mut items = vec![first];
+                while items.len() < max_items {
+                    match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                        Ok(Some(item)) => items.push(item),
+                        Ok(None) | Err(_) => break,
+                    }
+                }
+                // Settlements waiting for different confirmations cannot share a transaction
+                let mut batches = BTreeMap::<u64, Vec<BatchItem>>::new();
+                for item in items {
+                    batches.entry(item.tx.confirmations).or_default().push(item);
+                }
+                for batch in batches.into_values() {
+                    let provider = provider.clone();
+                    tokio::spawn(async move { send_batch(&provider, batch).await });
+                }
+            }
+        });
+        Self { items }
+    }
+
+    /// Sends `tx`, executing `authorization`, with the next batch, and waits for its outcome.
+    pub async fn send(
+        &self,
+        tx: MetaTransaction,
+        authorization: Eip3009Authorization,
+    ) -> BatchResult {
+        let (respond, response) = oneshot::channel();
+        self.items
+            .send(BatchItem {
+                tx,
+                authorization,
+                respond,
+            })
+            .map_err(|_| MetaTransactionSendError::Batch("batcher stopped".to_string()))?;
+        response
+            .await
+            .map_err(|_| MetaTransactionSendError::Batch("batch dropped".to_string()))?
+    }
+}
+
+/// Simulates the batch, answers the settlements that fail, sends the others,
+/// and answers each of them with its outcome in the mined transaction.
+async fn send_batch(provider: &Eip155ChainProvider, mut items: Vec<BatchItem>) {
+    if items.len() > 1 {
+        let from = provider.next_signer_address();
+        let simulation = provider
+            .inner()
+            .call(
+                TransactionRequest::default()
+                    .with_from(from)
+                    .with_to(MULTICALL3_ADDRESS)
+                    .with_input(aggregate_call(&items).abi_encode()),
+            )
+            .into_future()
+            .instrument(tracing::info_span!("simulate_batch", items = items.len()))
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|output| {
+                IMulticall3::aggregate3Call::abi_decode_returns(&output).map_err(|e| e.to_string())
+            });
+        let results = match simulation {
+            Ok(results) if results.len() == items.len() => results,
+            Ok(_) => return fail_all(items, "unexpected batch simulation result"),
+            Err(error) => return fail_all(items, &format!("batch simulation failed: {error}")),
+        };
+        let (succeeding, failing) = split_by_outcome(items, &results);
+        for item in failing {
+            let error = format!("call to {} reverted in batch simulation", item.tx.to);
+            let _ = item
+                .respond
+                .send(Err(MetaTransactionSendError::Batch(error)));
+        }
+        items = succeeding;
+        // The batch keeps the signer it was simulated with
+        for item in &mut items {
+            item.tx.from = Some(from);
+        }
+    }
+    match items.len() {
+        0 => {}
+        // A single settlement is cheaper without Multicall3
+        1 => {
+            let item = items.pop().expect("one item");
+            let result = provider.send_transaction(item.tx).await;
+            let _ = item.respond.send(result);
+        }
+        _ => {
+            let tx = MetaTransaction {
+                to: MULTICALL3_ADDRESS,
+                calldata: aggregate_call(&items).abi_encode().into(),
+                confirmations: items[0].tx.confirmations,
+                from: items[0].tx.from,
+            };
+            let result = provider
+                .send_transaction(tx)
+                .instrument(tracing::info_span!("send_batch", items = items.len()))
+                .await;
+            match result {
+                Ok(receipt) => {
+                    tracing::info!(tx = %receipt.transaction_hash, items = items.len(), "Sent settlement batch");
+                    let logs = receipt
+                        .receipt
+                        .as_ref()
+                        .filter(|receipt| receipt.status())
+                        .map_or(&[][..], |receipt| receipt.inner.logs());
+                    for item in items {
+                        let result = if is_authorization_used(logs, &item.authorization) {
+                            Ok(receipt.clone())
+                        } else {
+                            Err(MetaTransactionSendError::Batch(format!(
+                                "call to {} did not execute in batch transaction {}",
+                                item.tx.to, receipt.transaction_hash
+                            )))
+                        };
+                        let _ = item.respond.send(result);
+                    }
+                }
+                Err(error) => fail_all(items, &error.to_string()),
+            }
+        }
+    }
+}
+
+fn aggregate_call(items: &[BatchItem]) -> IMulticall3::aggregate3Call {
+    let calls = items
+        .iter()
+        .map(|item| IMulticall3::Call3 {
+            target: item.tx.to,
+            allowFailure: true,
+            callData: item.tx.calldata.clone(),
+        })
+        .collect();
+    IMulticall3::aggregate3Call { calls }
+}
+
+/// Splits `items` into those that succeed and those that fail, according to their `results`.
+fn split_by_outcome<T>(items: Vec<T>, results: &[IMulticall3::Result]) -> (Vec<T>, Vec<T>) {
+    let (succeeding, failing): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .zip(results)
+        .partition(|(_, result)| result.success);
+    (
+        succeeding.into_iter().map(|(item, _)| item).collect(),
+        failing.into_iter().map(|(item, _)| item).collect(),
+    )
+}
+
+/// Whether `logs` hold the `AuthorizationUsed` event of `authorization`, emitted by its token.
+fn is_authorization_used(logs: &[Log], authorization: &Eip3009Authorization) -> bool {
+    logs.iter()
+        .filter(|log| log.address() == authorization.token)
+        .filter_map(|log| log.log_decode::<AuthorizationUsed>().ok())
+        .any(|event| {
+            event.inner.authorizer == authorization.authorizer
+                && event.inner.nonce == authorization.nonce
+        })
+}
+
+fn fail_all(items: Vec<BatchItem>, error: &str) {
+    for item in items {
+        let _ = item
+            .respond
+            .send(Err(MetaTransactionSendError::Batch(error.to_string())));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, B256, Bytes};
+    use alloy_sol_types::SolEvent;
+
+    #[test]
+    fn test_split_by_outcome() {
+        let result = |success| IMulticall3::Result {
+            success,
+            returnData: Bytes::new(),
+        };
+        let (succeeding, failing) =
+            split_by_outcome(vec![1, 2, 3], &[result(true), result(false), result(true)]);
+        assert_eq!(succeeding, vec![1, 3]);
+        assert_eq!(failing, vec![2]);
+    }
+
+    #[test]
+    fn test_authorization_used() {
+        let authorization = Eip3009Authorization {
+            token: Address::repeat_byte(1),
+            authorizer: Address::repeat_byte(2),
+            nonce: B256::repeat_byte(3),
+        };
+        let log = |token, nonce| Log {
+            inner: alloy_primitives::Log {
+                address: token,
+                data: AuthorizationUsed {
+                    authorizer: authorization.authorizer,
+                    nonce,
+                }
+                .encode_log_data(),
+            },
+            ..Log::default()
+        };
+        let used = log(authorization.token, authorization.nonce);
+        assert!(is_authorization_used(&[used], &authorization));
+        let other_nonce = log(authorization.token, B256::repeat_byte(4));
+        let other_token = log(Address::repeat_byte(5), authorization.nonce);
+        assert!(!is_authorization_used(
+            &[other_nonce, other_token],
+            &authorization
+        ));
+    }
+}
@@ -1,31 +1,40 @@
+pub mod batch;
 pub mod pending_nonce_manager;
+pub mod token_metadata;
 pub mod types;
 
 use alloy_network::{Ethereum as AlloyEthereum, EthereumWallet, NetworkWallet, TransactionBuilder};
-use alloy_primitives::{Address, B256, Bytes};
+use alloy_primitives::{Address, B256, Bytes, TxHash, U256, address};
 use alloy_provider::fillers::{
-    BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller, WalletFiller,
+    BlobGasFiller, ChainIdFiller, FillProvider, GasFiller, JoinFill, NonceFiller, NonceManager,
+    WalletFiller,
 };
 use alloy_provider::{
-    Identity, PendingTransactionError, Provider, ProviderBuilder, RootProvider, WalletProvider,
+    Identity, PendingTransactionBuilder, PendingTransactionError, Provider, ProviderBuilder,
+    RootProvider, WalletProvider, WatchTxError,
 };
 use alloy_rpc_client::RpcClient;
-use alloy_rpc_types_eth::{BlockId, TransactionReceipt, TransactionRequest};
+use alloy_rpc_types_eth::{BlockId, BlockNumberOrTag, TransactionReceipt, TransactionRequest};
 use alloy_signer::Signer;
 use alloy_signer_local::PrivateKeySigner;
 use alloy_transport::TransportError;
 use alloy_transport::layers::{FallbackLayer, ThrottleLayer};
 use alloy_transport_http::Http;
+use dashmap::DashMap;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use tower::ServiceBuilder;
 use tracing::Instrument;
 
-use crate::chain::{ChainId, ChainProviderOps};
+use crate::chain::{ChainId, ChainProviderOps, Finality, FinalityError, FinalityPolicy};
 use crate::config::Eip155ChainConfig;
+use batch::SettlementBatcher;
 pub use pending_nonce_manager::*;
+pub use token_metadata::*;
 pub use types::*;
 
 /// Combined filler type for gas, blob gas, nonce, and chain ID.
@@ -43,6 +52,58 @@ pub type InnerProvider = FillProvider<
     RootProvider,
 >;
 
+/// How often receipts are polled while a transaction may be replaced.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the `finalized` block is polled while waiting for a transaction to be finalized.
+const FINALIZED_POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+/// How long to wait for a mined transaction to be finalized, which takes two epochs on Ethereum.
+const FINALIZATION_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Smallest fee increase, in percent, that nodes accept for a replacement transaction.
+const MIN_FEE_BUMP_PERCENT: u64 = 10;
+
+/// Attempts to look up the EIP-6492 validator at startup, waiting one more backoff after each failure.
+const EIP6492_DETECT_ATTEMPTS: u32 = 3;
+const EIP6492_DETECT_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Signature verifier for EIP-6492, EIP-1271, EOA, universally deployed on the supported EVM chains
+/// If absent on a target chain, EIP-6492 signatures are validated deployless, see [`Eip6492Validation`].
+pub const VALIDATOR_ADDRESS: Address = address!("0xdAcD51A54883eb67D95FAEb2BBfdC4a9a6BD2a3B");
+
+/// How EIP-6492 signatures are validated on a chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Eip6492Validation {
+    /// Through the universal validator deployed at [`VALIDATOR_ADDRESS`].
+    Validator,
+    /// Without the validator: an `eth_simulateV1` simulation deploys the wallet with its factory,
+    /// then checks the inner signature with ERC-1271 `isValidSignature`.
+    Deployless,
+}
+
+impl Eip6492Validation {
+    /// [`Self::Validator`] if the universal validator is deployed on the chain, [`Self::Deployless`] otherwise.
+    ///
+    /// The mode is kept for the lifetime of the provider, so a failed lookup is retried a few times,
+    /// and then returned as an error rather than guessing a mode.
+    pub async fn detect<P: Provider>(provider: P) -> Result<Self, TransportError> {
+        let mut attempt = 1;
+        loop {
+            match provider.get_code_at(VALIDATOR_ADDRESS).await {
+                Ok(code) if !code.is_empty() => return Ok(Self::Validator),
+                Ok(_) => return Ok(Self::Deployless),
+                Err(error) if attempt < EIP6492_DETECT_ATTEMPTS => {
+                    tracing::warn!(%error, attempt, "Failed to look up the EIP-6492 validator, retrying");
+                    tokio::time::sleep(EIP6492_DETECT_BACKOFF * attempt).await;
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
 pub const EIP155_NAMESPACE: &str = "eip155";
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -137,19 +198,31 @@ pub struct TokenDeploymentEip712 {
     pub version: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Eip155ChainProvider {
     chain: Eip155ChainReference,
     eip1559: bool,
     flashblocks: bool,
     receipt_timeout_secs: u64,
+    /// Fee caps, and how pending transactions are replaced.
+    fees: FeePolicy,
     inner: InnerProvider,
     /// Available signer addresses for round-robin selection.
     signer_addresses: Arc<Vec<Address>>,
     /// Current position in round-robin signer rotation.
     signer_cursor: Arc<AtomicUsize>,
-    /// Nonce manager for resetting nonces on transaction failures.
+    /// Nonce manager, told which nonces were broadcast, released and confirmed.
     nonce_manager: PendingNonceManager,
+    /// Sent transactions, to tell stuck ones from those whose receipt is awaited.
+    sent: SentTransactions,
+    /// Settlement finality, and the bounds of per-request overrides.
+    finality: FinalityPolicy,
+    /// How EIP-6492 signatures are validated, detected at startup.
+    eip6492_validation: Eip6492Validation,
+    /// Combines batchable settlements into Multicall3 transactions, if enabled.
+    batcher: Option<Arc<SettlementBatcher>>,
+    /// Name, version and decimals of tokens, for EIP-712 domains.
+    token_metadata: TokenMetadataCache,
 }
 
 impl Eip155ChainProvider {
@@ -216,7 +289,14 @@ impl Eip155ChainProvider {
 
         // 3. Provider
         // Create nonce manager explicitly so we can store a reference for error handling
-        let nonce_manager = PendingNonceManager::default();
+        let nonce_manager = match config.nonce_store_path() {
+            Some(path) => {
+                let store = FileNonceStore::open(path.clone())?;
+                tracing::info!(chain=%config.chain_id(), path=%path.display(), "Using nonce store");
+                PendingNonceManager::with_store(Arc::new(store))
+            }
+            None => PendingNonceManager::default(),
+        };
         // Build the filler stack: Gas -> BlobGas -> Nonce -> ChainId
         // This mirrors the InnerFiller type but with our custom nonce manager
         let filler = JoinFill::new(
@@ -234,22 +314,75 @@ impl Eip155ChainProvider {
             .wallet(wallet)
             .connect_client(client);
 
+        let finality = config.finality();
+        for finality in finality.finalities() {
+            required_confirmations(finality)?;
+        }
+
+        let fees = FeePolicy {
+            max_fee_per_gas: config.max_fee_per_gas().map(u128::from),
+            max_priority_fee_per_gas: config.max_priority_fee_per_gas().map(u128::from),
+            bump_interval: config.fee_bump_interval_secs().map(Duration::from_secs),
+            bump_percent: config.fee_bump_percent(),
+        };
+        if fees.bump_percent < MIN_FEE_BUMP_PERCENT {
+            return Err(format!("fee_bump_percent must be at least {MIN_FEE_BUMP_PERCENT}").into());
+        }
+
+        let token_metadata =
+            TokenMetadataCache::new(Duration::from_secs(config.token_metadata_ttl_secs()));
+        for token in config.token_metadata() {
+            if let (Some(name), Some(version)) = (&token.name, &token.version) {
+                let metadata = TokenMetadata {
+                    name: name.clone(),
+                    version: version.clone(),
+                    decimals: token.decimals,
+                };
+                token_metadata.pin(token.asset, metadata);
+            } else if let Err(error) = token_metadata.get(&inner, token.asset).await {
+                tracing::warn!(chain=%config.chain_id(), asset=%token.asset, %error, "Failed to fetch token metadata");
+            }
+        }
+
+        let eip6492_validation = Eip6492Validation::detect(&inner).await.map_err(|error| {
+            format!(
+                "failed to look up the EIP-6492 validator on {}: {error}",
+                config.chain_id()
+            )
+        })?;
+        tracing::info!(chain=%config.chain_id(), mode=?eip6492_validation, "Detected EIP-6492 validation mode");
+
         tracing::info!(chain=%config.chain_id(), signers=?signer_addresses, "Initialized EVM provider");
 
-        Ok(Self {
+        let mut provider = Self {
             chain: config.chain_reference(),
             eip1559: config.eip1559(),
             flashblocks: config.flashblocks(),
             receipt_timeout_secs: config.receipt_timeout_secs(),
+            fees,
             inner,
             signer_addresses,
             signer_cursor,
             nonce_manager,
-        })
+            sent: SentTransactions::default(),
+            finality,
+            eip6492_validation,
+            batcher: None,
+            token_metadata,
+        };
+        if let Some(batch_settlement) = config.batch_settlement() {
+            tracing::info!(chain=%config.chain_id(), window_ms=batch_settlement.window_ms, max_items=batch_settlement.max_items, "Batching settlements");
+            let batcher = SettlementBatcher::spawn(provider.clone(), batch_settlement);
+            provider.batcher = Some(Arc::new(batcher));
+        }
+        if let Some(interval) = config.stuck_nonce_check_interval_secs() {
+            provider.spawn_stuck_nonce_watcher(Duration::from_secs(interval));
+        }
+        Ok(provider)
     }
 
     /// Round-robin selection of next signer from wallet.
-    fn next_signer_address(&self) -> Address {
+    pub fn next_signer_address(&self) -> Address {
         debug_assert!(!self.signer_addresses.is_empty());
         if self.signer_addresses.len() == 1 {
             self.signer_addresses[0]
@@ -259,6 +392,436 @@ impl Eip155ChainProvider {
             self.signer_addresses[next]
         }
     }
+
+    /// Whether `address` is one of the signers of this provider.
+    pub fn is_signer(&self, address: &Address) -> bool {
+        self.signer_addresses.contains(address)
+    }
+
+    /// Name, version and decimals of tokens on this chain.
+    pub fn token_metadata(&self) -> &TokenMetadataCache {
+        &self.token_metadata
+    }
+
+    /// How EIP-6492 signatures are validated on this chain.
+    pub fn eip6492_validation(&self) -> Eip6492Validation {
+        self.eip6492_validation
+    }
+
+    /// Finality to settle with, given the one `requested` by a settle request,
+    /// and the number of confirmations to wait for, zero when the transaction is only submitted.
+    ///
+    /// Defaults to the scheme's `confirmations`.
+    pub fn settlement_finality(
+        &self,
+        requested: Option<Finality>,
+        confirmations: u64,
+    ) -> Result<(Finality, u64), FinalityError> {
+        let finality = self
+            .finality
+            .resolve(requested, Finality::Confirmations(confirmations))?;
+        Ok((finality, required_confirmations(finality)?))
+    }
+
+    /// Waits for the mined `transaction_hash` to reach `finality`.
+    ///
+    /// Settlements sent with the confirmations of [`Self::settlement_finality`] already have them,
+    /// so this only waits for `finalized`: until the `finalized` block reaches the block of the
+    /// transaction. Also waits for confirmations of transactions sent by others, such as bundlers.
+    pub async fn wait_for_finality(
+        &self,
+        transaction_hash: TxHash,
+        finality: Finality,
+    ) -> Result<(), MetaTransactionSendError> {
+        let (timeout, poll_interval) = match finality {
+            Finality::Finalized => (FINALIZATION_TIMEOUT, FINALIZED_POLL_INTERVAL),
+            Finality::Confirmations(_) => (
+                Duration::from_secs(self.receipt_timeout_secs),
+                RECEIPT_POLL_INTERVAL,
+            ),
+            _ => return Ok(()),
+        };
+        let started = Instant::now();
+        loop {
+            // The finalized block is read first: a receipt read after it, in a block up to it, is final.
+            let reached = match finality {
+                Finality::Finalized => self
+                    .inner
+                    .get_block_by_number(BlockNumberOrTag::Finalized)
+                    .await?
+                    .map(|block| block.header.number),
+                _ => Some(self.inner.get_block_number().await?),
+            };
+            let receipt = self.inner.get_transaction_receipt(transaction_hash).await?;
+            let block_number = receipt.and_then(|receipt| receipt.block_number);
+            if let (Some(reached), Some(block_number)) = (reached, block_number) {
+                let done = match finality {
+                    Finality::Confirmations(confirmations) => {
+                        reached + 1 >= block_number + confirmations
+                    }
+                    _ => reached >= block_number,
+                };
+                if done {
+                    return Ok(());
+                }
+            }
+            if started.elapsed() >= timeout {
+                return Err(PendingTransactionError::from(WatchTxError::Timeout).into());
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    /// Sets the fees of `txr` from the current estimate, within the configured caps.
+    async fn set_fees(&self, txr: &mut TransactionRequest) -> Result<(), TransportError> {
+        if self.eip1559 {
+            let estimate = self
+                .inner
+                .estimate_eip1559_fees()
+                .instrument(tracing::info_span!("estimate_eip1559_fees"))
+                .await?;
+            let max_fee_per_gas = self.fees.cap_max_fee(estimate.max_fee_per_gas);
+            txr.set_max_fee_per_gas(max_fee_per_gas);
+            txr.set_max_priority_fee_per_gas(
+                self.fees
+                    .cap_priority_fee(estimate.max_priority_fee_per_gas, max_fee_per_gas),
+            );
+        } else {
+            let gas_price = self
+                .inner
+                .get_gas_price()
+                .instrument(tracing::info_span!("get_gas_price"))
+                .await?;
+            txr.set_gas_price(self.fees.cap_max_fee(gas_price));
+        }
+        Ok(())
+    }
+
+    /// Waits for the sent `txr` to get `confirmations`, replacing it if fee bumping is enabled.
+    async fn confirm(
+        &self,
+        pending_tx: PendingTransactionBuilder<AlloyEthereum>,
+        txr: TransactionRequest,
+        confirmations: u64,
+    ) -> Result<MetaTransactionReceipt, MetaTransactionSendError> {
+        let from_address = txr.from.expect("sender is set before sending");
+        let nonce = txr.nonce.expect("nonce is set before sending");
+        let timeout = Duration::from_secs(self.receipt_timeout_secs);
+        let transaction_hash = *pending_tx.tx_hash();
+        let result = match self.fees.bump_interval {
+            Some(bump_interval) => {
+                self.watch_replacing(txr, transaction_hash, confirmations, timeout, bump_interval)
+                    .await
+            }
+            None => pending_tx
+                .with_required_confirmations(confirmations)
+                .with_timeout(Some(timeout))
+                .get_receipt()
+                .await
+                .map(|receipt| MetaTransactionReceipt {
+                    transaction_hash,
+                    receipt: Some(receipt),
+                })
+                .map_err(MetaTransactionSendError::from),
+        };
+        // A transaction that is not confirmed keeps its nonce unless it was dropped, and is cancelled by the stuck nonce check if need be
+        if result.is_ok() {
+            self.nonce_manager
+                .mark_confirmed(from_address, nonce + 1)
+                .await;
+            self.sent.confirmed(from_address, nonce + 1);
+        } else {
+            self.sent.unwatch(from_address, nonce);
+            // The transaction may have been dropped, leaving a gap before the later ones
+            self.spawn_fill_nonce_gaps(from_address, Some(nonce));
+        }
+        result
+    }
+
+    /// Polls the receipts of `transaction_hash` and of its replacements until one of them
+    /// has `confirmations`.
+    ///
+    /// Every `bump_interval` without a receipt, `txr` is sent again with the same nonce and higher fees.
+    /// Any of the sent transactions may end up mined, so all of them are watched.
+    async fn watch_replacing(
+        &self,
+        mut txr: TransactionRequest,
+        transaction_hash: TxHash,
+        confirmations: u64,
+        timeout: Duration,
+        bump_interval: Duration,
+    ) -> Result<MetaTransactionReceipt, MetaTransactionSendError> {
+        let started = Instant::now();
+        let mut next_bump = started + bump_interval;
+        let mut sent = vec![transaction_hash];
+        loop {
+            let mut mined = false;
+            for &transaction_hash in &sent {
+                let receipt = self.inner.get_transaction_receipt(transaction_hash).await?;
+                let Some((receipt, block_number)) =
+                    receipt.and_then(|r| r.block_number.map(|block_number| (r, block_number)))
+                else {
+                    continue;
+                };
+                mined = true;
+                let head = self.inner.get_block_number().await?;
+                if head + 1 >= block_number + confirmations {
+                    return Ok(MetaTransactionReceipt {
+                        transaction_hash,
+                        receipt: Some(receipt),
+                    });
+                }
+            }
+            if started.elapsed() >= timeout {
+                return Err(PendingTransactionError::from(WatchTxError::Timeout).into());
+            }
+            if !mined && Instant::now() >= next_bump {
+                next_bump += bump_interval;
+                let replaced = *sent.last().expect("at least one transaction is sent");
+                if !self.fees.bump_fees(&mut txr) {
+                    tracing::warn!(tx = %replaced, "Fee caps leave no room to replace pending transaction");
+                } else {
+                    match self.inner.send_transaction(txr.clone()).await {
+                        Ok(pending_tx) => {
+                            let from_address = txr.from.expect("sender is set before sending");
+                            let nonce = txr.nonce.expect("nonce is set before sending");
+                            self.nonce_manager
+                                .mark_broadcast(from_address, nonce, Some(*pending_tx.tx_hash()))
+                                .await;
+                            tracing::info!(tx = %pending_tx.tx_hash(), %replaced, "Replaced pending transaction with higher fees");
+                            sent.push(*pending_tx.tx_hash());
+                        }
+                        // A previous transaction may have been mined meanwhile, which the next poll finds out.
+                        Err(error) => {
+                            tracing::warn!(tx = %replaced, %error, "Failed to replace pending transaction")
+                        }
+                    }
+                }
+            }
+            tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Checks every signer for a stuck nonce each `interval`, see [`Self::unblock_nonce`].
+    fn spawn_stuck_nonce_watcher(&self, interval: Duration) {
+        let provider = self.clone();
+        tokio::spawn(async move {
+            let mut confirmed_nonces = HashMap::new();
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                for &address in provider.signer_addresses.iter() {
+                    let previous = confirmed_nonces.get(&address).copied();
+                    match provider.unblock_nonce(address, previous).await {
+                        Ok(confirmed) => {
+                            confirmed_nonces.insert(address, confirmed);
+                        }
+                        Err(error) => {
+                            tracing::warn!(chain = %provider.chain, signer = %address, %error, "Failed to check signer for a stuck nonce")
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Unblocks `address` if its confirmed nonce has not moved since the `previous` check.
+    ///
+    /// A transaction pending at the confirmed nonce is cancelled by a zero-value transfer
+    /// to the signer itself, paying higher fees, once it was sent more than the receipt timeout ago
+    /// and its receipt is no longer awaited, see [`SentTransactions::is_stuck`]. Gaps in the nonces
+    /// of the signer are filled as well, see [`PendingNonceManager::fill_gaps`].
+    ///
+    /// Returns the confirmed nonce.
+    async fn unblock_nonce(
+        &self,
+        address: Address,
+        previous: Option<u64>,
+    ) -> Result<u64, MetaTransactionSendError> {
+        let confirmed = self.inner.get_transaction_count(address).latest().await?;
+        self.nonce_manager.mark_confirmed(address, confirmed).await;
+        self.sent.confirmed(address, confirmed);
+        if previous != Some(confirmed) {
+            return Ok(confirmed);
+        }
+        let pending = self.inner.get_transaction_count(address).pending().await?;
+        if pending > confirmed {
+            let timeout = Duration::from_secs(self.receipt_timeout_secs);
+            if !self.sent.is_stuck(address, confirmed, timeout) {
+                return Ok(confirmed);
+            }
+            let mut txr = self.self_transfer(address).await?.with_nonce(confirmed);
+            self.fees.bump_fees(&mut txr);
+            let pending_tx = self.inner.send_transaction(txr).await?;
+            self.nonce_manager
+                .mark_broadcast(address, confirmed, Some(*pending_tx.tx_hash()))
+                .await;
+            self.sent.record(address, confirmed, false);
+            tracing::warn!(chain = %self.chain, signer = %address, nonce = confirmed, tx = %pending_tx.tx_hash(), "Cancelling stuck transaction");
+            return Ok(confirmed);
+        }
+        self.fill_nonce_gaps(address, None).await?;
+        Ok(confirmed)
+    }
+
+    /// Fills the nonce gaps of `address` in the background, see [`Self::fill_nonce_gaps`].
+    fn spawn_fill_nonce_gaps(&self, address: Address, timed_out: Option<u64>) {
+        let provider = self.clone();
+        tokio::spawn(async move {
+            if let Err(error) = provider.fill_nonce_gaps(address, timed_out).await {
+                tracing::warn!(chain = %provider.chain, signer = %address, %error, "Failed to fill nonce gaps");
+            }
+        });
+    }
+
+    /// Fills the nonce gaps of `address` with zero-value transfers to itself,
+    /// see [`PendingNonceManager::fill_gaps`].
+    async fn fill_nonce_gaps(
+        &self,
+        address: Address,
+        timed_out: Option<u64>,
+    ) -> Result<(), TransportError> {
+        let filler = self.self_transfer(address).await?;
+        self.nonce_manager
+            .fill_gaps(&self.inner, address, timed_out, filler)
+            .await
+    }
+
+    /// Zero-value transfer from `address` to itself, with the current fees.
+    async fn self_transfer(&self, address: Address) -> Result<TransactionRequest, TransportError> {
+        let mut txr = TransactionRequest::default()
+            .with_from(address)
+            .with_to(address)
+            .with_value(U256::ZERO)
+            .with_gas_limit(21_000);
+        self.set_fees(&mut txr).await?;
+        Ok(txr)
+    }
+}
+
+/// Transactions sent by the signers, by signer and nonce.
+#[derive(Debug, Clone, Default)]
+struct SentTransactions(Arc<DashMap<(Address, u64), SentTransaction>>);
+
+#[derive(Debug, Clone, Copy)]
+struct SentTransaction {
+    sent_at: Instant,
+    /// Whether [`Eip155ChainProvider::confirm`] awaits its receipt, replacing it if need be.
+    watched: bool,
+}
+
+impl SentTransactions {
+    /// Records that `address` just sent a transaction with `nonce`, whose receipt is `watched` or not.
+    fn record(&self, address: Address, nonce: u64, watched: bool) {
+        let sent_at = Instant::now();
+        self.0
+            .insert((address, nonce), SentTransaction { sent_at, watched });
+    }
+
+    /// Records that the receipt of the transaction of `address` with `nonce` is no longer awaited.
+    fn unwatch(&self, address: Address, nonce: u64) {
+        if let Some(mut sent) = self.0.get_mut(&(address, nonce)) {
+            sent.watched = false;
+        }
+    }
+
+    /// Forgets the transactions of `address` below its `confirmed` nonce.
+    fn confirmed(&self, address: Address, confirmed: u64) {
+        self.0
+            .retain(|(sender, nonce), _| *sender != address || *nonce >= confirmed);
+    }
+
+    /// Whether the transaction of `address` with `nonce` was sent more than `timeout` ago,
+    /// and its receipt is no longer awaited.
+    ///
+    /// A transaction not recorded, such as one sent before a restart, is timed from now on.
+    fn is_stuck(&self, address: Address, nonce: u64, timeout: Duration) -> bool {
+        let sent = *self
+            .0
+            .entry((address, nonce))
+            .or_insert_with(|| SentTransaction {
+                sent_at: Instant::now(),
+                watched: false,
+            });
+        !sent.watched && sent.sent_at.elapsed() >= timeout
+    }
+}
+
+/// Fee caps of sent transactions, and how pending transactions are replaced.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FeePolicy {
+    /// Cap on the max fee per gas, or on the gas price of legacy transactions.
+    pub max_fee_per_gas: Option<u128>,
+    /// Cap on the max priority fee per gas.
+    pub max_priority_fee_per_gas: Option<u128>,
+    /// How long to wait for a receipt before replacing a transaction. No replacement if `None`.
+    pub bump_interval: Option<Duration>,
+    /// Percentage by which fees are raised on each replacement.
+    pub bump_percent: u64,
+}
+
+impl FeePolicy {
+    fn cap_max_fee(&self, fee: u128) -> u128 {
+        self.max_fee_per_gas.map_or(fee, |cap| fee.min(cap))
+    }
+
+    fn cap_priority_fee(&self, fee: u128, max_fee_per_gas: u128) -> u128 {
+        self.max_priority_fee_per_gas
+            .map_or(fee, |cap| fee.min(cap))
+            .min(max_fee_per_gas)
+    }
+
+    /// `fee` raised by the bump percentage within `cap`,
+    /// or `None` if the cap leaves too little room for nodes to accept a replacement.
+    fn bumped(&self, fee: u128, cap: Option<u128>) -> Option<u128> {
+        let increase = (fee * u128::from(self.bump_percent) / 100).max(1);
+        let bumped = fee.saturating_add(increase);
+        let bumped = cap.map_or(bumped, |cap| bumped.min(cap));
+        let min_replacement = fee + (fee * u128::from(MIN_FEE_BUMP_PERCENT) / 100).max(1);
+        (bumped >= min_replacement).then_some(bumped)
+    }
+
+    /// Raises the fees of `txr` for a replacement.
+    /// Returns `false`, leaving `txr` untouched, if the caps do not allow it.
+    fn bump_fees(&self, txr: &mut TransactionRequest) -> bool {
+        if let Some(gas_price) = txr.gas_price {
+            let Some(gas_price) = self.bumped(gas_price, self.max_fee_per_gas) else {
+                return false;
+            };
+            txr.set_gas_price(gas_price);
+            return true;
+        }
+        let (Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) =
+            (txr.max_fee_per_gas, txr.max_priority_fee_per_gas)
+        else {
+            return false;
+        };
+        let (Some(bumped_max_fee), Some(bumped_priority_fee)) = (
+            self.bumped(max_fee_per_gas, self.max_fee_per_gas),
+            self.bumped(max_priority_fee_per_gas, self.max_priority_fee_per_gas),
+        ) else {
+            return false;
+        };
+        if bumped_priority_fee > bumped_max_fee {
+            return false;
+        }
+        txr.set_max_fee_per_gas(bumped_max_fee);
+        txr.set_max_priority_fee_per_gas(bumped_priority_fee);
+        true
+    }
+}
+
+/// Block confirmations to wait for to reach `finality`.
+///
+/// `finalized` waits for one confirmation, and then for [`Eip155ChainProvider::wait_for_finality`].
+fn required_confirmations(finality: Finality) -> Result<u64, FinalityError> {
+    match finality {
+        Finality::Submitted => Ok(0),
+        Finality::Confirmations(confirmations) => Ok(confirmations),
+        Finality::Finalized => Ok(1),
+        _ => Err(FinalityError::Unsupported(finality)),
+    }
 }
 
 impl Eip155MetaTransactionProvider for &Eip155ChainProvider {
@@ -273,9 +836,16 @@ impl Eip155MetaTransactionProvider for &Eip155ChainProvider {
     fn send_transaction(
         &self,
         tx: MetaTransaction,
-    ) -> impl Future<Output = Result<TransactionReceipt, Self::Error>> + Send {
+    ) -> impl Future<Output = Result<MetaTransactionReceipt, Self::Error>> + Send {
         (*self).send_transaction(tx)
     }
+    fn send_batchable_transaction(
+        &self,
+        tx: MetaTransaction,
+        authorization: Eip3009Authorization,
+    ) -> impl Future<Output = Result<MetaTransactionReceipt, Self::Error>> + Send {
+        (*self).send_batchable_transaction(tx, authorization)
+    }
 }
 
 impl Eip155MetaTransactionProvider for Eip155ChainProvider {
@@ -293,25 +863,34 @@ impl Eip155MetaTransactionProvider for Eip155ChainProvider {
     /// Send a meta-transaction with provided `to`, `calldata`, and automatically selected signer.
     ///
     /// This method constructs a transaction from the provided [`MetaTransaction`], automatically
-    /// selects the next available signer using round-robin selection, and handles gas pricing
+    /// selects the next available signer using round-robin selection (unless [`MetaTransaction::from`]
+    /// pins a specific signer), and handles gas pricing
     /// based on whether the network supports EIP-1559.
     ///
-    /// If the transaction fails at any point (during submission or receipt fetching), the nonce
-    /// for the sending address is reset to force a fresh query on the next transaction. This
-    /// ensures correctness even when transactions partially succeed (e.g., submitted but receipt
-    /// fetch times out).
+    /// The nonce is allocated by the [`PendingNonceManager`], and reported back as broadcast
+    /// or released depending on whether the node accepted the transaction. A released nonce is
+    /// reused by the next transaction, or filled right away if later transactions wait on it,
+    /// so that a failed submission leaves no gap. The same goes for a transaction dropped
+    /// from the mempool before its receipt timeout.
     ///
     /// # Gas Pricing Strategy
     ///
-    /// - **EIP-1559 networks**: Uses automatic gas pricing via the provider's fillers.
+    /// - **EIP-1559 networks**: Estimates the fees using `estimate_eip1559_fees()` and sets them explicitly.
     /// - **Legacy networks**: Fetches the current gas price using `get_gas_price()` and sets it explicitly.
     ///
+    /// Either way, fees are capped by the configured `max_fee_per_gas` and `max_priority_fee_per_gas`.
+    /// With a fee bump interval configured, a transaction still pending after the interval is
+    /// replaced by the same transaction, with the same nonce and higher fees.
+    ///
     /// # Timeout Configuration
     ///
+    /// With zero [`MetaTransaction::confirmations`], returns as soon as the transaction is sent,
+    /// and waits for its receipt in the background.
+    ///
     /// Receipt fetching is subject to a configurable timeout:
     /// - Default: 30 seconds
     /// - Override via `TX_RECEIPT_TIMEOUT_SECS` environment variable
-    /// - If the timeout expires, the nonce is reset and an error is returned
+    /// - If the timeout expires, an error is returned
     ///
     /// # Parameters
     ///
@@ -319,7 +898,7 @@ impl Eip155MetaTransactionProvider for Eip155ChainProvider {
     ///
     /// # Returns
     ///
-    /// A [`TransactionReceipt`] once the transaction has been mined and confirmed.
+    /// A [`MetaTransactionReceipt`] once the transaction has been mined and confirmed.
     ///
     /// # Errors
     ///
@@ -330,21 +909,14 @@ impl Eip155MetaTransactionProvider for Eip155ChainProvider {
     async fn send_transaction(
         &self,
         tx: MetaTransaction,
-    ) -> Result<TransactionReceipt, Self::Error> {
-        let from_address = self.next_signer_address();
+    ) -> Result<MetaTransactionReceipt, Self::Error> {
+        let from_address = tx.from.unwrap_or_else(|| self.next_signer_address());
         let mut txr = TransactionRequest::default()
             .with_to(tx.to)
             .with_from(from_address)
             .with_input(tx.calldata);
 
-        if !self.eip1559 {
-            let provider = &self.inner;
-            let gas: u128 = provider
-                .get_gas_price()
-                .instrument(tracing::info_span!("get_gas_price"))
-                .await?;
-            txr.set_gas_price(gas);
-        }
+        self.set_fees(&mut txr).await?;
 
         // Estimate gas if not provided
         if txr.gas.is_none() {
@@ -357,31 +929,68 @@ impl Eip155MetaTransactionProvider for Eip155ChainProvider {
             txr.set_gas_limit(gas_limit)
         }
 
-        // Send transaction with error handling for nonce reset
-        let pending_tx = match self.inner.send_transaction(txr).await {
+        // The nonce is allocated here rather than by the filler, to report what became of it
+        let nonce = self
+            .nonce_manager
+            .get_next_nonce(&self.inner, from_address)
+            .await?;
+        txr.set_nonce(nonce);
+
+        let pending_tx = match self.inner.send_transaction(txr.clone()).await {
             Ok(pending) => pending,
             Err(e) => {
-                // Transaction submission failed - reset nonce to force requery
-                self.nonce_manager.reset_nonce(from_address).await;
+                self.nonce_manager
+                    .broadcast_failed(from_address, nonce, &e)
+                    .await;
+                self.spawn_fill_nonce_gaps(from_address, None);
                 return Err(MetaTransactionSendError::Transport(e));
             }
         };
+        self.nonce_manager
+            .mark_broadcast(from_address, nonce, Some(*pending_tx.tx_hash()))
+            .await;
+        self.sent.record(from_address, nonce, true);
 
-        // Get receipt with timeout and error handling for nonce reset
-        // Default timeout of 30 seconds is reasonable for most EVM chains
-        let timeout = std::time::Duration::from_secs(self.receipt_timeout_secs);
+        let transaction_hash = *pending_tx.tx_hash();
+        let confirmations = tx.confirmations.max(1);
 
-        let watcher = pending_tx
-            .with_required_confirmations(tx.confirmations)
-            .with_timeout(Some(timeout));
+        if tx.confirmations == 0 {
+            let provider = self.clone();
+            tokio::spawn(async move {
+                match provider.confirm(pending_tx, txr, confirmations).await {
+                    Ok(receipt) if receipt.status() => {
+                        tracing::info!(tx = %receipt.transaction_hash, "Submitted transaction confirmed")
+                    }
+                    Ok(receipt) => {
+                        tracing::warn!(tx = %receipt.transaction_hash, "Submitted transaction reverted")
+                    }
+                    Err(error) => {
+                        tracing::warn!(tx = %transaction_hash, %error, "Submitted transaction failed to confirm");
+                    }
+                }
+            });
+            return Ok(MetaTransactionReceipt {
+                transaction_hash,
+                receipt: None,
+            });
+        }
 
-        match watcher.get_receipt().await {
-            Ok(receipt) => Ok(receipt),
-            Err(e) => {
-                // Receipt fetch failed (timeout or other error) - reset nonce to force requery
-                self.nonce_manager.reset_nonce(from_address).await;
-                Err(MetaTransactionSendError::PendingTransaction(e))
+        self.confirm(pending_tx, txr, confirmations).await
+    }
+
+    /// Sends `tx` with the next settlement batch when batching is enabled,
+    /// and it neither pins a signer nor returns before its receipt.
+    async fn send_batchable_transaction(
+        &self,
+        tx: MetaTransaction,
+        authorization: Eip3009Authorization,
+    ) -> Result<MetaTransactionReceipt, Self::Error> {
+        match &self.batcher {
+            // The receipt tells whether the settlement executed within the batch
+            Some(batcher) if tx.from.is_none() && tx.confirmations > 0 => {
+                batcher.send(tx, authorization).await
             }
+            _ => self.send_transaction(tx).await,
         }
     }
 }
@@ -392,6 +1001,8 @@ pub enum MetaTransactionSendError {
     Transport(#[from] TransportError),
     #[error(transparent)]
     PendingTransaction(#[from] PendingTransactionError),
+    #[error("Batched transaction failed: {0}")]
+    Batch(String),
 }
 
 impl ChainProviderOps for Eip155ChainProvider {
@@ -407,14 +1018,45 @@ impl ChainProviderOps for Eip155ChainProvider {
     }
 }
 
-/// Meta-transaction parameters: target address, calldata, and required confirmations.
+/// Meta-transaction parameters: target address, calldata, required confirmations and optional sender.
 pub struct MetaTransaction {
     /// Target contract address.
     pub to: Address,
     /// Transaction calldata (encoded function call).
     pub calldata: Bytes,
     /// Number of block confirmations to wait for.
+    /// Zero returns once the transaction is sent, without waiting for its receipt.
     pub confirmations: u64,
+    /// Signer to send the transaction from, if it matters to the target contract
+    /// (e.g. a Permit2 spender). Defaults to round-robin selection.
+    pub from: Option<Address>,
+}
+
+/// An EIP-3009 authorization, as identified by the `AuthorizationUsed` event of its token.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Eip3009Authorization {
+    /// Token contract executing the authorization.
+    pub token: Address,
+    /// Account the authorization transfers from.
+    pub authorizer: Address,
+    pub nonce: B256,
+}
+
+/// Outcome of a sent [`MetaTransaction`].
+///
+/// Holds the receipt once the transaction has the required confirmations, or only its hash
+/// when it was sent with zero confirmations and is confirmed in the background.
+#[derive(Debug, Clone)]
+pub struct MetaTransactionReceipt {
+    pub transaction_hash: TxHash,
+    pub receipt: Option<TransactionReceipt>,
+}
+
+impl MetaTransactionReceipt {
+    /// Whether the transaction succeeded. A transaction that is not confirmed yet is assumed to.
+    pub fn status(&self) -> bool {
+        self.receipt.as_ref().is_none_or(|receipt| receipt.status())
+    }
 }
 
 /// Trait for sending meta-transactions with custom target and calldata.
@@ -433,5 +1075,64 @@ pub trait Eip155MetaTransactionProvider {
     fn send_transaction(
         &self,
         tx: MetaTransaction,
-    ) -> impl Future<Output = Result<TransactionReceipt, Self::Error>> + Send;
+    ) -> impl Future<Output = Result<MetaTransactionReceipt, Self::Error>> + Send;
+
+    /// Sends a meta-transaction that may be batched with others through Multicall3.
+    ///
+    /// Only for calls whose outcome does not depend on `msg.sender`, executing `authorization`,
+    /// which tells whether the call succeeded within a batch. Sent on its own unless the provider
+    /// batches settlements.
+    fn send_batchable_transaction(
+        &self,
+        tx: MetaTransaction,
+        _authorization: Eip3009Authorization,
+    ) -> impl Future<Output = Result<MetaTransactionReceipt, Self::Error>> + Send {
+        self.send_transaction(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_bump_within_caps() {
+        let fees = FeePolicy {
+            max_fee_per_gas: Some(150),
+            max_priority_fee_per_gas: Some(12),
+            bump_interval: None,
+            bump_percent: 20,
+        };
+        let mut txr = TransactionRequest::default();
+        txr.set_max_fee_per_gas(100);
+        txr.set_max_priority_fee_per_gas(10);
+        assert!(fees.bump_fees(&mut txr));
+        assert_eq!(txr.max_fee_per_gas, Some(120));
+        assert_eq!(txr.max_priority_fee_per_gas, Some(12));
+
+        // The priority fee cap leaves no room for a 10% increase.
+        assert!(!fees.bump_fees(&mut txr));
+        assert_eq!(txr.max_fee_per_gas, Some(120));
+
+        let mut legacy = TransactionRequest::default();
+        legacy.set_gas_price(130);
+        assert!(fees.bump_fees(&mut legacy));
+        assert_eq!(legacy.gas_price, Some(150));
+        assert!(!fees.bump_fees(&mut legacy));
+    }
+
+    #[test]
+    fn test_only_unwatched_old_transactions_are_stuck() {
+        let sent = SentTransactions::default();
+        let address = Address::repeat_byte(1);
+        sent.record(address, 7, true);
+        assert!(!sent.is_stuck(address, 7, Duration::ZERO));
+        sent.unwatch(address, 7);
+        assert!(sent.is_stuck(address, 7, Duration::ZERO));
+        assert!(!sent.is_stuck(address, 7, Duration::from_secs(60)));
+        // A transaction sent before a restart is timed from when it is first seen
+        assert!(!sent.is_stuck(address, 8, Duration::from_secs(60)));
+        sent.confirmed(address, 8);
+        assert_eq!(sent.0.len(), 1);
+    }
 }
@@ -1,36 +1,52 @@
-use alloy_primitives::Address;
+use alloy_network::TransactionBuilder;
+use alloy_primitives::{Address, TxHash};
 use alloy_provider::Provider;
 use alloy_provider::fillers::NonceManager;
-use alloy_transport::TransportResult;
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_transport::{TransportError, TransportResult};
 use async_trait::async_trait;
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Debug;
+use std::path::PathBuf;
 use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
 use tokio::sync::Mutex;
 
-/// A nonce manager that caches nonces locally and checks pending transactions on initialization.
+/// A nonce manager that tracks the nonces of each signer through their lifecycle.
 ///
-/// This implementation attempts to improve upon Alloy's `CachedNonceManager` by using `.pending()` when
-/// fetching the initial nonce, which includes pending transactions in the mempool. This prevents
-/// "nonce too low" errors when the application restarts while transactions are still pending.
+/// Nonces are allocated locally, then reported as broadcast once the node accepted the
+/// transaction, or released when the broadcast failed. Released nonces are gaps: the next
+/// allocation reuses them before handing out new nonces, so that a failed broadcast never
+/// leaves a hole that blocks later transactions. Nonces below the confirmed transaction count
+/// are forgotten. See [`SignerNonceState`].
+///
+/// When no later allocation comes to reuse a gap that blocks broadcast transactions,
+/// [`Self::fill_gaps`] fills it. It runs after every failed broadcast and receipt timeout.
+/// A broadcast nonce only becomes a gap once the node knows none of the transactions sent with it.
 ///
 /// # How it works
 ///
-/// - **First call for an address**: Fetches the nonce using `.pending()`, which includes
-///   transactions in the mempool, not just confirmed transactions.
-/// - **Subsequent calls**: Increments the cached nonce locally without querying the RPC.
-/// - **Per-address tracking**: Each address has its own cached nonce, allowing concurrent
+/// - **First call for an address**: Loads the state kept in the [`NonceStore`], if any, and
+///   reconciles it with the chain using `.pending()`, which includes transactions in the mempool.
+///   This prevents "nonce too low" errors when the application restarts while transactions are
+///   still pending.
+/// - **Subsequent calls**: Allocates nonces locally without querying the RPC.
+/// - **Per-address tracking**: Each address has its own state, allowing concurrent
 ///   transaction submission from multiple addresses.
 ///
 /// # Thread Safety
 ///
-/// The nonce cache is shared across all clones using `Arc<DashMap>`, ensuring that concurrent
-/// requests see consistent nonce values. Each address's nonce is protected by its own `Mutex`
+/// The state is shared across all clones using `Arc<DashMap>`, ensuring that concurrent
+/// requests see consistent nonce values. Each address's state is protected by its own `Mutex`
 /// to prevent race conditions during allocation.
-/// ```
 #[derive(Clone, Debug, Default)]
 pub struct PendingNonceManager {
-    /// Cache of nonces per address. Each address has its own mutex-protected nonce value.
-    nonces: Arc<DashMap<Address, Arc<Mutex<u64>>>>,
+    /// Nonce state per address, `None` until initialized from the store and the chain.
+    nonces: Arc<DashMap<Address, Arc<Mutex<Option<SignerNonceState>>>>>,
+    /// Where the state is kept across restarts. Kept in memory only if `None`.
+    store: Option<Arc<dyn NonceStore>>,
 }
 
 #[async_trait]
@@ -40,45 +56,466 @@ impl NonceManager for PendingNonceManager {
         P: Provider<N>,
         N: alloy_network::Network,
     {
-        // Use `u64::MAX` as a sentinel value to indicate that the nonce has not been fetched yet.
-        const NONE: u64 = u64::MAX;
-
-        // Locks dashmap internally for a short duration to clone the `Arc`.
-        // We also don't want to hold the dashmap lock through the await point below.
-        let nonce = {
-            let rm = self
-                .nonces
-                .entry(address)
-                .or_insert_with(|| Arc::new(Mutex::new(NONE)));
-            Arc::clone(rm.value())
-        };
-
-        let mut nonce = nonce.lock().await;
-        let new_nonce = if *nonce == NONE {
-            // Initialize the nonce if we haven't seen this account before.
+        let state = self.state(address);
+        let mut state = state.lock().await;
+        if state.is_none() {
             tracing::trace!(%address, "fetching nonce");
-            provider.get_transaction_count(address).pending().await?
-        } else {
-            tracing::trace!(%address, current_nonce = *nonce, "incrementing nonce");
-            *nonce + 1
-        };
-        *nonce = new_nonce;
-        Ok(new_nonce)
+            let confirmed = provider.get_transaction_count(address).latest().await?;
+            let pending = provider.get_transaction_count(address).pending().await?;
+            let stored = self.load(address).await;
+            *state = Some(SignerNonceState::reconcile(stored, confirmed, pending));
+        }
+        let state = state.as_mut().expect("nonce state is initialized above");
+        // Not saved: allocated nonces are released on restart anyway, unless the node knows them
+        let nonce = state.allocate();
+        tracing::trace!(%address, nonce, "allocated nonce");
+        Ok(nonce)
     }
 }
 
 impl PendingNonceManager {
-    /// Resets the cached nonce for a given address, forcing a fresh query on next use.
+    /// Creates a nonce manager that keeps its state in `store` across restarts.
+    pub fn with_store(store: Arc<dyn NonceStore>) -> Self {
+        Self {
+            nonces: Arc::default(),
+            store: Some(store),
+        }
+    }
+
+    /// Records that the transaction `tx_hash` with `nonce` was accepted by the node,
+    /// or that the node knows a transaction with `nonce` if its hash is unknown.
     ///
-    /// This should be called when a transaction fails, as we cannot be certain of the
-    /// actual on-chain state (the transaction may or may not have reached the mempool).
-    /// By resetting to the sentinel value, the next call to `get_next_nonce` will query
-    /// the RPC provider using `.pending()`, which includes mempool transactions.
+    /// Transactions replacing an earlier one with the same nonce are recorded as well.
+    pub async fn mark_broadcast(&self, address: Address, nonce: u64, tx_hash: Option<TxHash>) {
+        self.update(address, |state| state.mark_broadcast(nonce, tx_hash))
+            .await;
+    }
+
+    /// Gives `nonce` back after its transaction failed to broadcast, to be reused or filled.
+    pub async fn release(&self, address: Address, nonce: u64) {
+        self.update(address, |state| state.release(nonce)).await;
+        tracing::debug!(%address, nonce, "released nonce after failed broadcast");
+    }
+
+    /// Tells about a transaction with `nonce` that failed to broadcast: the nonce is released,
+    /// unless the node knows another transaction with it.
+    pub async fn broadcast_failed(&self, address: Address, nonce: u64, error: &TransportError) {
+        if is_nonce_used(error) {
+            self.mark_broadcast(address, nonce, None).await;
+        } else {
+            self.release(address, nonce).await;
+        }
+    }
+
+    /// Records that every nonce below `confirmed`, the confirmed transaction count, is used.
+    pub async fn mark_confirmed(&self, address: Address, confirmed: u64) {
+        self.update(address, |state| state.mark_confirmed(confirmed))
+            .await;
+    }
+
+    /// Resets the nonce state of `address`, to be loaded and reconciled with the chain again on next use.
+    #[deprecated(
+        note = "nonces are tracked through their lifecycle, see `mark_broadcast` and `release`"
+    )]
+    #[allow(dead_code)] // Public for consumption by downstream crates.
     pub async fn reset_nonce(&self, address: Address) {
-        if let Some(nonce_lock) = self.nonces.get(&address) {
-            let mut nonce = nonce_lock.lock().await;
-            *nonce = u64::MAX; // NONE sentinel - will trigger fresh query
-            tracing::debug!(%address, "reset nonce cache, will requery on next use");
+        if let Some(state) = self
+            .nonces
+            .get(&address)
+            .map(|state| Arc::clone(state.value()))
+        {
+            *state.lock().await = None;
+            tracing::debug!(%address, "reset nonce state, will requery on next use");
+        }
+    }
+
+    /// Fills the gaps that block the transactions of `address`, see [`SignerNonceState::take_gaps`],
+    /// by sending `filler` with each of their nonces.
+    ///
+    /// Broadcast nonces the node may have dropped from the mempool become gaps, if it knows none
+    /// of the transactions sent with them: the lowest nonce missing from the pending transaction count,
+    /// and `timed_out`, a broadcast nonce whose receipt did not come in time.
+    pub async fn fill_gaps<P: Provider>(
+        &self,
+        provider: &P,
+        address: Address,
+        timed_out: Option<u64>,
+        filler: TransactionRequest,
+    ) -> Result<(), TransportError> {
+        let pending = provider.get_transaction_count(address).pending().await?;
+        let mut suspects = Vec::new();
+        self.update(address, |state| {
+            suspects = state.maybe_dropped(pending, timed_out);
+        })
+        .await;
+        for (nonce, tx_hashes) in suspects {
+            if !is_dropped(provider, &tx_hashes).await? {
+                continue;
+            }
+            // Unless another transaction was sent with the nonce meanwhile
+            self.update(address, |state| {
+                if state.transactions.get(&nonce) == Some(&tx_hashes) {
+                    state.mark_dropped(nonce);
+                }
+            })
+            .await;
+        }
+        let mut gaps = Vec::new();
+        self.update(address, |state| gaps = state.take_gaps()).await;
+        // Every taken gap must be broadcast or released, so a failure does not stop the others
+        let mut first_error = None;
+        for nonce in gaps {
+            match provider
+                .send_transaction(filler.clone().with_nonce(nonce))
+                .await
+            {
+                Ok(pending_tx) => {
+                    self.mark_broadcast(address, nonce, Some(*pending_tx.tx_hash()))
+                        .await;
+                    tracing::warn!(signer = %address, nonce, tx = %pending_tx.tx_hash(), "Filled nonce gap");
+                }
+                Err(error) => {
+                    self.broadcast_failed(address, nonce, &error).await;
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    fn state(&self, address: Address) -> Arc<Mutex<Option<SignerNonceState>>> {
+        // Locks dashmap internally for a short duration to clone the `Arc`.
+        // We also don't want to hold the dashmap lock through the await points.
+        let rm = self.nonces.entry(address).or_default();
+        Arc::clone(rm.value())
+    }
+
+    /// Applies `f` to the state of `address`, if it is initialized, and saves it.
+    async fn update(&self, address: Address, f: impl FnOnce(&mut SignerNonceState)) {
+        let state = self.state(address);
+        let mut state = state.lock().await;
+        if let Some(state) = state.as_mut() {
+            f(state);
+            self.save(address, state).await;
+        }
+    }
+
+    async fn load(&self, address: Address) -> Option<SignerNonceState> {
+        let store = self.store.as_ref()?;
+        match store.load(address).await {
+            Ok(state) => state,
+            Err(error) => {
+                tracing::warn!(%address, %error, "Failed to load nonce state, starting afresh");
+                None
+            }
+        }
+    }
+
+    async fn save(&self, address: Address, state: &SignerNonceState) {
+        if let Some(store) = &self.store
+            && let Err(error) = store.save(address, state).await
+        {
+            tracing::warn!(%address, %error, "Failed to save nonce state");
+        }
+    }
+}
+
+/// Nonces of a signer, by stage of their lifecycle.
+///
+/// Every nonce below `next` and at or above `confirmed` is either allocated, broadcast, released,
+/// or was used before the state was initialized.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerNonceState {
+    /// Every nonce below is confirmed on chain.
+    pub confirmed: u64,
+    /// Next nonce to allocate, once there is no released nonce to reuse.
+    pub next: u64,
+    /// Allocated nonces whose transaction has not been broadcast yet.
+    pub allocated: BTreeSet<u64>,
+    /// Nonces whose transaction was accepted by the node, and is not confirmed yet.
+    pub broadcast: BTreeSet<u64>,
+    /// Hashes of the transactions sent with broadcast nonces, replacements included.
+    #[serde(default)]
+    pub transactions: BTreeMap<u64, Vec<TxHash>>,
+    /// Nonces given back after a failed broadcast: gaps to reuse or fill.
+    pub released: BTreeSet<u64>,
+}
+
+impl SignerNonceState {
+    /// State to start from, given the `stored` one and the `confirmed` and `pending` transaction counts.
+    ///
+    /// Nonces allocated when the state was saved may never have been broadcast, so they become
+    /// gaps unless the node knows them already.
+    pub fn reconcile(stored: Option<Self>, confirmed: u64, pending: u64) -> Self {
+        let mut state = stored.unwrap_or_default();
+        let allocated = std::mem::take(&mut state.allocated);
+        state.released.extend(allocated);
+        state.released.retain(|nonce| *nonce >= pending);
+        state.next = state.next.max(pending);
+        state.mark_confirmed(confirmed);
+        state
+    }
+
+    /// Allocates the lowest released nonce, or the next one.
+    pub fn allocate(&mut self) -> u64 {
+        let nonce = self.released.pop_first().unwrap_or_else(|| {
+            self.next += 1;
+            self.next - 1
+        });
+        self.allocated.insert(nonce);
+        nonce
+    }
+
+    pub fn mark_broadcast(&mut self, nonce: u64, tx_hash: Option<TxHash>) {
+        self.allocated.remove(&nonce);
+        self.released.remove(&nonce);
+        if nonce >= self.confirmed {
+            self.broadcast.insert(nonce);
+            if let Some(tx_hash) = tx_hash {
+                self.transactions.entry(nonce).or_default().push(tx_hash);
+            }
+        }
+    }
+
+    pub fn release(&mut self, nonce: u64) {
+        self.allocated.remove(&nonce);
+        if nonce >= self.confirmed && !self.broadcast.contains(&nonce) {
+            self.released.insert(nonce);
         }
     }
+
+    /// Releases the broadcast `nonce`, whose transactions the node no longer knows.
+    pub fn mark_dropped(&mut self, nonce: u64) {
+        self.transactions.remove(&nonce);
+        if self.broadcast.remove(&nonce) {
+            self.released.insert(nonce);
+        }
+    }
+
+    pub fn mark_confirmed(&mut self, confirmed: u64) {
+        self.confirmed = self.confirmed.max(confirmed);
+        self.next = self.next.max(self.confirmed);
+        for nonces in [&mut self.allocated, &mut self.broadcast, &mut self.released] {
+            nonces.retain(|nonce| *nonce >= confirmed);
+        }
+        self.transactions.retain(|nonce, _| *nonce >= confirmed);
+    }
+
+    /// Broadcast nonces that may have been dropped from the mempool, with the hashes of their
+    /// transactions: `pending`, the lowest nonce missing from the pending transaction count,
+    /// and `timed_out` if it is missing as well.
+    ///
+    /// Nodes behind a load balancer may disagree on the pending count, so these are only
+    /// dropped once the node knows none of their transactions. Nonces without a known
+    /// transaction hash are never considered dropped.
+    pub fn maybe_dropped(&self, pending: u64, timed_out: Option<u64>) -> Vec<(u64, Vec<TxHash>)> {
+        let nonces = BTreeSet::from_iter(
+            std::iter::once(pending).chain(timed_out.filter(|nonce| *nonce >= pending)),
+        );
+        nonces
+            .into_iter()
+            .filter(|nonce| self.broadcast.contains(nonce))
+            .filter_map(|nonce| Some((nonce, self.transactions.get(&nonce)?.clone())))
+            .collect()
+    }
+
+    /// Takes the released nonces below the highest broadcast one, which block broadcast
+    /// transactions, and marks them as allocated so that they are not handed out meanwhile.
+    pub fn take_gaps(&mut self) -> Vec<u64> {
+        let gaps: Vec<u64> = self.broadcast.last().map_or_else(Vec::new, |highest| {
+            self.released.range(..highest).copied().collect()
+        });
+        for nonce in &gaps {
+            self.released.remove(nonce);
+            self.allocated.insert(*nonce);
+        }
+        gaps
+    }
+}
+
+/// Whether the node knows none of the transactions `tx_hashes`, sent with the same nonce.
+async fn is_dropped<P: Provider>(
+    provider: &P,
+    tx_hashes: &[TxHash],
+) -> Result<bool, TransportError> {
+    for tx_hash in tx_hashes {
+        if provider.get_transaction_by_hash(*tx_hash).await?.is_some() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Whether a failed broadcast still used its nonce, as the node knows another transaction with it.
+fn is_nonce_used(error: &TransportError) -> bool {
+    let message = error.to_string().to_lowercase();
+    [
+        "nonce too low",
+        "already known",
+        "replacement transaction underpriced",
+    ]
+    .iter()
+    .any(|used| message.contains(used))
+}
+
+/// Keeps the nonce state of signers across restarts.
+///
+/// The state of a signer is loaded and saved by one task at a time.
+#[async_trait]
+pub trait NonceStore: Debug + Send + Sync {
+    async fn load(&self, address: Address) -> Result<Option<SignerNonceState>, NonceStoreError>;
+    async fn save(&self, address: Address, state: &SignerNonceState)
+    -> Result<(), NonceStoreError>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NonceStoreError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// [`NonceStore`] keeping the state of each signer in a JSON file of its own, within a directory.
+///
+/// A file is replaced on every change by a temporary file, synced to disk before it is renamed
+/// over the previous one, so that a crash leaves either the previous state or the new one.
+#[derive(Debug)]
+pub struct FileNonceStore {
+    dir: PathBuf,
+    /// Last saved state of each signer, to skip saves that change nothing.
+    saved: DashMap<Address, SignerNonceState>,
+}
+
+impl FileNonceStore {
+    /// Opens the store in the directory at `dir`, created if it does not exist.
+    pub fn open(dir: PathBuf) -> Result<Self, NonceStoreError> {
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir,
+            saved: DashMap::new(),
+        })
+    }
+
+    fn path(&self, address: Address, extension: &str) -> PathBuf {
+        self.dir.join(format!("{address}.{extension}"))
+    }
+}
+
+#[async_trait]
+impl NonceStore for FileNonceStore {
+    async fn load(&self, address: Address) -> Result<Option<SignerNonceState>, NonceStoreError> {
+        let state: SignerNonceState = match tokio::fs::read(self.path(address, "json")).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        self.saved.insert(address, state.clone());
+        Ok(Some(state))
+    }
+
+    async fn save(
+        &self,
+        address: Address,
+        state: &SignerNonceState,
+    ) -> Result<(), NonceStoreError> {
+        if self
+            .saved
+            .get(&address)
+            .is_some_and(|saved| *saved == *state)
+        {
+            return Ok(());
+        }
+        let bytes = serde_json::to_vec(state)?;
+        let tmp_path = self.path(address, "tmp");
+        let mut file = tokio::fs::File::create(&tmp_path).await?;
+        file.write_all(&bytes).await?;
+        file.sync_all().await?;
+        tokio::fs::rename(&tmp_path, self.path(address, "json")).await?;
+        // Makes the rename itself durable
+        tokio::fs::File::open(&self.dir).await?.sync_all().await?;
+        self.saved.insert(address, state.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_released_nonces_are_reused_first() {
+        let mut state = SignerNonceState::reconcile(None, 3, 5);
+        assert_eq!(state.allocate(), 5);
+        assert_eq!(state.allocate(), 6);
+        assert_eq!(state.allocate(), 7);
+        state.release(5);
+        state.mark_broadcast(6, None);
+        state.mark_broadcast(7, None);
+        assert_eq!(state.allocate(), 5);
+        assert_eq!(state.allocate(), 8);
+
+        state.release(5);
+        assert_eq!(state.take_gaps(), vec![5]);
+        assert!(state.allocated.contains(&5));
+        state.mark_broadcast(5, None);
+        state.mark_confirmed(8);
+        assert_eq!(state.broadcast, BTreeSet::new());
+        assert_eq!(state.allocated, BTreeSet::from([8]));
+    }
+
+    #[test]
+    fn test_reconcile_after_restart() {
+        let mut stored = SignerNonceState::reconcile(None, 10, 10);
+        for _ in 0..4 {
+            stored.allocate();
+        }
+        stored.mark_broadcast(10, None);
+        stored.mark_broadcast(12, None);
+        // 11 and 13 were allocated when the facilitator stopped. The node knows 10 only.
+        let state = SignerNonceState::reconcile(Some(stored), 10, 11);
+        assert_eq!(state.next, 14);
+        assert_eq!(state.released, BTreeSet::from([11, 13]));
+        assert_eq!(state.broadcast, BTreeSet::from([10, 12]));
+    }
+
+    #[test]
+    fn test_dropped_nonce_is_filled() {
+        let mut state = SignerNonceState::reconcile(None, 4, 4);
+        let tx_hash = |nonce: u64| TxHash::with_last_byte(nonce as u8);
+        for nonce in 4..7 {
+            state.allocate();
+            state.mark_broadcast(nonce, Some(tx_hash(nonce)));
+        }
+        // 4 is missing from the pending count, which alone does not make it a gap
+        assert_eq!(state.maybe_dropped(4, None), vec![(4, vec![tx_hash(4)])]);
+        assert_eq!(state.take_gaps(), Vec::<u64>::new());
+        // Once the node knows none of its transactions, 4 was dropped, and blocks 5 and 6
+        state.mark_dropped(4);
+        assert_eq!(state.take_gaps(), vec![4]);
+        state.mark_broadcast(4, Some(tx_hash(7)));
+        assert_eq!(state.transactions[&4], vec![tx_hash(7)]);
+        // The highest nonce is only released, to be reused by the next transaction
+        state.mark_dropped(6);
+        assert_eq!(state.take_gaps(), Vec::<u64>::new());
+        assert_eq!(state.allocate(), 6);
+        // Without a known transaction hash, a nonce is never considered dropped
+        state.mark_broadcast(6, None);
+        assert_eq!(state.maybe_dropped(6, Some(6)), vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_file_nonce_store_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("x402-nonces-{}", std::process::id()));
+        let address = Address::repeat_byte(1);
+        let mut state = SignerNonceState::reconcile(None, 2, 3);
+        let nonce = state.allocate();
+        state.mark_broadcast(nonce, Some(TxHash::repeat_byte(2)));
+        let store = FileNonceStore::open(dir.clone()).unwrap();
+        store.save(address, &state).await.unwrap();
+        let store = FileNonceStore::open(dir.clone()).unwrap();
+        assert_eq!(store.load(address).await.unwrap(), Some(state));
+        assert_eq!(store.load(Address::ZERO).await.unwrap(), None);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 }
@@ -0,0 +1,180 @@
+//! Cache of the token metadata needed to build EIP-712 domains.
+//!
+//! Payment requirements without `extra` leave it to the facilitator to look up the name
+//! and version of the token. These rarely change, so they are cached per asset for a TTL,
+//! up to [`TOKEN_METADATA_CACHE_CAPACITY`] assets, and fetched together with the decimals
+//! in a single Multicall3 round trip on a miss. Metadata given in the configuration is kept
+//! apart, and never expires.
+
+use alloy_primitives::Address;
+use alloy_provider::{Failure, Provider};
+use alloy_sol_types::{decode_revert_reason, sol};
+use dashmap::DashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::Instrument;
+
+use crate::util::TtlCache;
+
+/// Maximum number of fetched token metadata kept in the cache.
+pub const TOKEN_METADATA_CACHE_CAPACITY: usize = 1024;
+
+sol! {
+    #[allow(missing_docs)]
+    #[sol(rpc)]
+    interface ITokenMetadata {
+        function name() external view returns (string);
+        function version() external view returns (string);
+        function decimals() external view returns (uint8);
+    }
+}
+
+/// Name, version and decimals of a token.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    /// EIP-712 domain name.
+    pub name: String,
+    /// EIP-712 domain version.
+    pub version: String,
+    /// Decimals, if the token exposes them.
+    pub decimals: Option<u8>,
+}
+
+/// Token metadata per asset of a chain.
+#[derive(Debug, Clone)]
+pub struct TokenMetadataCache {
+    /// Metadata from the configuration.
+    pinned: Arc<DashMap<Address, TokenMetadata>>,
+    /// Fetched metadata, expiring after the TTL.
+    fetched: Arc<TtlCache<Address, TokenMetadata>>,
+}
+
+impl TokenMetadataCache {
+    /// Creates an empty cache, whose fetched entries expire after `ttl`.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            pinned: Arc::default(),
+            fetched: Arc::new(TtlCache::new(ttl, TOKEN_METADATA_CACHE_CAPACITY)),
+        }
+    }
+
+    /// Sets the metadata of `asset` for good.
+    pub fn pin(&self, asset: Address, metadata: TokenMetadata) {
+        self.pinned.insert(asset, metadata);
+    }
+
+    /// Metadata of `asset`, fetched through `provider` unless cached.
+    pub async fn get<P: Provider>(
+        &self,
+        provider: &P,
+        asset: Address,
+    ) -> Result<TokenMetadata, TokenMetadataError> {
+        if let Some(metadata) = self.pinned.get(&asset) {
+            return Ok(metadata.clone());
+        }
+        if let Some(metadata) = self.fetched.get(&asset) {
+            return Ok(metadata);
+        }
+        let metadata = fetch_token_metadata(provider, asset).await?;
+        self.fetched.insert(asset, metadata.clone());
+        Ok(metadata)
+    }
+}
+
+/// Fetches the metadata of `asset` in a single Multicall3 call.
+///
+/// Falls back to one call per field if the Multicall3 call itself fails, e.g. where Multicall3
+/// is not deployed. A failed `name` or `version` call fails the lookup, while decimals are optional.
+async fn fetch_token_metadata<P: Provider>(
+    provider: &P,
+    asset: Address,
+) -> Result<TokenMetadata, TokenMetadataError> {
+    let contract = ITokenMetadata::new(asset, provider);
+    let batched = provider
+        .multicall()
+        .add(contract.name())
+        .add(contract.version())
+        .add(contract.decimals())
+        .aggregate3()
+        .instrument(tracing::info_span!(
+            "fetch_token_metadata",
+            token_contract = %asset,
+            otel.kind = "client",
+        ))
+        .await;
+    match batched {
+        Ok((name, version, decimals)) => {
+            return Ok(TokenMetadata {
+                name: name.map_err(|failure| TokenMetadataError::call_failed("name", failure))?,
+                version: version
+                    .map_err(|failure| TokenMetadataError::call_failed("version", failure))?,
+                decimals: decimals.ok(),
+            });
+        }
+        Err(error) => {
+            tracing::debug!(token_contract = %asset, %error, "Multicall3 failed, fetching token metadata field by field");
+        }
+    }
+    let name = contract
+        .name()
+        .call()
+        .into_future()
+        .instrument(tracing::info_span!(
+            "fetch_eip712_name",
+            otel.kind = "client",
+        ))
+        .await?;
+    let version = contract
+        .version()
+        .call()
+        .into_future()
+        .instrument(tracing::info_span!(
+            "fetch_eip712_version",
+            otel.kind = "client",
+        ))
+        .await?;
+    let decimals = contract.decimals().call().await.ok();
+    Ok(TokenMetadata {
+        name,
+        version,
+        decimals,
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TokenMetadataError {
+    #[error(transparent)]
+    Contract(#[from] alloy_contract::Error),
+    #[error("{call}() call failed: {reason}")]
+    CallFailed { call: &'static str, reason: String },
+}
+
+impl TokenMetadataError {
+    fn call_failed(call: &'static str, failure: Failure) -> Self {
+        let reason = decode_revert_reason(&failure.return_data)
+            .unwrap_or_else(|| format!("reverted with {}", failure.return_data));
+        Self::CallFailed { call, reason }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_provider::ProviderBuilder;
+
+    #[tokio::test]
+    async fn test_pinned_metadata_is_served_without_rpc() {
+        // Nothing listens there: any RPC call would fail.
+        let provider = ProviderBuilder::new().connect_http("http://127.0.0.1:1".parse().unwrap());
+        let cache = TokenMetadataCache::new(Duration::ZERO);
+        let asset = Address::repeat_byte(1);
+        let metadata = TokenMetadata {
+            name: "USD Coin".to_string(),
+            version: "2".to_string(),
+            decimals: Some(6),
+        };
+        cache.pin(asset, metadata.clone());
+        assert_eq!(cache.get(&provider, asset).await.unwrap(), metadata);
+        assert!(cache.get(&provider, Address::repeat_byte(2)).await.is_err());
+    }
+}
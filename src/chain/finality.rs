@@ -0,0 +1,218 @@
+//! Settlement finality: how far a settlement transaction progresses before the facilitator responds.
+//!
+//! Each chain has a default [`Finality`], and optional bounds within which a settle request
+//! may ask for another one, see [`FinalityPolicy`]. The finality used is reported in the settle response.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::{Display, Formatter};
+
+use crate::proto::PaymentVerificationError;
+use crate::scheme::X402SchemeFacilitatorError;
+
+/// How far a settlement transaction progresses before the facilitator responds.
+///
+/// Serialized as `"submitted"`, `"processed"`, `"confirmed"`, `"finalized"`,
+/// or as the number of block confirmations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finality {
+    /// Accepted by a node. Confirmation continues in the background.
+    Submitted,
+    /// Solana `processed` commitment.
+    Processed,
+    /// Solana `confirmed` commitment.
+    Confirmed,
+    /// Solana `finalized` commitment, or the `finalized` block on EVM chains.
+    Finalized,
+    /// Number of block confirmations, on EVM chains.
+    Confirmations(u64),
+}
+
+impl Finality {
+    /// Position of the finality in the order from the weakest to the strongest.
+    ///
+    /// Only meaningful between finalities supported on the same chain.
+    fn rank(&self) -> u64 {
+        match self {
+            Finality::Submitted => 0,
+            Finality::Processed => 1,
+            Finality::Confirmed => 2,
+            Finality::Finalized => u64::MAX,
+            Finality::Confirmations(confirmations) => *confirmations,
+        }
+    }
+}
+
+impl Display for Finality {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Finality::Submitted => write!(f, "submitted"),
+            Finality::Processed => write!(f, "processed"),
+            Finality::Confirmed => write!(f, "confirmed"),
+            Finality::Finalized => write!(f, "finalized"),
+            Finality::Confirmations(confirmations) => write!(f, "{confirmations} confirmations"),
+        }
+    }
+}
+
+impl Serialize for Finality {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Finality::Confirmations(confirmations) => serializer.serialize_u64(*confirmations),
+            level => serializer.serialize_str(&level.to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Finality {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "lowercase")]
+        enum Level {
+            Submitted,
+            Processed,
+            Confirmed,
+            Finalized,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum FinalityWire {
+            Level(Level),
+            Confirmations(u64),
+        }
+
+        let finality = match FinalityWire::deserialize(deserializer)? {
+            FinalityWire::Level(Level::Submitted) => Finality::Submitted,
+            FinalityWire::Level(Level::Processed) => Finality::Processed,
+            FinalityWire::Level(Level::Confirmed) => Finality::Confirmed,
+            FinalityWire::Level(Level::Finalized) => Finality::Finalized,
+            FinalityWire::Confirmations(0) => {
+                return Err(serde::de::Error::custom(
+                    "confirmations must be at least 1, use \"submitted\" instead",
+                ));
+            }
+            FinalityWire::Confirmations(confirmations) => Finality::Confirmations(confirmations),
+        };
+        Ok(finality)
+    }
+}
+
+/// Default finality of a chain, and the bounds within which settle requests may override it.
+///
+/// Bounds that are not set default to the default finality,
+/// so overrides are rejected unless `min` or `max` is configured.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FinalityPolicy {
+    pub default: Option<Finality>,
+    pub min: Option<Finality>,
+    pub max: Option<Finality>,
+}
+
+impl FinalityPolicy {
+    /// Configured finalities, to check that the chain supports them.
+    pub fn finalities(&self) -> impl Iterator<Item = Finality> {
+        [self.default, self.min, self.max].into_iter().flatten()
+    }
+
+    /// Finality to settle with: `requested` if it is within bounds, otherwise the default.
+    ///
+    /// `fallback` is the default when the policy does not set one.
+    pub fn resolve(
+        &self,
+        requested: Option<Finality>,
+        fallback: Finality,
+    ) -> Result<Finality, FinalityError> {
+        let default = self.default.unwrap_or(fallback);
+        let Some(requested) = requested else {
+            return Ok(default);
+        };
+        let min = self.min.unwrap_or(default);
+        let max = self.max.unwrap_or(default);
+        if requested.rank() < min.rank() || requested.rank() > max.rank() {
+            return Err(FinalityError::OutOfBounds {
+                requested,
+                min,
+                max,
+            });
+        }
+        Ok(requested)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FinalityError {
+    #[error("Finality {requested} is outside of the allowed range from {min} to {max}")]
+    OutOfBounds {
+        requested: Finality,
+        min: Finality,
+        max: Finality,
+    },
+    #[error("Finality {0} is not supported on this chain")]
+    Unsupported(Finality),
+}
+
+impl From<FinalityError> for PaymentVerificationError {
+    fn from(value: FinalityError) -> Self {
+        PaymentVerificationError::InvalidFormat(value.to_string())
+    }
+}
+
+impl From<FinalityError> for X402SchemeFacilitatorError {
+    fn from(value: FinalityError) -> Self {
+        X402SchemeFacilitatorError::PaymentVerification(value.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_finality_serde() {
+        for (finality, value) in [
+            (Finality::Submitted, json!("submitted")),
+            (Finality::Finalized, json!("finalized")),
+            (Finality::Confirmations(3), json!(3)),
+        ] {
+            assert_eq!(serde_json::to_value(finality).unwrap(), value);
+            assert_eq!(serde_json::from_value::<Finality>(value).unwrap(), finality);
+        }
+        assert!(serde_json::from_value::<Finality>(json!(0)).is_err());
+        assert!(serde_json::from_value::<Finality>(json!("safe")).is_err());
+    }
+
+    #[test]
+    fn test_policy_resolve() {
+        let fixed = FinalityPolicy::default();
+        let fallback = Finality::Confirmed;
+        assert_eq!(fixed.resolve(None, fallback).unwrap(), fallback);
+        assert_eq!(fixed.resolve(Some(fallback), fallback).unwrap(), fallback);
+        assert!(fixed.resolve(Some(Finality::Finalized), fallback).is_err());
+
+        let bounded = FinalityPolicy {
+            default: Some(Finality::Confirmations(2)),
+            min: Some(Finality::Submitted),
+            max: Some(Finality::Confirmations(6)),
+        };
+        let fallback = Finality::Confirmations(1);
+        assert_eq!(
+            bounded.resolve(None, fallback).unwrap(),
+            Finality::Confirmations(2)
+        );
+        assert_eq!(
+            bounded
+                .resolve(Some(Finality::Submitted), fallback)
+                .unwrap(),
+            Finality::Submitted
+        );
+        assert!(
+            bounded
+                .resolve(Some(Finality::Confirmations(12)), fallback)
+                .is_err()
+        );
+    }
+}
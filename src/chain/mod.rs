@@ -1,8 +1,10 @@
 mod chain_id;
 pub mod eip155;
+mod finality;
 pub mod solana;
 
 pub use chain_id::*;
+pub use finality::*;
 
 use crate::config::ChainConfig;
 use std::collections::HashMap;
@@ -55,9 +57,7 @@ impl ChainProviderOps for ChainProvider {
 pub struct ChainRegistry(HashMap<ChainId, ChainProvider>);
 
 impl ChainRegistry {
-    pub async fn from_config(
-        chains: &Vec<ChainConfig>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
+    pub async fn from_config(chains: &[ChainConfig]) -> Result<Self, Box<dyn std::error::Error>> {
         let mut providers = HashMap::new();
         for chain in chains {
             let chain_provider = ChainProvider::from_config(chain).await?;
@@ -4,6 +4,7 @@ use solana_client::client_error::{ClientError, ClientErrorKind};
 use solana_client::nonblocking::pubsub_client::PubsubClient;
 use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_client::pubsub_client::PubsubClientError;
+use solana_client::rpc_client::RpcClientConfig;
 use solana_client::rpc_client::SerializableTransaction;
 use solana_client::rpc_config::{
     RpcSendTransactionConfig, RpcSignatureSubscribeConfig, RpcSimulateTransactionConfig,
@@ -11,23 +12,61 @@ use solana_client::rpc_config::{
 use solana_client::rpc_response::{RpcSignatureResult, UiTransactionError};
 use solana_commitment_config::CommitmentConfig;
 use solana_keypair::Keypair;
-use solana_pubkey::Pubkey;
+use solana_message::v0::{LoadedAddresses, MessageAddressTableLookup};
+use solana_pubkey::{Pubkey, pubkey};
 use solana_signature::Signature;
 use solana_signer::{Signer, SignerError};
 use solana_transaction::TransactionError;
 use solana_transaction::versioned::VersionedTransaction;
+use std::collections::VecDeque;
 use std::fmt::{Debug, Display, Formatter};
 use std::str::FromStr;
 use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
-use crate::chain::{ChainId, ChainProviderOps};
-use crate::config::SolanaChainConfig;
+use crate::chain::{ChainId, ChainProviderOps, Finality, FinalityError, FinalityPolicy};
+use crate::config::{DynamicComputeUnitPriceConfig, SolanaChainConfig};
 use crate::networks::KnownNetworkSolana;
 use crate::scheme::X402SchemeFacilitatorError;
+use crate::util::TtlCache;
+
+pub mod fallback;
+
+use fallback::FallbackRpcSender;
 
 pub const SOLANA_NAMESPACE: &str = "solana";
 
+/// Default time to wait for a sent transaction to be confirmed.
+pub const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(90);
+/// Default interval between rebroadcasts of a sent transaction.
+pub const DEFAULT_REBROADCAST_INTERVAL: Duration = Duration::from_secs(2);
+/// How often to poll the signature status when no pubsub endpoint is configured.
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long a fetched mint account is reused before it is fetched again, unless configured.
+pub const DEFAULT_MINT_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Maximum number of mint accounts kept in the cache.
+pub const MINT_CACHE_CAPACITY: usize = 1024;
+
+pub const SYSTEM_PROGRAM_PUBKEY: Pubkey = pubkey!("11111111111111111111111111111111");
+pub const ATA_PROGRAM_PUBKEY: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+
+/// Discriminator of the System Program `AdvanceNonceAccount` instruction (bincode-encoded `u32`).
+pub const SYSTEM_ADVANCE_NONCE_DISCRIMINATOR: [u8; 4] = 4u32.to_le_bytes();
+/// RecentBlockhashes sysvar, passed to `AdvanceNonceAccount`.
+pub const RECENT_BLOCKHASHES_SYSVAR_PUBKEY: Pubkey =
+    pubkey!("SysvarRecentB1ockHashes11111111111111111111");
+
+/// Address Lookup Table program, owner of lookup table accounts.
+pub const ADDRESS_LOOKUP_TABLE_PROGRAM_PUBKEY: Pubkey =
+    pubkey!("AddressLookupTab1e1111111111111111111111111");
+
+/// Size of the header of a lookup table account: the bincode-encoded `ProgramState::LookupTable`
+/// discriminant and metadata, padded. The stored addresses follow it.
+const LOOKUP_TABLE_META_SIZE: usize = 56;
+
 /// A Solana chain reference consisting of 32 ASCII characters.
 /// The genesis hash is the first 32 characters of the base58-encoded genesis block hash.
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
@@ -170,6 +209,14 @@ pub enum SolanaChainProviderError {
     Transport(Box<ClientErrorKind>),
     #[error(transparent)]
     PubsubTransport(#[from] PubsubClientError),
+    #[error("Invalid address lookup table {0}")]
+    InvalidLookupTable(Pubkey),
+    #[error("Transaction {0} expired: its blockhash is no longer valid")]
+    BlockhashExpired(Signature),
+    #[error("Timed out waiting for transaction {0} to be confirmed")]
+    ConfirmationTimeout(Signature),
+    #[error(transparent)]
+    Finality(#[from] FinalityError),
 }
 
 impl From<ClientError> for SolanaChainProviderError {
@@ -184,19 +231,35 @@ impl From<SolanaChainProviderError> for X402SchemeFacilitatorError {
     }
 }
 
+#[derive(Clone)]
 pub struct SolanaChainProvider {
     chain: SolanaChainReference,
-    keypair: Arc<Keypair>,
+    /// Fee payer keypairs available for signing.
+    keypairs: Arc<Vec<Keypair>>,
+    /// Current position in round-robin fee payer rotation.
+    fee_payer_cursor: Arc<AtomicUsize>,
     rpc_client: Arc<RpcClient>,
     pubsub_client: Arc<Option<PubsubClient>>,
     max_compute_unit_limit: u32,
     max_compute_unit_price: u64,
+    /// Current compute unit price ceiling, at most `max_compute_unit_price`.
+    ///
+    /// Stays at `max_compute_unit_price` unless refreshed from recent prioritization fees.
+    compute_unit_price_ceiling: Arc<AtomicU64>,
+    /// How long to wait for a sent transaction to be confirmed.
+    confirmation_timeout: Duration,
+    /// How often to resend a transaction until it is confirmed.
+    rebroadcast_interval: Duration,
+    /// Settlement finality, and the bounds of per-request overrides.
+    finality: FinalityPolicy,
+    /// Recently fetched mint accounts by address.
+    mint_cache: Arc<TtlCache<Pubkey, Account>>,
 }
 
 impl Debug for SolanaChainProvider {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SolanaChainProvider")
-            .field("pubkey", &self.keypair.pubkey())
+            .field("fee_payers", &self.fee_payers())
             .field("chain", &self.chain)
             .field("rpc_url", &self.rpc_client.url())
             .finish()
@@ -207,38 +270,64 @@ impl SolanaChainProvider {
     pub async fn from_config(
         config: &SolanaChainConfig,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let rpc_url = config.rpc();
+        let rpc_sender = FallbackRpcSender::new(config.rpc(), config.broadcast_transactions());
+        let rpc_client = RpcClient::new_sender(rpc_sender, RpcClientConfig::default());
         let pubsub_url = config.pubsub().clone().map(|url| url.to_string());
-        let keypair = Keypair::from_base58_string(&config.signer().to_string());
+        let keypairs = config
+            .signers()
+            .iter()
+            .map(|signer| Keypair::from_base58_string(&signer.to_string()))
+            .collect::<Vec<_>>();
+        if keypairs.is_empty() {
+            return Err("at least one signer should be provided".into());
+        }
         let max_compute_unit_limit = config.max_compute_unit_limit();
         let max_compute_unit_price = config.max_compute_unit_price();
         let chain = config.chain_reference();
         let provider = Self::new(
-            keypair,
-            rpc_url.to_string(),
+            keypairs,
+            rpc_client,
             pubsub_url,
             chain,
             max_compute_unit_limit,
             max_compute_unit_price,
         )
-        .await?;
+        .await?
+        .with_confirmation(
+            Duration::from_secs(config.confirmation_timeout_secs()),
+            Duration::from_secs(config.rebroadcast_interval_secs()),
+        );
+        let provider = provider
+            .with_mint_cache_ttl(Duration::from_secs(config.mint_cache_ttl_secs()))
+            .with_finality(config.finality())?;
+        let provider = match config.dynamic_compute_unit_price() {
+            Some(dynamic) => {
+                if dynamic.percentile > 100 {
+                    return Err(
+                        "dynamic_compute_unit_price.percentile should be at most 100".into(),
+                    );
+                }
+                provider.with_dynamic_compute_unit_price(dynamic)
+            }
+            None => provider,
+        };
         Ok(provider)
     }
 
     pub async fn new(
-        keypair: Keypair,
-        rpc_url: String,
+        keypairs: Vec<Keypair>,
+        rpc_client: RpcClient,
         pubsub_url: Option<String>,
         chain: SolanaChainReference,
         max_compute_unit_limit: u32,
         max_compute_unit_price: u64,
     ) -> Result<Self, PubsubClientError> {
         {
-            let signer_addresses = vec![keypair.pubkey()];
+            let signer_addresses = keypairs.iter().map(|k| k.pubkey()).collect::<Vec<_>>();
             let chain_id: ChainId = chain.into();
             tracing::info!(
                 chain = %chain_id,
-                rpc = rpc_url,
+                rpc = rpc_client.url(),
                 pubsub = ?pubsub_url,
                 signers = ?signer_addresses,
                 max_compute_unit_limit,
@@ -246,7 +335,6 @@ impl SolanaChainProvider {
                 "Initialized Solana provider"
             );
         }
-        let rpc_client = RpcClient::new(rpc_url);
         let pubsub_client = if let Some(pubsub_url) = pubsub_url {
             let client = PubsubClient::new(pubsub_url).await?;
             Some(client)
@@ -254,54 +342,169 @@ impl SolanaChainProvider {
             None
         };
         Ok(Self {
-            keypair: Arc::new(keypair),
+            keypairs: Arc::new(keypairs),
+            fee_payer_cursor: Arc::new(AtomicUsize::new(0)),
             chain,
             rpc_client: Arc::new(rpc_client),
             pubsub_client: Arc::new(pubsub_client),
             max_compute_unit_limit,
             max_compute_unit_price,
+            compute_unit_price_ceiling: Arc::new(AtomicU64::new(max_compute_unit_price)),
+            confirmation_timeout: DEFAULT_CONFIRMATION_TIMEOUT,
+            rebroadcast_interval: DEFAULT_REBROADCAST_INTERVAL,
+            finality: FinalityPolicy::default(),
+            mint_cache: Arc::new(TtlCache::new(DEFAULT_MINT_CACHE_TTL, MINT_CACHE_CAPACITY)),
         })
     }
 
-    pub fn fee_payer(&self) -> Address {
-        Address(self.keypair.pubkey())
+    /// Sets how long [`Self::send_and_confirm`] waits for confirmation,
+    /// and how often it resends the transaction meanwhile.
+    pub fn with_confirmation(
+        mut self,
+        confirmation_timeout: Duration,
+        rebroadcast_interval: Duration,
+    ) -> Self {
+        self.confirmation_timeout = confirmation_timeout;
+        self.rebroadcast_interval = rebroadcast_interval;
+        self
+    }
+
+    /// Sets how long a fetched mint account is reused before it is fetched again.
+    pub fn with_mint_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.mint_cache = Arc::new(TtlCache::new(ttl, MINT_CACHE_CAPACITY));
+        self
+    }
+
+    /// Sets the settlement finality, and the bounds of per-request overrides.
+    pub fn with_finality(mut self, finality: FinalityPolicy) -> Result<Self, FinalityError> {
+        for finality in finality.finalities() {
+            commitment_config(finality)?;
+        }
+        self.finality = finality;
+        Ok(self)
+    }
+
+    /// Derives the compute unit price ceiling from recent prioritization fees,
+    /// refreshed in the background for as long as the provider lives.
+    pub fn with_dynamic_compute_unit_price(self, config: &DynamicComputeUnitPriceConfig) -> Self {
+        let rpc_client = self.rpc_client.clone();
+        let ceiling = Arc::downgrade(&self.compute_unit_price_ceiling);
+        let accounts = config
+            .accounts
+            .iter()
+            .map(|a| *a.pubkey())
+            .collect::<Vec<_>>();
+        let config = config.clone();
+        let max_compute_unit_price = self.max_compute_unit_price;
+        let chain_id: ChainId = self.chain.into();
+        tokio::spawn(async move {
+            let refresh_interval = Duration::from_secs(config.refresh_interval_secs.max(1));
+            let mut window = CeilingWindow::new(Duration::from_secs(config.window_secs));
+            let mut interval = tokio::time::interval(refresh_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let Some(ceiling) = ceiling.upgrade() else {
+                    break;
+                };
+                let fees = match rpc_client.get_recent_prioritization_fees(&accounts).await {
+                    Ok(fees) => fees,
+                    Err(error) => {
+                        tracing::warn!(chain = %chain_id, %error, "Failed to fetch recent prioritization fees");
+                        continue;
+                    }
+                };
+                let fees = fees
+                    .iter()
+                    .map(|fee| fee.prioritization_fee)
+                    .collect::<Vec<_>>();
+                if let Some(value) = compute_unit_price_ceiling(
+                    &fees,
+                    config.percentile,
+                    config.multiplier,
+                    config.min_compute_unit_price,
+                    max_compute_unit_price,
+                ) {
+                    let value = window.push(Instant::now(), value);
+                    ceiling.store(value, Ordering::Relaxed);
+                    tracing::debug!(chain = %chain_id, ceiling = value, "Refreshed compute unit price ceiling");
+                }
+            }
+        });
+        self
+    }
+
+    /// All fee payers to advertise to clients, starting from the next one in round-robin order.
+    ///
+    /// Any configured fee payer is accepted during verification. Clients usually pick
+    /// the first one advertised, so rotating the order spreads transactions across all keys.
+    pub fn advertised_fee_payers(&self) -> Vec<Address> {
+        debug_assert!(!self.keypairs.is_empty());
+        let start = if self.keypairs.len() == 1 {
+            0
+        } else {
+            self.fee_payer_cursor.fetch_add(1, Ordering::Relaxed) % self.keypairs.len()
+        };
+        (0..self.keypairs.len())
+            .map(|offset| Address(self.keypairs[(start + offset) % self.keypairs.len()].pubkey()))
+            .collect()
+    }
+
+    /// All fee payer public keys managed by this provider.
+    pub fn fee_payers(&self) -> Vec<Pubkey> {
+        self.keypairs.iter().map(|k| k.pubkey()).collect()
+    }
+
+    /// Whether `pubkey` belongs to one of the fee payers managed by this provider.
+    pub fn is_fee_payer(&self, pubkey: &Pubkey) -> bool {
+        self.keypairs.iter().any(|k| k.pubkey() == *pubkey)
     }
 
     pub fn max_compute_unit_limit(&self) -> u32 {
         self.max_compute_unit_limit
     }
 
+    /// Highest compute unit price currently accepted in a payment transaction.
     pub fn max_compute_unit_price(&self) -> u64 {
-        self.max_compute_unit_price
-    }
-
-    pub fn pubkey(&self) -> Pubkey {
-        self.keypair.pubkey()
+        self.compute_unit_price_ceiling.load(Ordering::Relaxed)
     }
 
+    /// Sign the transaction with every managed keypair that is among its required signers.
+    ///
+    /// Typically this is just the fee payer the client picked from the advertised set.
     pub fn sign(
         &self,
         tx: VersionedTransaction,
     ) -> Result<VersionedTransaction, SolanaChainProviderError> {
         let mut tx = tx.clone();
         let msg_bytes = tx.message.serialize();
-        let signature = self.keypair.try_sign_message(msg_bytes.as_slice())?;
         // Required signatures are the first N account keys
         let num_required = tx.message.header().num_required_signatures as usize;
         let static_keys = tx.message.static_account_keys();
-        // Find signer’s position
-        let pos = static_keys[..num_required]
+        // Find signers’ positions
+        let positions = static_keys[..num_required.min(static_keys.len())]
             .iter()
-            .position(|k| *k == self.pubkey())
-            .ok_or(SolanaChainProviderError::InvalidTransaction(
+            .enumerate()
+            .filter_map(|(pos, key)| {
+                self.keypairs
+                    .iter()
+                    .find(|k| k.pubkey() == *key)
+                    .map(|keypair| (pos, keypair))
+            })
+            .collect::<Vec<_>>();
+        if positions.is_empty() {
+            return Err(SolanaChainProviderError::InvalidTransaction(
                 UiTransactionError::from(TransactionError::InvalidAccountIndex),
-            ))?;
-        // Ensure signature vector is large enough, then place the signature
+            ));
+        }
+        // Ensure signature vector is large enough, then place the signatures
         if tx.signatures.len() < num_required {
             tx.signatures.resize(num_required, Signature::default());
         }
-        // tx.signatures.push(signature);
-        tx.signatures[pos] = signature;
+        for (pos, keypair) in positions {
+            let signature = keypair.try_sign_message(msg_bytes.as_slice())?;
+            tx.signatures[pos] = signature;
+        }
         Ok(tx)
     }
 
@@ -328,6 +531,56 @@ impl SolanaChainProvider {
         Ok(accounts)
     }
 
+    /// Resolves the addresses a v0 message loads from address lookup tables,
+    /// in the order the message refers to them.
+    pub async fn load_addresses(
+        &self,
+        lookups: &[MessageAddressTableLookup],
+    ) -> Result<LoadedAddresses, SolanaChainProviderError> {
+        if lookups.is_empty() {
+            return Ok(LoadedAddresses::default());
+        }
+        let table_keys = lookups
+            .iter()
+            .map(|lookup| lookup.account_key)
+            .collect::<Vec<_>>();
+        let tables = self.get_multiple_accounts(&table_keys).await?;
+        let mut loaded = LoadedAddresses::default();
+        for (lookup, table) in lookups.iter().zip(tables) {
+            let invalid = || SolanaChainProviderError::InvalidLookupTable(lookup.account_key);
+            let table = table
+                .filter(|table| table.owner == ADDRESS_LOOKUP_TABLE_PROGRAM_PUBKEY)
+                .ok_or_else(invalid)?;
+            let addresses = lookup_table_addresses(&table.data).ok_or_else(invalid)?;
+            for (indexes, resolved) in [
+                (&lookup.writable_indexes, &mut loaded.writable),
+                (&lookup.readonly_indexes, &mut loaded.readonly),
+            ] {
+                for index in indexes {
+                    let address = addresses.get(*index as usize).ok_or_else(invalid)?;
+                    resolved.push(*address);
+                }
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Mint account fetched less than the mint cache TTL ago, if any.
+    pub fn cached_mint(&self, mint: &Pubkey) -> Option<Account> {
+        self.mint_cache.get(mint)
+    }
+
+    /// Stores a freshly fetched mint account.
+    pub fn cache_mint(&self, mint: Pubkey, account: Account) {
+        self.mint_cache.insert(mint, account);
+    }
+
+    /// Current epoch, which selects the Token-2022 transfer fee in effect.
+    pub async fn get_epoch(&self) -> Result<u64, SolanaChainProviderError> {
+        let epoch_info = self.rpc_client.get_epoch_info().await?;
+        Ok(epoch_info.epoch)
+    }
+
     pub async fn send(
         &self,
         tx: &VersionedTransaction,
@@ -345,12 +598,63 @@ impl SolanaChainProvider {
         Ok(signature)
     }
 
+    /// Finality to settle with, given the one `requested` by a settle request.
+    ///
+    /// Defaults to the scheme `commitment` when the chain policy does not set one.
+    pub fn settlement_finality(
+        &self,
+        requested: Option<Finality>,
+        commitment: Finality,
+    ) -> Result<Finality, FinalityError> {
+        let finality = self.finality.resolve(requested, commitment)?;
+        commitment_config(finality)?;
+        Ok(finality)
+    }
+
+    /// Sends the transaction and waits for it to reach `finality`.
+    ///
+    /// With [`Finality::Submitted`], returns as soon as the transaction is sent,
+    /// and keeps confirming it in the background.
+    pub async fn settle(
+        &self,
+        tx: &VersionedTransaction,
+        finality: Finality,
+    ) -> Result<Signature, SolanaChainProviderError> {
+        match commitment_config(finality)? {
+            Some(commitment_config) => self.send_and_confirm(tx, commitment_config).await,
+            None => {
+                let tx_sig = self.send(tx).await?;
+                let provider = self.clone();
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    match provider
+                        .send_and_confirm(&tx, CommitmentConfig::confirmed())
+                        .await
+                    {
+                        Ok(_) => {
+                            tracing::info!(signature = %tx_sig, "Submitted transaction confirmed")
+                        }
+                        Err(error) => {
+                            tracing::warn!(signature = %tx_sig, %error, "Submitted transaction failed to confirm")
+                        }
+                    }
+                });
+                Ok(tx_sig)
+            }
+        }
+    }
+
+    /// Sends the transaction and waits for it to reach `commitment_config`.
+    ///
+    /// The same signed transaction is resent every rebroadcast interval, until it is confirmed,
+    /// its blockhash expires ([`SolanaChainProviderError::BlockhashExpired`]),
+    /// or the confirmation timeout elapses ([`SolanaChainProviderError::ConfirmationTimeout`]).
     pub async fn send_and_confirm(
         &self,
         tx: &VersionedTransaction,
         commitment_config: CommitmentConfig,
     ) -> Result<Signature, SolanaChainProviderError> {
-        let tx_sig = tx.get_signature();
+        let tx_sig = *tx.get_signature();
 
         use futures_util::stream::StreamExt;
 
@@ -360,49 +664,123 @@ impl SolanaChainProvider {
                 enable_received_notification: None,
             };
             let (mut stream, unsubscribe) = pubsub_client
-                .signature_subscribe(tx_sig, Some(config))
+                .signature_subscribe(&tx_sig, Some(config))
                 .await?;
             if let Err(e) = self.send(tx).await {
                 tracing::error!(error = %e, "Failed to send transaction");
                 unsubscribe().await;
                 return Err(e);
             }
-            if let Some(response) = stream.next().await {
+            let notification = async {
+                let response = stream.next().await.ok_or_else(|| {
+                    SolanaChainProviderError::Transport(Box::new(ClientErrorKind::Custom(
+                        "Can not get response from signatureSubscribe".to_string(),
+                    )))
+                })?;
                 let error = if let RpcSignatureResult::ProcessedSignature(r) = response.value {
                     r.err
                 } else {
                     None
                 };
                 match error {
-                    None => Ok(*tx_sig),
+                    None => Ok(tx_sig),
                     Some(error) => Err(SolanaChainProviderError::InvalidTransaction(error)),
                 }
-            } else {
-                Err(SolanaChainProviderError::Transport(Box::new(
-                    ClientErrorKind::Custom(
-                        "Can not get response from signatureSubscribe".to_string(),
-                    ),
-                )))
-            }
+            };
+            let result = self.confirm_with_rebroadcast(tx, notification).await;
+            unsubscribe().await;
+            result
         } else {
             self.send(tx).await?;
+            let polling = async {
+                loop {
+                    if let Some(result) = self.signature_status(&tx_sig, commitment_config).await? {
+                        return result;
+                    }
+                    tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+                }
+            };
+            self.confirm_with_rebroadcast(tx, polling).await
+        }
+    }
+
+    /// Waits for `confirmation` while resending `tx`, bounded by the confirmation timeout.
+    ///
+    /// Transactions using a durable nonce do not expire with their blockhash,
+    /// so they are resent until confirmed or timed out.
+    ///
+    /// Expiry is checked at `processed`, the latest view of the chain, rather than at the
+    /// settlement commitment: at `finalized`, a blockhash fetched at `confirmed` may not be
+    /// known yet. A transaction that landed before its blockhash expired is then left to reach
+    /// the settlement commitment.
+    async fn confirm_with_rebroadcast<F>(
+        &self,
+        tx: &VersionedTransaction,
+        confirmation: F,
+    ) -> Result<Signature, SolanaChainProviderError>
+    where
+        F: Future<Output = Result<Signature, SolanaChainProviderError>>,
+    {
+        let tx_sig = *tx.get_signature();
+        let expires = !uses_durable_nonce(tx);
+        let rebroadcast = async {
             loop {
-                let confirmed = self
-                    .rpc_client
-                    .confirm_transaction_with_commitment(tx_sig, commitment_config)
-                    .await?;
-                if confirmed.value {
-                    return Ok(*tx_sig);
+                tokio::time::sleep(self.rebroadcast_interval).await;
+                let is_blockhash_valid = !expires
+                    || self
+                        .rpc_client
+                        .is_blockhash_valid(
+                            tx.message.recent_blockhash(),
+                            CommitmentConfig::processed(),
+                        )
+                        .await?;
+                if !is_blockhash_valid {
+                    // The transaction may have landed right before its blockhash expired
+                    let status = self
+                        .signature_status(&tx_sig, CommitmentConfig::processed())
+                        .await?;
+                    return match status {
+                        Some(Ok(_)) => std::future::pending().await,
+                        Some(Err(e)) => Err(e),
+                        None => Err(SolanaChainProviderError::BlockhashExpired(tx_sig)),
+                    };
+                }
+                if let Err(e) = self.send(tx).await {
+                    tracing::warn!(error = %e, signature = %tx_sig, "Failed to rebroadcast transaction");
                 }
-                tokio::time::sleep(Duration::from_millis(200)).await;
             }
-        }
+        };
+        let confirmed = async {
+            tokio::select! {
+                result = confirmation => result,
+                result = rebroadcast => result,
+            }
+        };
+        tokio::time::timeout(self.confirmation_timeout, confirmed)
+            .await
+            .map_err(|_| SolanaChainProviderError::ConfirmationTimeout(tx_sig))?
+    }
+
+    /// Outcome of the transaction, if it has reached `commitment_config`.
+    async fn signature_status(
+        &self,
+        signature: &Signature,
+        commitment_config: CommitmentConfig,
+    ) -> Result<Option<Result<Signature, SolanaChainProviderError>>, SolanaChainProviderError> {
+        let status = self
+            .rpc_client
+            .get_signature_status_with_commitment(signature, commitment_config)
+            .await?;
+        Ok(status.map(|result| match result {
+            Ok(()) => Ok(*signature),
+            Err(error) => Err(SolanaChainProviderError::InvalidTransaction(error.into())),
+        }))
     }
 }
 
 impl ChainProviderOps for SolanaChainProvider {
     fn signer_addresses(&self) -> Vec<String> {
-        vec![self.fee_payer().to_string()]
+        self.fee_payers().iter().map(|k| k.to_string()).collect()
     }
 
     fn chain_id(&self) -> ChainId {
@@ -478,3 +856,247 @@ impl FromStr for Address {
         Ok(Self(pubkey))
     }
 }
+
+/// Whether the transaction uses a durable nonce, advanced by its first instruction,
+/// instead of a recent blockhash.
+fn uses_durable_nonce(tx: &VersionedTransaction) -> bool {
+    let Some(instruction) = tx.message.instructions().first() else {
+        return false;
+    };
+    let program_id = tx
+        .message
+        .static_account_keys()
+        .get(instruction.program_id_index as usize);
+    program_id == Some(&SYSTEM_PROGRAM_PUBKEY)
+        && instruction
+            .data
+            .starts_with(&SYSTEM_ADVANCE_NONCE_DISCRIMINATOR)
+}
+
+/// Addresses stored in an initialized lookup table account.
+fn lookup_table_addresses(data: &[u8]) -> Option<Vec<Pubkey>> {
+    // ProgramState::LookupTable, bincode-encoded as a little-endian u32
+    if data.get(..4)? != 1u32.to_le_bytes() {
+        return None;
+    }
+    let addresses = data.get(LOOKUP_TABLE_META_SIZE..)?;
+    if addresses.len() % 32 != 0 {
+        return None;
+    }
+    addresses
+        .chunks_exact(32)
+        .map(|address| Pubkey::try_from(address).ok())
+        .collect()
+}
+
+/// Commitment to wait for to reach `finality`, or `None` for a transaction that is only submitted.
+fn commitment_config(finality: Finality) -> Result<Option<CommitmentConfig>, FinalityError> {
+    match finality {
+        Finality::Submitted => Ok(None),
+        Finality::Processed => Ok(Some(CommitmentConfig::processed())),
+        Finality::Confirmed => Ok(Some(CommitmentConfig::confirmed())),
+        Finality::Finalized => Ok(Some(CommitmentConfig::finalized())),
+        Finality::Confirmations(_) => Err(FinalityError::Unsupported(finality)),
+    }
+}
+
+/// Compute unit price ceilings derived over a sliding window.
+///
+/// A client may build its transaction with the ceiling advertised a moment ago, so the
+/// effective ceiling is the highest one derived within the window: it rises right away,
+/// but only falls once the higher ceilings leave the window.
+struct CeilingWindow {
+    window: Duration,
+    /// Ceilings with the time they were derived, oldest first.
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl CeilingWindow {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Adds the ceiling derived at `now`, returning the effective ceiling.
+    fn push(&mut self, now: Instant, ceiling: u64) -> u64 {
+        while let Some((derived_at, _)) = self.samples.front()
+            && now.saturating_duration_since(*derived_at) > self.window
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, ceiling));
+        self.samples
+            .iter()
+            .map(|(_, ceiling)| *ceiling)
+            .max()
+            .unwrap_or(ceiling)
+    }
+}
+
+/// Compute unit price ceiling derived from recent prioritization `fees`:
+/// their `percentile`, times `multiplier`, clamped between `min` and `max`.
+///
+/// Returns `None` without any fees to derive it from.
+fn compute_unit_price_ceiling(
+    fees: &[u64],
+    percentile: u8,
+    multiplier: f64,
+    min: u64,
+    max: u64,
+) -> Option<u64> {
+    if fees.is_empty() {
+        return None;
+    }
+    let mut fees = fees.to_vec();
+    fees.sort_unstable();
+    let rank = (fees.len() - 1) * usize::from(percentile.min(100)) / 100;
+    let ceiling = (fees[rank] as f64 * multiplier).ceil() as u64;
+    Some(ceiling.max(min).min(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_table_addresses() {
+        let addresses = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut data = vec![0u8; LOOKUP_TABLE_META_SIZE];
+        data[..4].copy_from_slice(&1u32.to_le_bytes());
+        for address in addresses {
+            data.extend_from_slice(address.as_ref());
+        }
+        assert_eq!(lookup_table_addresses(&data), Some(addresses.to_vec()));
+
+        // Uninitialized table
+        data[..4].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(lookup_table_addresses(&data), None);
+    }
+
+    #[test]
+    fn test_uses_durable_nonce() {
+        use crate::scheme::v1_solana_exact::client::{
+            advance_nonce_instruction, system_transfer_instruction,
+        };
+        use solana_message::v0::Message as MessageV0;
+        use solana_message::{Hash, VersionedMessage};
+
+        let payer = Pubkey::new_unique();
+        let transfer = system_transfer_instruction(&payer, &Pubkey::new_unique(), 1);
+        let transaction = |instructions: &[_]| VersionedTransaction {
+            signatures: Vec::new(),
+            message: VersionedMessage::V0(
+                MessageV0::try_compile(&payer, instructions, &[], Hash::default()).unwrap(),
+            ),
+        };
+        let advance_nonce = advance_nonce_instruction(&Pubkey::new_unique(), &payer);
+        assert!(uses_durable_nonce(&transaction(&[
+            advance_nonce,
+            transfer.clone()
+        ])));
+        assert!(!uses_durable_nonce(&transaction(&[transfer])));
+    }
+
+    #[tokio::test]
+    async fn test_landed_transaction_outlives_its_blockhash() {
+        use solana_client::rpc_request::RpcRequest;
+        use solana_message::v0::Message as MessageV0;
+        use solana_message::{Hash, VersionedMessage};
+        use std::collections::HashMap;
+
+        let tx = VersionedTransaction {
+            signatures: vec![Signature::default()],
+            message: VersionedMessage::V0(
+                MessageV0::try_compile(&Pubkey::new_unique(), &[], &[], Hash::default()).unwrap(),
+            ),
+        };
+        let status = |confirmation_status| {
+            let mocks = HashMap::from([
+                (
+                    RpcRequest::IsBlockhashValid,
+                    serde_json::json!({ "context": { "slot": 1 }, "value": false }),
+                ),
+                (
+                    RpcRequest::GetSignatureStatuses,
+                    serde_json::json!({ "context": { "slot": 1 }, "value": [{
+                        "slot": 1,
+                        "confirmations": 0,
+                        "err": null,
+                        "status": { "Ok": null },
+                        "confirmationStatus": confirmation_status,
+                    }] }),
+                ),
+            ]);
+            RpcClient::new_mock_with_mocks("sig_not_found".to_string(), mocks)
+        };
+        let confirm = async |rpc_client| {
+            let provider = SolanaChainProvider::new(
+                vec![Keypair::new()],
+                rpc_client,
+                None,
+                SolanaChainReference::solana_devnet(),
+                200_000,
+                1_000,
+            )
+            .await
+            .unwrap()
+            .with_confirmation(Duration::from_millis(200), Duration::from_millis(10));
+            provider
+                .confirm_with_rebroadcast(&tx, std::future::pending())
+                .await
+        };
+
+        // Landed, but not at the settlement commitment yet: the blockhash expiry does not fail it
+        assert!(matches!(
+            confirm(status("confirmed")).await,
+            Err(SolanaChainProviderError::ConfirmationTimeout(_))
+        ));
+        let not_found = RpcClient::new_mock_with_mocks(
+            "sig_not_found".to_string(),
+            HashMap::from([(
+                RpcRequest::IsBlockhashValid,
+                serde_json::json!({ "context": { "slot": 1 }, "value": false }),
+            )]),
+        );
+        assert!(matches!(
+            confirm(not_found).await,
+            Err(SolanaChainProviderError::BlockhashExpired(_))
+        ));
+    }
+
+    #[test]
+    fn test_ceiling_window() {
+        let start = Instant::now();
+        let mut window = CeilingWindow::new(Duration::from_secs(60));
+        assert_eq!(window.push(start, 100), 100);
+        assert_eq!(window.push(start + Duration::from_secs(10), 500), 500);
+        // A lower ceiling only takes effect once the higher ones leave the window
+        assert_eq!(window.push(start + Duration::from_secs(20), 50), 500);
+        assert_eq!(window.push(start + Duration::from_secs(71), 50), 50);
+    }
+
+    #[test]
+    fn test_compute_unit_price_ceiling() {
+        let fees = [0, 0, 100, 200, 400];
+        assert_eq!(
+            compute_unit_price_ceiling(&fees, 50, 1.5, 1, 1_000),
+            Some(150)
+        );
+        assert_eq!(
+            compute_unit_price_ceiling(&fees, 100, 2.0, 1, 1_000),
+            Some(800)
+        );
+        // Clamped by the absolute cap and the floor
+        assert_eq!(
+            compute_unit_price_ceiling(&fees, 100, 5.0, 1, 1_000),
+            Some(1_000)
+        );
+        assert_eq!(
+            compute_unit_price_ceiling(&fees, 0, 1.5, 10, 1_000),
+            Some(10)
+        );
+        assert_eq!(compute_unit_price_ceiling(&[], 50, 1.5, 1, 1_000), None);
+    }
+}
@@ -0,0 +1,345 @@
+//! JSON-RPC transport that spreads Solana requests over several endpoints.
+//!
+//! Each endpoint is paced to its configured rate limit and keeps a running
+//! health score built from its recent success rate and latency. Requests go to
+//! the healthiest endpoint first and fail over to the next one when the endpoint
+//! itself is at fault: a transport error, or a node reporting itself unhealthy.
+//! Errors returned by a healthy node are passed through as-is. The health of an
+//! endpoint recovers while it is not used, so that a demoted endpoint is tried again.
+//!
+//! `sendTransaction` can optionally be fanned out to all endpoints at once,
+//! which improves the chances of a transaction landing. As the signature is the
+//! same everywhere, the first endpoint to accept the transaction wins, while the
+//! other sends complete in the background.
+
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use futures_util::stream::FuturesUnordered;
+use serde_json::Value;
+use solana_client::client_error::{ClientError, ClientErrorKind, Result as ClientResult};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY;
+use solana_client::rpc_request::{RpcError, RpcRequest};
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::config::RpcConfig;
+
+/// Weight of the most recent request in an endpoint's health averages.
+const HEALTH_SMOOTHING: f64 = 0.2;
+
+/// Time for the failures and latency recorded for an endpoint to weigh half as much,
+/// while the endpoint is not used.
+const HEALTH_RECOVERY_HALF_LIFE: Duration = Duration::from_secs(30);
+
+/// [`RpcSender`] over a set of Solana RPC endpoints.
+pub struct FallbackRpcSender {
+    endpoints: Vec<Arc<Endpoint>>,
+    broadcast_transactions: bool,
+}
+
+impl FallbackRpcSender {
+    /// Creates a sender over `endpoints`, which should not be empty.
+    ///
+    /// When `broadcast_transactions` is set, `sendTransaction` is sent to every endpoint.
+    pub fn new(endpoints: &[RpcConfig], broadcast_transactions: bool) -> Self {
+        let endpoints = endpoints
+            .iter()
+            .map(|config| Arc::new(Endpoint::new(config)))
+            .collect();
+        Self {
+            endpoints,
+            broadcast_transactions,
+        }
+    }
+
+    /// Endpoints ordered from the healthiest to the least healthy.
+    fn ranked_endpoints(&self) -> Vec<&Endpoint> {
+        let now = Instant::now();
+        let mut ranked = self
+            .endpoints
+            .iter()
+            .map(|endpoint| (endpoint.score(now), endpoint.as_ref()))
+            .collect::<Vec<_>>();
+        // Stable sort keeps the configured order among equally healthy endpoints.
+        ranked.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        ranked.into_iter().map(|(_, endpoint)| endpoint).collect()
+    }
+
+    /// Sends to every endpoint, returning as soon as one succeeds.
+    async fn broadcast(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        let mut sends = self
+            .endpoints
+            .iter()
+            .map(|endpoint| {
+                let endpoint = endpoint.clone();
+                let params = params.clone();
+                async move { endpoint.send(request, params).await }
+            })
+            .collect::<FuturesUnordered<_>>();
+        let mut first_error = None;
+        while let Some(result) = sends.next().await {
+            match result {
+                Ok(value) => {
+                    // The other endpoints still get the transaction
+                    tokio::spawn(async move { while sends.next().await.is_some() {} });
+                    return Ok(value);
+                }
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        Err(first_error.expect("at least one RPC endpoint"))
+    }
+}
+
+#[async_trait]
+impl RpcSender for FallbackRpcSender {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        if self.broadcast_transactions
+            && request == RpcRequest::SendTransaction
+            && self.endpoints.len() > 1
+        {
+            return self.broadcast(request, params).await;
+        }
+        let mut last_error = None;
+        for endpoint in self.ranked_endpoints() {
+            match endpoint.send(request, params.clone()).await {
+                Err(error) if is_endpoint_failure(&error) => {
+                    tracing::warn!(rpc = %endpoint.client.url(), %request, %error, "Solana RPC endpoint failed, trying the next one");
+                    last_error = Some(error);
+                }
+                result => return result,
+            }
+        }
+        Err(last_error.expect("at least one RPC endpoint"))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.client.get_transport_stats())
+            .fold(RpcTransportStats::default(), |total, stats| {
+                RpcTransportStats {
+                    request_count: total.request_count + stats.request_count,
+                    elapsed_time: total.elapsed_time + stats.elapsed_time,
+                    rate_limited_time: total.rate_limited_time + stats.rate_limited_time,
+                }
+            })
+    }
+
+    fn url(&self) -> String {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.client.url())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Whether `error` is the endpoint's fault, so the request is worth retrying elsewhere.
+fn is_endpoint_failure(error: &ClientError) -> bool {
+    match error.kind() {
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => {
+            *code == JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+        }
+        ClientErrorKind::TransactionError(_) | ClientErrorKind::SigningError(_) => false,
+        _ => true,
+    }
+}
+
+/// A single RPC endpoint with its rate limit and health.
+struct Endpoint {
+    client: RpcClient,
+    rate_limiter: Option<RateLimiter>,
+    health: Mutex<Health>,
+}
+
+impl Endpoint {
+    fn new(config: &RpcConfig) -> Self {
+        Self {
+            client: RpcClient::new(config.http.to_string()),
+            rate_limiter: config.rate_limit.map(RateLimiter::new),
+            health: Mutex::new(Health::new(Instant::now())),
+        }
+    }
+
+    fn score(&self, now: Instant) -> f64 {
+        self.health.lock().unwrap().score(now)
+    }
+
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+        let started = Instant::now();
+        let result = self.client.send::<Value>(request, params).await;
+        let success = !matches!(&result, Err(error) if is_endpoint_failure(error));
+        self.health
+            .lock()
+            .unwrap()
+            .record(success, started.elapsed(), Instant::now());
+        result
+    }
+}
+
+/// Exponentially weighted success rate and latency of an endpoint.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Health {
+    success_rate: f64,
+    latency_secs: f64,
+    /// When the averages were last updated.
+    updated_at: Instant,
+}
+
+impl Health {
+    /// Health of an endpoint without any request yet.
+    fn new(now: Instant) -> Self {
+        Self {
+            success_rate: 1.0,
+            latency_secs: 0.0,
+            updated_at: now,
+        }
+    }
+
+    /// Health as of `now`: since the last update, it recovers towards that of a new endpoint.
+    fn at(&self, now: Instant) -> Self {
+        let idle = now.saturating_duration_since(self.updated_at);
+        let weight = 0.5f64.powf(idle.as_secs_f64() / HEALTH_RECOVERY_HALF_LIFE.as_secs_f64());
+        Self {
+            success_rate: 1.0 - (1.0 - self.success_rate) * weight,
+            latency_secs: self.latency_secs * weight,
+            updated_at: now,
+        }
+    }
+
+    /// Higher is healthier: failures weigh much more than latency.
+    fn score(&self, now: Instant) -> f64 {
+        let health = self.at(now);
+        health.success_rate / (1.0 + health.latency_secs)
+    }
+
+    fn record(&mut self, success: bool, latency: Duration, now: Instant) {
+        *self = self.at(now);
+        let success = if success { 1.0 } else { 0.0 };
+        self.success_rate += HEALTH_SMOOTHING * (success - self.success_rate);
+        self.latency_secs += HEALTH_SMOOTHING * (latency.as_secs_f64() - self.latency_secs);
+    }
+}
+
+/// Spaces requests evenly so that an endpoint stays within its rate limit.
+struct RateLimiter {
+    interval: Duration,
+    next_slot: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next_slot: Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn acquire(&self) {
+        let wait = {
+            let mut next_slot = self.next_slot.lock().unwrap();
+            let now = Instant::now();
+            let slot = (*next_slot).max(now);
+            *next_slot = slot + self.interval;
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_ranks_failing_endpoint_last() {
+        let now = Instant::now();
+        let mut healthy = Health::new(now);
+        let mut failing = Health::new(now);
+        for _ in 0..3 {
+            healthy.record(true, Duration::from_millis(800), now);
+            failing.record(false, Duration::from_millis(50), now);
+        }
+        assert!(healthy.score(now) > failing.score(now));
+
+        let mut fast = Health::new(now);
+        fast.record(true, Duration::from_millis(50), now);
+        assert!(fast.score(now) > healthy.score(now));
+
+        // Left alone, the failing endpoint recovers and is tried again
+        let later = now + 4 * HEALTH_RECOVERY_HALF_LIFE;
+        healthy.record(true, Duration::from_millis(800), later);
+        assert!(failing.score(later) > healthy.score(later));
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_returns_on_first_success() {
+        // An endpoint that accepts connections but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((connection, _)) = listener.accept().await {
+                connections.push(connection);
+            }
+        });
+        let endpoint = |client| {
+            Arc::new(Endpoint {
+                client,
+                rate_limiter: None,
+                health: Mutex::new(Health::new(Instant::now())),
+            })
+        };
+        let mocks = std::collections::HashMap::from([(
+            RpcRequest::SendTransaction,
+            Value::String("signature".to_string()),
+        )]);
+        let sender = FallbackRpcSender {
+            endpoints: vec![
+                endpoint(RpcClient::new(url)),
+                endpoint(RpcClient::new_mock_with_mocks(
+                    "succeeds".to_string(),
+                    mocks,
+                )),
+            ],
+            broadcast_transactions: true,
+        };
+        let result = tokio::time::timeout(
+            Duration::from_secs(5),
+            sender.send(RpcRequest::SendTransaction, Value::Null),
+        )
+        .await
+        .expect("broadcast waited for the unresponsive endpoint");
+        assert_eq!(result.unwrap(), Value::String("signature".to_string()));
+    }
+
+    #[test]
+    fn test_endpoint_failure_classification() {
+        let unhealthy = ClientError::from(RpcError::RpcResponseError {
+            code: JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+            message: "Node is behind".to_string(),
+            data: solana_client::rpc_request::RpcResponseErrorData::Empty,
+        });
+        assert!(is_endpoint_failure(&unhealthy));
+
+        let rejected = ClientError::from(RpcError::RpcResponseError {
+            code: -32002,
+            message: "Transaction simulation failed".to_string(),
+            data: solana_client::rpc_request::RpcResponseErrorData::Empty,
+        });
+        assert!(!is_endpoint_failure(&rejected));
+
+        let transport = ClientError::from(std::io::Error::other("connection reset"));
+        assert!(is_endpoint_failure(&transport));
+    }
+}
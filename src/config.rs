@@ -1,6 +1,6 @@
 //! Configuration module for the x402 facilitator server.
 
-use alloy_primitives::B256;
+use alloy_primitives::{Address, B256};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -12,7 +12,7 @@ use url::Url;
 
 use crate::chain::eip155;
 use crate::chain::solana;
-use crate::chain::{ChainId, ChainIdPattern};
+use crate::chain::{ChainId, ChainIdPattern, Finality, FinalityPolicy};
 
 /// CLI arguments for the x402 facilitator server.
 #[derive(Parser, Debug)]
@@ -38,6 +38,90 @@ pub struct Config {
     chains: Vec<ChainConfig>,
     #[serde(default)]
     schemes: Vec<SchemeConfig>,
+    #[serde(default)]
+    tenants: Vec<TenantConfig>,
+    #[serde(default)]
+    upstreams: Vec<UpstreamConfig>,
+}
+
+/// Configuration for a tenant of a multi-tenant facilitator.
+///
+/// Each tenant gets its own chains (and thus its own signers) and schemes. Requests are
+/// routed to a tenant either by the `/t/{name}/...` path prefix or by one of its API keys.
+///
+/// Example JSON:
+/// ```json
+/// {
+///   "name": "acme",
+///   "api_keys": ["$ACME_API_KEY"],
+///   "chains": {
+///     "eip155:8453": { "signers": ["$ACME_EVM_PRIVATE_KEY"], "rpc": [{ "http": "https://mainnet.base.org" }] }
+///   },
+///   "schemes": [{ "id": "v2-eip155-exact", "chains": "eip155:*" }]
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct TenantConfig {
+    /// Tenant name, used in the `/t/{name}/...` path prefix and to tag logs and metrics.
+    pub name: String,
+    /// API keys that resolve to this tenant (optional).
+    #[serde(default)]
+    pub api_keys: Vec<LiteralOrEnv<String>>,
+    /// Chains available to this tenant.
+    #[serde(default, with = "chains_serde")]
+    pub chains: Vec<ChainConfig>,
+    /// Schemes enabled for this tenant.
+    #[serde(default)]
+    pub schemes: Vec<SchemeConfig>,
+    /// Upstream facilitators for this tenant.
+    #[serde(default)]
+    pub upstreams: Vec<UpstreamConfig>,
+}
+
+/// Configuration for an upstream facilitator.
+///
+/// Verify and settle requests that no local scheme handles are proxied to the first
+/// upstream whose filters match, and its payment kinds are merged into `/supported`.
+///
+/// Example JSON:
+/// ```json
+/// {
+///   "url": "https://facilitator.partner.com",
+///   "chains": "solana:*",
+///   "schemes": ["exact"]
+/// }
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpstreamConfig {
+    /// Base URL of the upstream facilitator, where `/verify`, `/settle` and `/supported` live.
+    pub url: LiteralOrEnv<Url>,
+    /// Chains forwarded to this upstream (optional, defaults to all chains).
+    #[serde(default)]
+    pub chains: Option<ChainIdPattern>,
+    /// Scheme names (e.g. "exact") forwarded to this upstream (optional, defaults to all schemes).
+    #[serde(default)]
+    pub schemes: Option<Vec<String>>,
+    /// Timeout to connect to the upstream, in seconds (optional, defaults to 5).
+    #[serde(default = "upstream_config_defaults::default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Timeout of a whole request to the upstream, in seconds (optional, defaults to 30).
+    #[serde(default = "upstream_config_defaults::default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// How long a `/supported` response of the upstream is cached, in seconds (optional, defaults to 60).
+    #[serde(default = "upstream_config_defaults::default_supported_ttl_secs")]
+    pub supported_ttl_secs: u64,
+}
+
+mod upstream_config_defaults {
+    pub fn default_connect_timeout_secs() -> u64 {
+        5
+    }
+    pub fn default_timeout_secs() -> u64 {
+        30
+    }
+    pub fn default_supported_ttl_secs() -> u64 {
+        60
+    }
 }
 
 /// Configuration for a specific scheme.
@@ -71,7 +155,7 @@ mod scheme_config_defaults {
 #[derive(Debug, Clone)]
 pub enum ChainConfig {
     /// EVM chain configuration (for chains with "eip155:" prefix).
-    Eip155(Eip155ChainConfig),
+    Eip155(Box<Eip155ChainConfig>),
     /// Solana chain configuration (for chains with "solana:" prefix).
     Solana(Box<SolanaChainConfig>),
 }
@@ -345,6 +429,22 @@ impl Deref for SolanaSignerConfig {
     }
 }
 
+/// Configuration for Solana signers (fee payers).
+///
+/// Deserializes either a single key string or an array of key strings, so that
+/// the legacy `"signer": "..."` form keeps working next to `"signers": [...]`.
+///
+/// Example JSON:
+/// ```json
+/// {
+///   "signers": [
+///     "$SOLANA_FEE_PAYER_1",
+///     "$SOLANA_FEE_PAYER_2"
+///   ]
+/// }
+/// ```
+pub type SolanaSignersConfig = Vec<SolanaSignerConfig>;
+
 // ============================================================================
 // Chain Configurations
 // ============================================================================
@@ -368,6 +468,36 @@ impl Eip155ChainConfig {
     pub fn receipt_timeout_secs(&self) -> u64 {
         self.inner.receipt_timeout_secs
     }
+    pub fn max_fee_per_gas(&self) -> Option<u64> {
+        self.inner.max_fee_per_gas
+    }
+    pub fn max_priority_fee_per_gas(&self) -> Option<u64> {
+        self.inner.max_priority_fee_per_gas
+    }
+    pub fn fee_bump_interval_secs(&self) -> Option<u64> {
+        self.inner.fee_bump_interval_secs
+    }
+    pub fn fee_bump_percent(&self) -> u64 {
+        self.inner.fee_bump_percent
+    }
+    pub fn stuck_nonce_check_interval_secs(&self) -> Option<u64> {
+        self.inner.stuck_nonce_check_interval_secs
+    }
+    pub fn nonce_store_path(&self) -> Option<&PathBuf> {
+        self.inner.nonce_store_path.as_ref()
+    }
+    pub fn batch_settlement(&self) -> Option<&BatchSettlementConfig> {
+        self.inner.batch_settlement.as_ref()
+    }
+    pub fn token_metadata_ttl_secs(&self) -> u64 {
+        self.inner.token_metadata_ttl_secs
+    }
+    pub fn token_metadata(&self) -> &[TokenMetadataConfig] {
+        &self.inner.token_metadata
+    }
+    pub fn finality(&self) -> FinalityPolicy {
+        self.inner.finality.policy()
+    }
     pub fn signers(&self) -> &Eip155SignersConfig {
         &self.inner.signers
     }
@@ -386,12 +516,15 @@ pub struct SolanaChainConfig {
 }
 
 impl SolanaChainConfig {
-    pub fn signer(&self) -> &SolanaSignerConfig {
-        &self.inner.signer
+    pub fn signers(&self) -> &SolanaSignersConfig {
+        &self.inner.signers
     }
-    pub fn rpc(&self) -> &Url {
+    pub fn rpc(&self) -> &[RpcConfig] {
         &self.inner.rpc
     }
+    pub fn broadcast_transactions(&self) -> bool {
+        self.inner.broadcast_transactions
+    }
     pub fn max_compute_unit_limit(&self) -> u32 {
         self.inner.max_compute_unit_limit
     }
@@ -404,6 +537,21 @@ impl SolanaChainConfig {
     pub fn pubsub(&self) -> &Option<Url> {
         &self.inner.pubsub
     }
+    pub fn confirmation_timeout_secs(&self) -> u64 {
+        self.inner.confirmation_timeout_secs
+    }
+    pub fn rebroadcast_interval_secs(&self) -> u64 {
+        self.inner.rebroadcast_interval_secs
+    }
+    pub fn mint_cache_ttl_secs(&self) -> u64 {
+        self.inner.mint_cache_ttl_secs
+    }
+    pub fn finality(&self) -> FinalityPolicy {
+        self.inner.finality.policy()
+    }
+    pub fn dynamic_compute_unit_price(&self) -> Option<&DynamicComputeUnitPriceConfig> {
+        self.inner.dynamic_compute_unit_price.as_ref()
+    }
 }
 
 /// Configuration specific to EVM-compatible chains.
@@ -423,9 +571,54 @@ pub struct Eip155ChainConfigInner {
     /// How long to wait till the transaction receipt is available (optional)
     #[serde(default = "eip155_chain_config::default_receipt_timeout_secs")]
     pub receipt_timeout_secs: u64,
+    /// Cap on the max fee per gas of sent transactions, in wei (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<u64>,
+    /// Cap on the max priority fee per gas of sent transactions, in wei (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<u64>,
+    /// How long to wait for a receipt before replacing the transaction with higher fees (optional).
+    /// Replacement is disabled when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_bump_interval_secs: Option<u64>,
+    /// Percentage by which fees are raised on each replacement (optional)
+    #[serde(default = "eip155_chain_config::default_fee_bump_percent")]
+    pub fee_bump_percent: u64,
+    /// How often to check signers for stuck nonces (optional).
+    /// The check is disabled when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stuck_nonce_check_interval_secs: Option<u64>,
+    /// Directory where the nonces of signers are kept across restarts, in a JSON file per signer (optional).
+    /// Nonces are kept in memory only when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce_store_path: Option<PathBuf>,
+    /// Batch settlements through Multicall3 (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_settlement: Option<BatchSettlementConfig>,
+    /// How long the fetched name, version and decimals of tokens are cached (optional)
+    #[serde(default = "eip155_chain_config::default_token_metadata_ttl_secs")]
+    pub token_metadata_ttl_secs: u64,
+    /// Token metadata to set, or to fetch at startup when incomplete (optional)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub token_metadata: Vec<TokenMetadataConfig>,
+    /// Settlement finality, and the bounds of per-request overrides (optional)
+    #[serde(flatten)]
+    pub finality: FinalityConfig,
 }
 
 mod eip155_chain_config {
+    pub fn default_fee_bump_percent() -> u64 {
+        20
+    }
+    pub fn default_token_metadata_ttl_secs() -> u64 {
+        3600
+    }
+    pub fn default_batch_window_ms() -> u64 {
+        200
+    }
+    pub fn default_batch_max_items() -> usize {
+        20
+    }
     pub fn default_eip1559() -> bool {
         true
     }
@@ -441,10 +634,17 @@ mod eip155_chain_config {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SolanaChainConfigInner {
     /// Signer configuration for this chain (required).
-    /// A single private key (base58 format, 64 bytes) or env var reference.
-    pub signer: SolanaSignerConfig,
+    /// One or more private keys (base58 format, 64 bytes) or env var references.
+    /// Each key acts as a fee payer; `signer` is accepted as an alias.
+    #[serde(alias = "signer", with = "solana_chain_config::signers_serde")]
+    pub signers: SolanaSignersConfig,
     /// RPC provider configuration for this chain (required).
-    pub rpc: Url,
+    /// Either a single URL or an array of endpoints, tried in order of health.
+    #[serde(with = "solana_chain_config::rpc_serde")]
+    pub rpc: Vec<RpcConfig>,
+    /// Whether to send settlement transactions to every RPC endpoint at once (optional)
+    #[serde(default)]
+    pub broadcast_transactions: bool,
     /// RPC pubsub provider endpoint (optional)
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pubsub: Option<Url>,
@@ -454,6 +654,109 @@ pub struct SolanaChainConfigInner {
     /// Maximum compute unit price for transactions (optional)
     #[serde(default = "solana_chain_config::default_max_compute_unit_price")]
     pub max_compute_unit_price: u64,
+    /// How long to wait for a settlement transaction to be confirmed (optional)
+    #[serde(default = "solana_chain_config::default_confirmation_timeout_secs")]
+    pub confirmation_timeout_secs: u64,
+    /// How often to resend a settlement transaction until it is confirmed (optional)
+    #[serde(default = "solana_chain_config::default_rebroadcast_interval_secs")]
+    pub rebroadcast_interval_secs: u64,
+    /// How long a fetched mint account is cached (optional)
+    #[serde(default = "solana_chain_config::default_mint_cache_ttl_secs")]
+    pub mint_cache_ttl_secs: u64,
+    /// Settlement finality, and the bounds of per-request overrides (optional)
+    #[serde(flatten)]
+    pub finality: FinalityConfig,
+    /// Derive the compute unit price ceiling from recent network fees (optional)
+    /// `max_compute_unit_price` remains the absolute cap.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dynamic_compute_unit_price: Option<DynamicComputeUnitPriceConfig>,
+}
+
+/// Finality a chain settles with, and the range a settle request may pick another one from.
+///
+/// Without `finality`, EVM chains wait for the scheme's `confirmations`, and Solana chains for `confirmed`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FinalityConfig {
+    /// Default settlement finality.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finality: Option<Finality>,
+    /// Weakest finality a settle request may ask for, defaults to `finality`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_finality: Option<Finality>,
+    /// Strongest finality a settle request may ask for, defaults to `finality`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_finality: Option<Finality>,
+}
+
+impl FinalityConfig {
+    pub fn policy(&self) -> FinalityPolicy {
+        FinalityPolicy {
+            default: self.finality,
+            min: self.min_finality,
+            max: self.max_finality,
+        }
+    }
+}
+
+/// Metadata of a token, used to build its EIP-712 domain.
+///
+/// With both `name` and `version`, the metadata is used as is and never expires.
+/// Otherwise, it is fetched at startup to warm the cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenMetadataConfig {
+    /// Token contract address.
+    pub asset: Address,
+    /// EIP-712 domain name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// EIP-712 domain version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Token decimals.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decimals: Option<u8>,
+}
+
+/// Settlements combined into one Multicall3 transaction.
+///
+/// A batch collects the settlements that arrive within `window_ms` of the first one,
+/// up to `max_items`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchSettlementConfig {
+    /// How long a batch waits for more settlements, in milliseconds.
+    #[serde(default = "eip155_chain_config::default_batch_window_ms")]
+    pub window_ms: u64,
+    /// Maximum number of settlements in a batch.
+    #[serde(default = "eip155_chain_config::default_batch_max_items")]
+    pub max_items: usize,
+}
+
+/// Compute unit price ceiling that follows recent prioritization fees.
+///
+/// The ceiling is the given percentile of the fees paid in recent slots,
+/// multiplied by `multiplier`, and refreshed every `refresh_interval_secs`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DynamicComputeUnitPriceConfig {
+    /// Percentile of recent prioritization fees, from 0 to 100.
+    #[serde(default = "solana_chain_config::default_fee_percentile")]
+    pub percentile: u8,
+    /// Headroom applied on top of the percentile.
+    #[serde(default = "solana_chain_config::default_fee_multiplier")]
+    pub multiplier: f64,
+    /// Lowest ceiling, so that minimal priority fees stay accepted when the network is quiet.
+    #[serde(default = "solana_chain_config::default_min_compute_unit_price")]
+    pub min_compute_unit_price: u64,
+    /// How often to refresh the ceiling.
+    #[serde(default = "solana_chain_config::default_fee_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+    /// The ceiling is the highest one derived within this window, so that it only goes down
+    /// once clients had time to use the previous one.
+    #[serde(default = "solana_chain_config::default_fee_window_secs")]
+    pub window_secs: u64,
+    /// Write-locked accounts to look up fees for, typically busy token accounts.
+    /// Network-wide fees are used when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts: Vec<solana::Address>,
 }
 
 mod solana_chain_config {
@@ -463,6 +766,101 @@ mod solana_chain_config {
     pub fn default_max_compute_unit_price() -> u64 {
         1_000_000
     }
+    pub fn default_confirmation_timeout_secs() -> u64 {
+        90
+    }
+    pub fn default_rebroadcast_interval_secs() -> u64 {
+        2
+    }
+    pub fn default_mint_cache_ttl_secs() -> u64 {
+        300
+    }
+    pub fn default_fee_percentile() -> u8 {
+        75
+    }
+    pub fn default_fee_multiplier() -> f64 {
+        1.5
+    }
+    pub fn default_min_compute_unit_price() -> u64 {
+        1
+    }
+    pub fn default_fee_refresh_interval_secs() -> u64 {
+        10
+    }
+    pub fn default_fee_window_secs() -> u64 {
+        60
+    }
+
+    /// Accepts either a single signer or an array of signers.
+    pub mod signers_serde {
+        use super::super::{SolanaSignerConfig, SolanaSignersConfig};
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany {
+            One(SolanaSignerConfig),
+            Many(Vec<SolanaSignerConfig>),
+        }
+
+        pub fn serialize<S>(signers: &SolanaSignersConfig, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            signers.serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<SolanaSignersConfig, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let signers = match OneOrMany::deserialize(deserializer)? {
+                OneOrMany::One(signer) => vec![signer],
+                OneOrMany::Many(signers) => signers,
+            };
+            Ok(signers)
+        }
+    }
+
+    /// Accepts either a single RPC URL or an array of RPC endpoint configurations.
+    pub mod rpc_serde {
+        use super::super::RpcConfig;
+        use serde::{Deserialize, Deserializer, Serialize, Serializer};
+        use url::Url;
+
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany {
+            One(Url),
+            Many(Vec<RpcConfig>),
+        }
+
+        pub fn serialize<S>(rpc: &[RpcConfig], serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            rpc.serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<RpcConfig>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let rpc = match OneOrMany::deserialize(deserializer)? {
+                OneOrMany::One(http) => vec![RpcConfig {
+                    http,
+                    rate_limit: None,
+                }],
+                OneOrMany::Many(rpc) => rpc,
+            };
+            if rpc.is_empty() {
+                return Err(serde::de::Error::custom(
+                    "at least one RPC endpoint should be provided",
+                ));
+            }
+            Ok(rpc)
+        }
+    }
 }
 
 /// Custom serde module for deserializing the chains map with type discrimination
@@ -527,7 +925,7 @@ mod chains_serde {
                                     .map_err(|e| serde::de::Error::custom(format!("{}", e)))?,
                                 inner,
                             };
-                            ChainConfig::Eip155(config)
+                            ChainConfig::Eip155(Box::new(config))
                         }
                         solana::SOLANA_NAMESPACE => {
                             let inner: SolanaChainConfigInner = access.next_value()?;
@@ -564,6 +962,8 @@ impl Default for Config {
             host: config_defaults::default_host(),
             chains: Vec::new(),
             schemes: Vec::new(),
+            tenants: Vec::new(),
+            upstreams: Vec::new(),
         }
     }
 }
@@ -669,4 +1069,92 @@ impl Config {
     pub fn schemes(&self) -> &Vec<SchemeConfig> {
         &self.schemes
     }
+
+    /// Get the tenants configuration list.
+    ///
+    /// Top-level `chains` and `schemes` make up the default tenant, used when a request
+    /// carries neither a tenant path prefix nor an API key.
+    pub fn tenants(&self) -> &Vec<TenantConfig> {
+        &self.tenants
+    }
+
+    /// Get the upstream facilitators configuration list.
+    pub fn upstreams(&self) -> &Vec<UpstreamConfig> {
+        &self.upstreams
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_keypair::Keypair;
+
+    fn solana_chain_config(signers: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "chains": {
+                "solana:EtWTRABZaYq6iMfeYKouRu166VU2xqa1": {
+                    "rpc": "https://api.devnet.solana.com",
+                    "signer": signers,
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_solana_single_signer() {
+        let key = Keypair::new().to_base58_string();
+        let config: Config = serde_json::from_value(solana_chain_config(key.into())).unwrap();
+        let ChainConfig::Solana(solana) = &config.chains()[0] else {
+            panic!("expected solana chain config");
+        };
+        assert_eq!(solana.signers().len(), 1);
+    }
+
+    #[test]
+    fn test_solana_multiple_signers() {
+        let keys = vec![
+            Keypair::new().to_base58_string(),
+            Keypair::new().to_base58_string(),
+        ];
+        let config: Config =
+            serde_json::from_value(solana_chain_config(keys.clone().into())).unwrap();
+        let ChainConfig::Solana(solana) = &config.chains()[0] else {
+            panic!("expected solana chain config");
+        };
+        let signers = solana
+            .signers()
+            .iter()
+            .map(|s| s.to_base58())
+            .collect::<Vec<_>>();
+        assert_eq!(signers, keys);
+    }
+
+    #[test]
+    fn test_tenants() {
+        let config: Config = serde_json::from_value(serde_json::json!({
+            "tenants": [
+                {
+                    "name": "acme",
+                    "api_keys": ["acme-key"],
+                    "chains": {
+                        "solana:EtWTRABZaYq6iMfeYKouRu166VU2xqa1": {
+                            "rpc": "https://api.devnet.solana.com",
+                            "signers": [Keypair::new().to_base58_string()],
+                        }
+                    },
+                    "schemes": [{ "id": "v2-solana-exact", "chains": "solana:*" }]
+                },
+                { "name": "globex" }
+            ]
+        }))
+        .unwrap();
+        let tenants = config.tenants();
+        assert_eq!(tenants.len(), 2);
+        assert_eq!(tenants[0].name, "acme");
+        assert_eq!(tenants[0].api_keys[0].inner(), "acme-key");
+        assert_eq!(tenants[0].chains.len(), 1);
+        assert_eq!(tenants[0].schemes.len(), 1);
+        assert!(tenants[1].chains.is_empty());
+        assert!(config.chains().is_empty());
+    }
 }
@@ -9,12 +9,14 @@
 //! - Contract interaction using Alloy
 //! - Network-specific configuration via [`ProviderCache`] and [`USDCDeployment`]
 
+use futures_util::future::join_all;
 use std::collections::HashMap;
 
 use crate::facilitator::Facilitator;
+use crate::facilitator_upstream::{FacilitatorUpstream, FacilitatorUpstreamError};
 use crate::proto;
 use crate::proto::PaymentVerificationError;
-use crate::scheme::{SchemeRegistry, X402SchemeFacilitatorError};
+use crate::scheme::{SchemeHandlerSlug, SchemeRegistry, X402SchemeFacilitatorError};
 
 /// A concrete [`Facilitator`] implementation that verifies and settles x402 payments
 /// using a network-aware provider cache.
@@ -23,6 +25,7 @@ use crate::scheme::{SchemeRegistry, X402SchemeFacilitatorError};
 /// which enables testing or customization beyond the default [`ProviderCache`].
 pub struct FacilitatorLocal<A> {
     handlers: A,
+    upstreams: Vec<FacilitatorUpstream>,
 }
 
 impl<A> FacilitatorLocal<A> {
@@ -30,7 +33,24 @@ impl<A> FacilitatorLocal<A> {
     ///
     /// The provider cache is used to resolve the appropriate EVM provider for each payment's target network.
     pub fn new(handlers: A) -> Self {
-        FacilitatorLocal { handlers }
+        FacilitatorLocal {
+            handlers,
+            upstreams: Vec::new(),
+        }
+    }
+
+    /// Sets the upstream facilitators that requests without a local scheme handler are proxied to.
+    ///
+    /// Upstreams are tried in order; the first one matching the request chain and scheme wins.
+    pub fn with_upstreams(mut self, upstreams: Vec<FacilitatorUpstream>) -> Self {
+        self.upstreams = upstreams;
+        self
+    }
+
+    fn upstream_for(&self, slug: Option<&SchemeHandlerSlug>) -> Option<&FacilitatorUpstream> {
+        self.upstreams
+            .iter()
+            .find(|upstream| upstream.matches(slug))
     }
 }
 
@@ -41,12 +61,23 @@ impl Facilitator for FacilitatorLocal<SchemeRegistry> {
         &self,
         request: &proto::VerifyRequest,
     ) -> Result<proto::VerifyResponse, Self::Error> {
-        let handler = request
-            .scheme_handler_slug()
-            .and_then(|slug| self.handlers.by_slug(&slug))
-            .ok_or(FacilitatorLocalError::Verification(
-                PaymentVerificationError::UnsupportedScheme.into(),
-            ))?;
+        let slug = request.scheme_handler_slug();
+        let handler = slug.as_ref().and_then(|slug| self.handlers.by_slug(slug));
+        let handler = match (handler, self.upstream_for(slug.as_ref())) {
+            (Some(handler), _) => handler,
+            (None, Some(upstream)) => {
+                tracing::debug!(upstream = %upstream.base_url(), "Forwarding verify request upstream");
+                return upstream
+                    .verify(request)
+                    .await
+                    .map_err(FacilitatorLocalError::Upstream);
+            }
+            (None, None) => {
+                return Err(FacilitatorLocalError::Verification(
+                    PaymentVerificationError::UnsupportedScheme.into(),
+                ));
+            }
+        };
         let response = handler
             .verify(request)
             .await
@@ -58,12 +89,23 @@ impl Facilitator for FacilitatorLocal<SchemeRegistry> {
         &self,
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, Self::Error> {
-        let handler = request
-            .scheme_handler_slug()
-            .and_then(|slug| self.handlers.by_slug(&slug))
-            .ok_or(FacilitatorLocalError::Verification(
-                PaymentVerificationError::UnsupportedScheme.into(),
-            ))?;
+        let slug = request.scheme_handler_slug();
+        let handler = slug.as_ref().and_then(|slug| self.handlers.by_slug(slug));
+        let handler = match (handler, self.upstream_for(slug.as_ref())) {
+            (Some(handler), _) => handler,
+            (None, Some(upstream)) => {
+                tracing::debug!(upstream = %upstream.base_url(), "Forwarding settle request upstream");
+                return upstream
+                    .settle(request)
+                    .await
+                    .map_err(FacilitatorLocalError::Upstream);
+            }
+            (None, None) => {
+                return Err(FacilitatorLocalError::Verification(
+                    PaymentVerificationError::UnsupportedScheme.into(),
+                ));
+            }
+        };
         let response = handler
             .settle(request)
             .await
@@ -83,6 +125,31 @@ impl Facilitator for FacilitatorLocal<SchemeRegistry> {
                 }
             }
         }
+        // Upstreams are queried concurrently, and merged in order of priority
+        let upstream_supported =
+            join_all(self.upstreams.iter().map(|upstream| upstream.supported())).await;
+        for (upstream, supported) in self.upstreams.iter().zip(upstream_supported) {
+            match supported {
+                Ok(supported) => {
+                    for kind in supported.kinds {
+                        let is_known = kinds.iter().any(|known: &proto::SupportedPaymentKind| {
+                            known.x402_version == kind.x402_version
+                                && known.scheme == kind.scheme
+                                && known.network == kind.network
+                        });
+                        if !is_known {
+                            kinds.push(kind);
+                        }
+                    }
+                    for (chain_id, signer_addresses) in supported.signers {
+                        signers.entry(chain_id).or_insert(signer_addresses);
+                    }
+                }
+                Err(error) => {
+                    tracing::warn!(upstream = %upstream.base_url(), error = %error, "Failed to fetch upstream supported kinds");
+                }
+            }
+        }
         Ok(proto::SupportedResponse {
             kinds,
             extensions: Vec::new(),
@@ -97,4 +164,66 @@ pub enum FacilitatorLocalError {
     Verification(X402SchemeFacilitatorError),
     #[error(transparent)]
     Settlement(X402SchemeFacilitatorError),
+    #[error(transparent)]
+    Upstream(FacilitatorUpstreamError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::facilitator_upstream::tests::{kind, serve_upstream};
+    use serde_json::json;
+    use url::Url;
+
+    #[tokio::test]
+    async fn test_supported_merges_upstreams() {
+        let first = serve_upstream(json!({
+            "kinds": [kind(2, "eip155:8453")],
+            "signers": { "eip155:8453": ["0x01"] },
+        }))
+        .await;
+        let second = serve_upstream(json!({
+            "kinds": [kind(2, "eip155:8453"), kind(1, "solana")],
+            "signers": { "eip155:8453": ["0x02"] },
+        }))
+        .await;
+        let unreachable = Url::parse("http://127.0.0.1:1").unwrap();
+        let facilitator = FacilitatorLocal::new(SchemeRegistry::default()).with_upstreams(vec![
+            FacilitatorUpstream::new(unreachable, None, None),
+            FacilitatorUpstream::new(first, None, None),
+            FacilitatorUpstream::new(second, None, None),
+        ]);
+
+        let supported = facilitator.supported().await.unwrap();
+        let networks: Vec<_> = supported
+            .kinds
+            .iter()
+            .map(|kind| kind.network.as_str())
+            .collect();
+        assert_eq!(networks, vec!["eip155:8453", "solana"]);
+        let base = "eip155:8453".parse().unwrap();
+        assert_eq!(supported.signers[&base], vec!["0x01".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_unhandled_request_is_forwarded() {
+        let upstream = serve_upstream(json!({ "kinds": [] })).await;
+        let facilitator = FacilitatorLocal::new(SchemeRegistry::default())
+            .with_upstreams(vec![FacilitatorUpstream::new(upstream, None, None)]);
+        let request: proto::VerifyRequest = serde_json::from_value(json!({
+            "x402Version": 2,
+            "paymentPayload": { "accepted": { "network": "eip155:8453", "scheme": "exact" } },
+        }))
+        .unwrap();
+
+        let response = facilitator.verify(&request).await.unwrap();
+        let response = serde_json::to_value(response).unwrap();
+        assert_eq!(response["isValid"], true);
+        assert!(matches!(
+            facilitator.settle(&request).await,
+            Err(FacilitatorLocalError::Upstream(
+                FacilitatorUpstreamError::Response { .. }
+            ))
+        ));
+    }
 }
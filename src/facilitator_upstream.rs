@@ -0,0 +1,323 @@
+//! Facilitator implementation forwarding x402 requests to an upstream facilitator over HTTP.
+//!
+//! [`FacilitatorUpstream`] lets a facilitator settle some networks itself while relying on
+//! a partner facilitator for the others: [`crate::facilitator_local::FacilitatorLocal`]
+//! proxies verify and settle requests it has no scheme handler for to the first matching
+//! upstream, and merges the upstream payment kinds into its own `/supported` response.
+//! Upstream `/supported` responses are cached, and fetched with a short timeout, so that
+//! a slow upstream does not stall `/supported` for every client.
+
+use axum::http::StatusCode;
+use reqwest::Client;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use url::Url;
+
+use crate::chain::{ChainId, ChainIdPattern};
+use crate::config::UpstreamConfig;
+use crate::facilitator::Facilitator;
+use crate::proto;
+use crate::scheme::SchemeHandlerSlug;
+use crate::util::TtlCache;
+
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_SUPPORTED_TTL: Duration = Duration::from_secs(60);
+/// Longest wait for the upstream `/supported`, shorter than the request timeout.
+const SUPPORTED_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A remote facilitator reached over HTTP, restricted to an optional chain pattern and scheme list.
+#[derive(Debug, Clone)]
+pub struct FacilitatorUpstream {
+    client: Client,
+    base_url: Url,
+    chains: Option<ChainIdPattern>,
+    schemes: Option<Vec<String>>,
+    /// Timeout of `/supported` requests.
+    supported_timeout: Duration,
+    /// Last `/supported` response, filtered.
+    supported: Arc<TtlCache<(), proto::SupportedResponse>>,
+}
+
+impl FacilitatorUpstream {
+    pub fn new(
+        base_url: Url,
+        chains: Option<ChainIdPattern>,
+        schemes: Option<Vec<String>>,
+    ) -> Self {
+        // Make sure `join` appends to the base path instead of replacing its last segment.
+        let mut base_url = base_url;
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        Self {
+            client: Self::client(DEFAULT_CONNECT_TIMEOUT, DEFAULT_TIMEOUT),
+            base_url,
+            chains,
+            schemes,
+            supported_timeout: SUPPORTED_TIMEOUT,
+            supported: Arc::new(TtlCache::new(DEFAULT_SUPPORTED_TTL, 1)),
+        }
+    }
+
+    pub fn from_config(config: &UpstreamConfig) -> Self {
+        Self::new(
+            config.url.inner().clone(),
+            config.chains.clone(),
+            config.schemes.clone(),
+        )
+        .with_timeouts(
+            Duration::from_secs(config.connect_timeout_secs),
+            Duration::from_secs(config.timeout_secs),
+        )
+        .with_supported_ttl(Duration::from_secs(config.supported_ttl_secs))
+    }
+
+    /// Sets the timeouts to connect to the upstream and to complete a whole request.
+    pub fn with_timeouts(mut self, connect_timeout: Duration, timeout: Duration) -> Self {
+        self.client = Self::client(connect_timeout, timeout);
+        self.supported_timeout = timeout.min(SUPPORTED_TIMEOUT);
+        self
+    }
+
+    /// Sets how long a `/supported` response of the upstream is reused.
+    pub fn with_supported_ttl(mut self, ttl: Duration) -> Self {
+        self.supported = Arc::new(TtlCache::new(ttl, 1));
+        self
+    }
+
+    fn client(connect_timeout: Duration, timeout: Duration) -> Client {
+        Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .build()
+            .expect("HTTP client")
+    }
+
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
+    /// Whether requests for `slug` should be forwarded to this upstream.
+    ///
+    /// A request with an unknown slug (e.g. an unknown v1 network name) only matches
+    /// an upstream with no chain and no scheme filter.
+    pub fn matches(&self, slug: Option<&SchemeHandlerSlug>) -> bool {
+        match slug {
+            Some(slug) => self.matches_chain(&slug.chain_id) && self.matches_scheme(&slug.name),
+            None => self.chains.is_none() && self.schemes.is_none(),
+        }
+    }
+
+    fn matches_chain(&self, chain_id: &ChainId) -> bool {
+        self.chains
+            .as_ref()
+            .is_none_or(|pattern| pattern.matches(chain_id))
+    }
+
+    fn matches_scheme(&self, scheme: &str) -> bool {
+        self.schemes
+            .as_ref()
+            .is_none_or(|schemes| schemes.iter().any(|s| s == scheme))
+    }
+
+    /// Whether a payment kind advertised by the upstream falls within this upstream's filters.
+    ///
+    /// Kinds use a CAIP-2 network for v2 and a network name for v1.
+    fn matches_kind(&self, kind: &proto::SupportedPaymentKind) -> bool {
+        let chain_id = ChainId::from_str(&kind.network)
+            .ok()
+            .or_else(|| ChainId::from_network_name(&kind.network));
+        let matches_chain = match chain_id {
+            Some(chain_id) => self.matches_chain(&chain_id),
+            None => self.chains.is_none(),
+        };
+        matches_chain && self.matches_scheme(&kind.scheme)
+    }
+
+    async fn post<Req, Res>(
+        &self,
+        path: &str,
+        request: &Req,
+    ) -> Result<Res, FacilitatorUpstreamError>
+    where
+        Req: serde::Serialize,
+        Res: serde::de::DeserializeOwned,
+    {
+        let url = self.base_url.join(path)?;
+        let response = self.client.post(url).json(request).send().await?;
+        Self::parse(response).await
+    }
+
+    async fn parse<Res>(response: reqwest::Response) -> Result<Res, FacilitatorUpstreamError>
+    where
+        Res: serde::de::DeserializeOwned,
+    {
+        let status = response.status();
+        if status.is_success() {
+            Ok(response.json::<Res>().await?)
+        } else {
+            let body = response.json::<serde_json::Value>().await?;
+            Err(FacilitatorUpstreamError::Response { status, body })
+        }
+    }
+}
+
+impl Facilitator for FacilitatorUpstream {
+    type Error = FacilitatorUpstreamError;
+
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, Self::Error> {
+        self.post("verify", request).await
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, Self::Error> {
+        self.post("settle", request).await
+    }
+
+    /// Returns the upstream payment kinds and signers, restricted to this upstream's filters.
+    ///
+    /// Served from the cache while fresh. Failures are not cached.
+    async fn supported(&self) -> Result<proto::SupportedResponse, Self::Error> {
+        if let Some(supported) = self.supported.get(&()) {
+            return Ok(supported);
+        }
+        let url = self.base_url.join("supported")?;
+        let response = self
+            .client
+            .get(url)
+            .timeout(self.supported_timeout)
+            .send()
+            .await?;
+        let mut supported: proto::SupportedResponse = Self::parse(response).await?;
+        supported.kinds.retain(|kind| self.matches_kind(kind));
+        supported
+            .signers
+            .retain(|chain_id, _| self.matches_chain(chain_id));
+        self.supported.insert((), supported.clone());
+        Ok(supported)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FacilitatorUpstreamError {
+    #[error("Invalid upstream URL: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("Upstream request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    /// The upstream answered with a non-success status; the response is relayed as is.
+    #[error("Upstream responded with {status}")]
+    Response {
+        status: StatusCode,
+        body: serde_json::Value,
+    },
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use axum::Json;
+    use axum::Router;
+    use axum::routing::{get, post};
+    use serde_json::{Value, json};
+
+    /// Serves a fake facilitator advertising `supported`, and returns its base URL.
+    ///
+    /// `/verify` echoes the request, `/settle` always fails with 400.
+    pub(crate) async fn serve_upstream(supported: Value) -> Url {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let router = Router::new()
+            .route(
+                "/verify",
+                post(|Json(request): Json<Value>| async move {
+                    Json(json!({ "isValid": true, "request": request }))
+                }),
+            )
+            .route(
+                "/settle",
+                post(|| async {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({ "success": false, "errorReason": "invalid_scheme" })),
+                    )
+                }),
+            )
+            .route("/supported", get(move || async move { Json(supported) }));
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        url
+    }
+
+    pub(crate) fn kind(x402_version: u8, network: &str) -> Value {
+        json!({ "x402Version": x402_version, "scheme": "exact", "network": network })
+    }
+
+    #[tokio::test]
+    async fn test_forwarding() {
+        let url = serve_upstream(json!({ "kinds": [] })).await;
+        let upstream = FacilitatorUpstream::new(url, None, None);
+        let request: proto::VerifyRequest =
+            serde_json::from_value(json!({ "x402Version": 2 })).unwrap();
+
+        let response = upstream.verify(&request).await.unwrap();
+        let response = serde_json::to_value(response).unwrap();
+        assert_eq!(response["isValid"], true);
+        assert_eq!(response["request"]["x402Version"], 2);
+
+        match upstream.settle(&request).await.unwrap_err() {
+            FacilitatorUpstreamError::Response { status, body } => {
+                assert_eq!(status, StatusCode::BAD_REQUEST);
+                assert_eq!(body["errorReason"], "invalid_scheme");
+            }
+            error => panic!("unexpected error: {error}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_supported_is_filtered() {
+        let url = serve_upstream(json!({
+            "kinds": [
+                kind(2, "eip155:8453"),
+                kind(2, "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp"),
+                kind(1, "solana"),
+            ],
+            "signers": { "eip155:8453": ["0x01"], "solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp": ["Fee"] },
+        }))
+        .await;
+        let upstream =
+            FacilitatorUpstream::new(url, Some(ChainIdPattern::wildcard("solana")), None);
+
+        let supported = upstream.supported().await.unwrap();
+        let networks: Vec<_> = supported
+            .kinds
+            .iter()
+            .map(|kind| kind.network.as_str())
+            .collect();
+        assert_eq!(
+            networks,
+            vec!["solana:5eykt4UsFv8P8NJdTREpY1vzqKqZKvdp", "solana"]
+        );
+        assert_eq!(supported.signers.len(), 1);
+        assert_eq!(upstream.supported.get(&()).unwrap().kinds.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_upstream_times_out() {
+        // A non-routable address never completes the TCP handshake.
+        let url = Url::parse("http://10.255.255.1").unwrap();
+        let upstream = FacilitatorUpstream::new(url, None, None)
+            .with_timeouts(Duration::from_millis(100), Duration::from_millis(200));
+        let started = std::time::Instant::now();
+        assert!(matches!(
+            upstream.supported().await,
+            Err(FacilitatorUpstreamError::Http(_))
+        ));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
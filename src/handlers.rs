@@ -7,13 +7,16 @@ use axum::routing::{get, post};
 use axum::{Json, Router, response::IntoResponse};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::sync::Arc;
 use tracing::instrument;
 
 use crate::facilitator::Facilitator;
 use crate::facilitator_local::FacilitatorLocalError;
+use crate::facilitator_upstream::FacilitatorUpstreamError;
 use crate::proto;
 use crate::proto::{AsPaymentProblem, ErrorReason};
 use crate::scheme::X402SchemeFacilitatorError;
+use crate::tenant::{ResolvedTenant, TenantRegistry};
 
 #[instrument(skip_all)]
 pub async fn get_verify_info() -> impl IntoResponse {
@@ -25,6 +28,7 @@ pub async fn get_settle_info() -> impl IntoResponse {
     Json(json!({"endpoint": "/settle", "description": "POST to settle x402 payments"}))
 }
 
+#[allow(dead_code)] // Public for consumption by downstream crates.
 pub fn routes<A>() -> Router<A>
 where
     A: Facilitator + Clone + Send + Sync + 'static,
@@ -40,6 +44,50 @@ where
         .route("/supported", get(get_supported::<A>))
 }
 
+/// Routes of a multi-tenant facilitator.
+///
+/// The endpoints of [`routes`] are served both at the root, for the default tenant or
+/// the tenant of the request API key, and under the `/t/{tenant}` prefix.
+pub fn tenant_routes() -> Router<Arc<TenantRegistry>> {
+    let endpoints = Router::new()
+        .route("/verify", get(get_verify_info))
+        .route("/verify", post(post_tenant_verify))
+        .route("/settle", get(get_settle_info))
+        .route("/settle", post(post_tenant_settle))
+        .route("/health", get(get_tenant_health))
+        .route("/supported", get(get_tenant_supported));
+    Router::new()
+        .route("/", get(get_root))
+        .merge(endpoints.clone())
+        .nest("/t/{tenant}", endpoints)
+}
+
+#[instrument(skip_all, fields(tenant = %tenant.name()))]
+pub async fn get_tenant_supported(ResolvedTenant(tenant): ResolvedTenant) -> impl IntoResponse {
+    get_supported(State(tenant)).await
+}
+
+#[instrument(skip_all, fields(tenant = %tenant.name()))]
+pub async fn get_tenant_health(ResolvedTenant(tenant): ResolvedTenant) -> impl IntoResponse {
+    get_health(State(tenant)).await
+}
+
+#[instrument(skip_all, fields(tenant = %tenant.name()))]
+pub async fn post_tenant_verify(
+    ResolvedTenant(tenant): ResolvedTenant,
+    body: Json<proto::VerifyRequest>,
+) -> impl IntoResponse {
+    post_verify(State(tenant), body).await
+}
+
+#[instrument(skip_all, fields(tenant = %tenant.name()))]
+pub async fn post_tenant_settle(
+    ResolvedTenant(tenant): ResolvedTenant,
+    body: Json<proto::SettleRequest>,
+) -> impl IntoResponse {
+    post_settle(State(tenant), body).await
+}
+
 /// `GET /`: Returns an HTML homepage
 #[instrument(skip_all)]
 pub async fn get_root() -> impl IntoResponse {
@@ -163,6 +211,16 @@ impl IntoResponse for FacilitatorLocalError {
                 };
                 (status_code, Json(settlement_error_response)).into_response()
             }
+            FacilitatorLocalError::Upstream(upstream_error) => match upstream_error {
+                FacilitatorUpstreamError::Response { status, body } => {
+                    (status, Json(body)).into_response()
+                }
+                upstream_error => (
+                    StatusCode::BAD_GATEWAY,
+                    Json(json!({ "error": upstream_error.to_string() })),
+                )
+                    .into_response(),
+            },
         }
     }
 }
@@ -20,10 +20,12 @@ pub mod chain;
 pub mod config;
 pub mod facilitator;
 pub mod facilitator_local;
+pub mod facilitator_upstream;
 pub mod handlers;
 pub mod networks;
 pub mod proto;
 pub mod scheme;
+pub mod tenant;
 pub mod timestamp;
 pub mod util;
 
@@ -9,6 +9,7 @@
 //! - `GET /settle` – Supported settlement schema
 //! - `POST /settle` – Settle an accepted payment payload on-chain
 //! - `GET /supported` – List supported payment kinds (version/scheme/network)
+//! - `/t/{tenant}/...` – The same endpoints, for a tenant configured under `tenants`
 //!
 //! This server includes:
 //! - OpenTelemetry tracing via `TraceLayer`
@@ -24,10 +25,12 @@ mod chain;
 mod config;
 mod facilitator;
 mod facilitator_local;
+mod facilitator_upstream;
 mod handlers;
 mod networks;
 mod proto;
 mod scheme;
+mod tenant;
 mod timestamp;
 mod util;
 
@@ -38,10 +41,8 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use tower_http::cors;
 
-use crate::chain::ChainRegistry;
 use crate::config::Config;
-use crate::facilitator_local::FacilitatorLocal;
-use crate::scheme::{SchemeBlueprints, SchemeRegistry};
+use crate::tenant::TenantRegistry;
 use crate::util::{SigDown, Telemetry};
 
 /// Initializes the x402 facilitator server.
@@ -64,16 +65,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let config = Config::load()?;
 
-    let chain_registry = ChainRegistry::from_config(config.chains()).await?;
-    let scheme_blueprints = SchemeBlueprints::full();
-    let scheme_registry =
-        SchemeRegistry::build(chain_registry, scheme_blueprints, config.schemes());
-
-    let facilitator = FacilitatorLocal::new(scheme_registry);
-    let axum_state = Arc::new(facilitator);
+    let tenant_registry = TenantRegistry::from_config(&config).await?;
+    let axum_state = Arc::new(tenant_registry);
 
     let http_endpoints = Router::new()
-        .merge(handlers::routes().with_state(axum_state))
+        .merge(handlers::tenant_routes().with_state(axum_state))
         .layer(telemetry.http_tracing())
         .layer(
             cors::CorsLayer::new()
@@ -4,7 +4,7 @@ use std::fmt;
 use std::fmt::Display;
 use std::str::FromStr;
 
-use crate::chain::ChainId;
+use crate::chain::{ChainId, Finality};
 use crate::scheme::SchemeHandlerSlug;
 
 pub mod client;
@@ -128,6 +128,16 @@ impl VerifyRequest {
         self.0
     }
 
+    /// Settlement finality requested with a settle request, if any.
+    pub fn finality(&self) -> Result<Option<Finality>, PaymentVerificationError> {
+        let finality = self
+            .0
+            .get("finality")
+            .map(Finality::deserialize)
+            .transpose()?;
+        Ok(finality)
+    }
+
     pub fn scheme_handler_slug(&self) -> Option<SchemeHandlerSlug> {
         let x402_version = self.0.get("x402Version")?.as_u64()?;
         let x402_version = X402Version::try_from(x402_version).ok()?;
@@ -186,6 +196,8 @@ pub enum PaymentVerificationError {
     AssetMismatch,
     #[error("Onchain balance is not enough to cover the payment amount")]
     InsufficientFunds,
+    #[error("Onchain allowance is not enough to cover the payment amount")]
+    InsufficientAllowance,
     #[error("{0}")]
     InvalidSignature(String),
     #[error("{0}")]
@@ -204,6 +216,7 @@ impl AsPaymentProblem for PaymentVerificationError {
             PaymentVerificationError::InvalidFormat(_) => ErrorReason::InvalidFormat,
             PaymentVerificationError::InvalidPaymentAmount => ErrorReason::InvalidPaymentAmount,
             PaymentVerificationError::InsufficientFunds => ErrorReason::InsufficientFunds,
+            PaymentVerificationError::InsufficientAllowance => ErrorReason::InsufficientAllowance,
             PaymentVerificationError::Early => ErrorReason::InvalidPaymentEarly,
             PaymentVerificationError::Expired => ErrorReason::InvalidPaymentExpired,
             PaymentVerificationError::ChainIdMismatch => ErrorReason::ChainIdMismatch,
@@ -243,6 +256,7 @@ pub enum ErrorReason {
     InvalidSignature,
     TransactionSimulation,
     InsufficientFunds,
+    InsufficientAllowance,
     UnsupportedChain,
     UnsupportedScheme,
     UnexpectedError,
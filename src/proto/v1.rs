@@ -1,3 +1,4 @@
+use crate::chain::Finality;
 use crate::proto;
 
 use serde::de::DeserializeOwned;
@@ -50,10 +51,12 @@ impl Display for X402Version1 {
 }
 
 pub enum SettleResponse {
+    /// `finality` is how far the transaction had progressed when the facilitator responded.
     Success {
         payer: String,
         transaction: String,
         network: String,
+        finality: Option<Finality>,
     },
     Error {
         reason: String,
@@ -79,6 +82,8 @@ struct SettleResponseWire {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transaction: Option<String>,
     pub network: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finality: Option<Finality>,
 }
 
 impl Serialize for SettleResponse {
@@ -91,12 +96,14 @@ impl Serialize for SettleResponse {
                 payer,
                 transaction,
                 network,
+                finality,
             } => SettleResponseWire {
                 success: true,
                 error_reason: None,
                 payer: Some(payer.clone()),
                 transaction: Some(transaction.clone()),
                 network: network.clone(),
+                finality: *finality,
             },
             SettleResponse::Error { reason, network } => SettleResponseWire {
                 success: false,
@@ -104,6 +111,7 @@ impl Serialize for SettleResponse {
                 payer: None,
                 transaction: None,
                 network: network.clone(),
+                finality: None,
             },
         };
         wire.serialize(serializer)
@@ -128,6 +136,7 @@ impl<'de> Deserialize<'de> for SettleResponse {
                     payer,
                     transaction,
                     network: wire.network,
+                    finality: wire.finality,
                 })
             }
             false => {
@@ -150,7 +159,8 @@ impl<'de> Deserialize<'de> for SettleResponse {
 #[derive(Debug)]
 pub enum VerifyResponse {
     /// The payload matches the requirements and passes all checks.
-    Valid { payer: String },
+    /// `memos` carries memo instructions found in the payment transaction, if any.
+    Valid { payer: String, memos: Vec<String> },
     /// The payload was well-formed but failed verification due to the specified [`FacilitatorErrorReason`]
     Invalid {
         reason: String,
@@ -171,7 +181,18 @@ impl VerifyResponse {
     ///
     /// Indicates that the provided payment payload has been validated against the payment requirements.
     pub fn valid(payer: String) -> Self {
-        VerifyResponse::Valid { payer }
+        VerifyResponse::Valid {
+            payer,
+            memos: Vec::new(),
+        }
+    }
+
+    /// Attaches memos found in the payment transaction to a successful verification response.
+    pub fn with_memos(self, memos: Vec<String>) -> Self {
+        match self {
+            VerifyResponse::Valid { payer, .. } => VerifyResponse::Valid { payer, memos },
+            invalid => invalid,
+        }
     }
 
     /// Constructs a failed verification response with the given `payer` address and error `reason`.
@@ -192,6 +213,8 @@ struct VerifyResponseWire {
     payer: Option<String>,
     #[serde(default)]
     invalid_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    memos: Vec<String>,
 }
 
 impl Serialize for VerifyResponse {
@@ -200,15 +223,17 @@ impl Serialize for VerifyResponse {
         S: Serializer,
     {
         let wire = match self {
-            VerifyResponse::Valid { payer } => VerifyResponseWire {
+            VerifyResponse::Valid { payer, memos } => VerifyResponseWire {
                 is_valid: true,
                 payer: Some(payer.clone()),
                 invalid_reason: None,
+                memos: memos.clone(),
             },
             VerifyResponse::Invalid { reason, payer } => VerifyResponseWire {
                 is_valid: false,
                 payer: payer.clone(),
                 invalid_reason: Some(reason.clone()),
+                memos: Vec::new(),
             },
         };
         wire.serialize(serializer)
@@ -226,7 +251,10 @@ impl<'de> Deserialize<'de> for VerifyResponse {
                 let payer = wire
                     .payer
                     .ok_or_else(|| serde::de::Error::missing_field("payer"))?;
-                Ok(VerifyResponse::Valid { payer })
+                Ok(VerifyResponse::Valid {
+                    payer,
+                    memos: wire.memos,
+                })
             }
             false => {
                 let reason = wire
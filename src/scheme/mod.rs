@@ -1,6 +1,12 @@
+pub mod permit2;
+pub mod sidecar;
 pub mod v1_eip155_exact;
 pub mod v1_solana_exact;
+pub mod v2_eip155_eip2612;
+pub mod v2_eip155_erc4337;
 pub mod v2_eip155_exact;
+pub mod v2_eip155_permit2;
+pub mod v2_eip155_upto;
 pub mod v2_solana_exact;
 
 pub use v1_eip155_exact::V1Eip155Exact;
@@ -13,8 +19,13 @@ use crate::chain::{ChainId, ChainProvider, ChainProviderOps, ChainRegistry};
 use crate::config::SchemeConfig;
 use crate::proto;
 use crate::proto::{AsPaymentProblem, ErrorReason, PaymentProblem, PaymentVerificationError};
+use crate::scheme::sidecar::Sidecar;
 use crate::scheme::v1_solana_exact::V1SolanaExact;
+use crate::scheme::v2_eip155_eip2612::V2Eip155Eip2612;
+use crate::scheme::v2_eip155_erc4337::V2Eip155Erc4337;
 use crate::scheme::v2_eip155_exact::V2Eip155Exact;
+use crate::scheme::v2_eip155_permit2::V2Eip155Permit2;
+use crate::scheme::v2_eip155_upto::V2Eip155Upto;
 use crate::scheme::v2_solana_exact::V2SolanaExact;
 
 #[async_trait::async_trait]
@@ -28,6 +39,12 @@ pub trait X402SchemeFacilitator: Send + Sync {
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError>;
     async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError>;
+    /// The x402 version and scheme name served by this handler, when they are not those of its blueprint.
+    ///
+    /// Used by handlers whose scheme is only known from configuration, like [`sidecar::SidecarFacilitator`].
+    fn scheme_override(&self) -> Option<(u8, &str)> {
+        None
+    }
 }
 
 pub trait X402SchemeBlueprint: X402SchemeId + X402SchemeFacilitatorBuilder {}
@@ -96,7 +113,12 @@ impl SchemeBlueprints {
             .and_register(V1Eip155Exact)
             .and_register(V1SolanaExact)
             .and_register(V2Eip155Exact)
+            .and_register(V2Eip155Permit2)
+            .and_register(V2Eip155Eip2612)
+            .and_register(V2Eip155Erc4337)
+            .and_register(V2Eip155Upto)
             .and_register(V2SolanaExact)
+            .and_register(Sidecar)
     }
 
     pub fn and_register<B: X402SchemeBlueprint + 'static>(mut self, blueprint: B) -> Self {
@@ -151,10 +173,13 @@ impl Debug for SchemeRegistry {
 }
 
 impl SchemeRegistry {
+    /// Builds a handler for every enabled scheme entry.
+    ///
+    /// When two entries resolve to the same [`SchemeHandlerSlug`], the last one wins.
     pub fn build(
         chains: ChainRegistry,
         blueprints: SchemeBlueprints,
-        config: &Vec<SchemeConfig>,
+        config: &[SchemeConfig],
     ) -> Self {
         let mut handlers = HashMap::with_capacity(config.len());
         for config in config {
@@ -188,12 +213,14 @@ impl SchemeRegistry {
                     continue;
                 }
             };
-            let slug = SchemeHandlerSlug::new(
-                chain_id.clone(),
-                blueprint.x402_version(),
-                blueprint.scheme().to_string(),
-            );
-            tracing::info!(chain_id = %chain_id, scheme = %blueprint.scheme(), id=blueprint.id(), "Registered scheme handler");
+            let (x402_version, scheme) = handler
+                .scheme_override()
+                .unwrap_or((blueprint.x402_version(), blueprint.scheme()));
+            let slug = SchemeHandlerSlug::new(chain_id.clone(), x402_version, scheme.to_string());
+            if handlers.contains_key(&slug) {
+                tracing::warn!(%slug, id = blueprint.id(), "Scheme handled by more than one scheme entry, the last one wins");
+            }
+            tracing::info!(chain_id = %chain_id, scheme = %scheme, id=blueprint.id(), "Registered scheme handler");
             handlers.insert(slug, handler);
         }
         Self(handlers)
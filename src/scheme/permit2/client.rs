@@ -0,0 +1,159 @@
+use alloy_primitives::{B256, U256};
+use async_trait::async_trait;
+use rand::{Rng, rng};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::marker::PhantomData;
+
+use crate::chain::eip155::{ChecksummedAddress, Eip155ChainReference, TokenAmount};
+use crate::proto::client::{PaymentCandidate, PaymentCandidateSigner, X402Error, X402SchemeClient};
+use crate::proto::v2::ResourceInfo;
+use crate::proto::{PaymentRequired, v2};
+use crate::scheme::X402SchemeId;
+use crate::scheme::permit2::{PaymentRequirementsExtra, Permit2Authorization, Permit2Payload};
+use crate::scheme::v1_eip155_exact::client::SignerLike;
+use crate::timestamp::UnixTimestamp;
+use crate::util::Base64Bytes;
+
+/// A scheme paid with a Permit2 witness transfer, signed by a [`Permit2Client`].
+pub trait Permit2Witness: X402SchemeId + Send + Sync + 'static {
+    /// Scheme name of the payment requirements.
+    type Scheme: Clone + Serialize + DeserializeOwned + Send + Sync + 'static;
+    /// Permit signed by the payer: the authorization, and the fields of the witness.
+    type Permit: Serialize + Send + Sync + 'static;
+
+    /// Permit for `authorization`, paying for `resource`.
+    fn permit(authorization: Permit2Authorization, resource: &ResourceInfo) -> Self::Permit;
+
+    /// Hash of the message a payer signs to authorize `permit`.
+    fn signing_hash(chain: &Eip155ChainReference, permit: &Self::Permit) -> B256;
+}
+
+type PaymentRequirements<K> = v2::PaymentRequirements<
+    <K as Permit2Witness>::Scheme,
+    TokenAmount,
+    ChecksummedAddress,
+    PaymentRequirementsExtra,
+>;
+
+/// Client for a Permit2 scheme `K`: signs a Permit2 witness transfer of the required amount
+/// to the advertised spender.
+///
+/// The payer must have approved the Permit2 contract on the token beforehand.
+#[derive(Debug)]
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub struct Permit2Client<S, K> {
+    signer: S,
+    scheme: K,
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+impl<S, K> Permit2Client<S, K> {
+    pub fn with_scheme(signer: S, scheme: K) -> Self {
+        Self { signer, scheme }
+    }
+}
+
+impl<S, K: X402SchemeId> X402SchemeId for Permit2Client<S, K> {
+    fn namespace(&self) -> &str {
+        self.scheme.namespace()
+    }
+
+    fn scheme(&self) -> &str {
+        self.scheme.scheme()
+    }
+}
+
+impl<S, K> X402SchemeClient for Permit2Client<S, K>
+where
+    S: SignerLike + Clone + Send + Sync + 'static,
+    K: Permit2Witness,
+{
+    fn accept(&self, payment_required: &PaymentRequired) -> Vec<PaymentCandidate> {
+        let payment_required = match payment_required {
+            PaymentRequired::V2(payment_required) => payment_required,
+            PaymentRequired::V1(_) => {
+                return vec![];
+            }
+        };
+        payment_required
+            .accepts
+            .iter()
+            .filter_map(|v| {
+                let requirements = PaymentRequirements::<K>::deserialize(v).ok()?;
+                // The spender is required to build the permit
+                requirements.extra.as_ref()?;
+                let chain_reference = Eip155ChainReference::try_from(&requirements.network).ok()?;
+                let candidate = PaymentCandidate {
+                    chain_id: requirements.network.clone(),
+                    asset: requirements.asset.to_string(),
+                    amount: requirements.amount.into(),
+                    scheme: self.scheme().to_string(),
+                    x402_version: self.x402_version(),
+                    pay_to: requirements.pay_to.to_string(),
+                    signer: Box::new(PayloadSigner::<S, K> {
+                        resource_info: payment_required.resource.clone(),
+                        signer: self.signer.clone(),
+                        chain_reference,
+                        requirements,
+                        scheme: PhantomData,
+                    }),
+                };
+                Some(candidate)
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+struct PayloadSigner<S, K: Permit2Witness> {
+    signer: S,
+    resource_info: ResourceInfo,
+    chain_reference: Eip155ChainReference,
+    requirements: PaymentRequirements<K>,
+    scheme: PhantomData<K>,
+}
+
+#[async_trait]
+impl<S, K> PaymentCandidateSigner for PayloadSigner<S, K>
+where
+    S: Sync + SignerLike,
+    K: Permit2Witness,
+{
+    async fn sign_payment(&self) -> Result<String, X402Error> {
+        let extra = self.requirements.extra.as_ref().ok_or_else(|| {
+            X402Error::SigningError("Missing Permit2 spender in requirements".to_string())
+        })?;
+        let nonce: [u8; 32] = rng().random();
+        let authorization = Permit2Authorization {
+            from: self.signer.address(),
+            token: self.requirements.asset.into(),
+            amount: self.requirements.amount.into(),
+            spender: extra.spender.into(),
+            nonce: U256::from_be_bytes(nonce),
+            deadline: UnixTimestamp::now() + self.requirements.max_timeout_seconds,
+            to: self.requirements.pay_to.into(),
+        };
+        let permit = K::permit(authorization, &self.resource_info);
+        let hash = K::signing_hash(&self.chain_reference, &permit);
+        let signature = self
+            .signer
+            .sign_hash(&hash)
+            .await
+            .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+
+        let payload = v2::PaymentPayload {
+            x402_version: v2::X402Version2,
+            accepted: self.requirements.clone(),
+            resource: self.resource_info.clone(),
+            payload: Permit2Payload {
+                signature: signature.as_bytes().into(),
+                permit,
+            },
+        };
+        let json = serde_json::to_vec(&payload)?;
+        let b64 = Base64Bytes::encode(&json);
+
+        Ok(b64.to_string())
+    }
+}
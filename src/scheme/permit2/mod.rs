@@ -0,0 +1,489 @@
+//! Permit2 witness transfers, shared by the schemes whose payer signs a Permit2
+//! `permitWitnessTransferFrom`: [`crate::scheme::v2_eip155_upto`] and
+//! [`crate::scheme::v2_eip155_permit2`].
+//!
+//! The payer authorizes a facilitator signer (the spender, advertised as `extra.spender`
+//! in `/supported`) to transfer the permitted amount of a token to `to`. Schemes differ
+//! in the witness they bind to the transfer, and in the amount they settle.
+//!
+//! The payer must have approved the canonical [`PERMIT2_ADDRESS`] contract on the token.
+
+pub mod client;
+
+use alloy_contract::SolCallBuilder;
+use alloy_primitives::{Address, B256, Bytes, TxHash, U256, address, keccak256};
+use alloy_provider::Provider;
+use alloy_sol_types::{Eip712Domain, SolStruct, SolValue, eip712_domain, sol};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{Instrument, instrument};
+use tracing_core::Level;
+
+use crate::chain::ChainProviderOps;
+use crate::chain::eip155::{
+    ChecksummedAddress, Eip155ChainProvider, Eip155MetaTransactionProvider, MetaTransaction,
+};
+use crate::proto;
+use crate::proto::PaymentVerificationError;
+use crate::scheme::v1_eip155_exact::{
+    Eip155ExactConfig, Eip155ExactError, assert_enough_value, assert_time,
+};
+use crate::timestamp::UnixTimestamp;
+
+/// Canonical Permit2 contract, deployed at the same address on all supported EVM chains.
+pub const PERMIT2_ADDRESS: Address = address!("0x000000000022D473030F116dDEE9F6B43aC78BA3");
+
+sol! {
+    #[allow(missing_docs)]
+    #[derive(Debug)]
+    #[sol(rpc)]
+    interface IPermit2 {
+        struct TokenPermissions {
+            address token;
+            uint256 amount;
+        }
+
+        struct PermitTransferFrom {
+            TokenPermissions permitted;
+            uint256 nonce;
+            uint256 deadline;
+        }
+
+        struct SignatureTransferDetails {
+            address to;
+            uint256 requestedAmount;
+        }
+
+        function permitWitnessTransferFrom(
+            PermitTransferFrom memory permit,
+            SignatureTransferDetails calldata transferDetails,
+            address owner,
+            bytes32 witness,
+            string calldata witnessTypeString,
+            bytes calldata signature
+        ) external;
+    }
+
+    #[allow(missing_docs)]
+    #[derive(Debug)]
+    #[sol(rpc)]
+    interface IERC20 {
+        function balanceOf(address account) external view returns (uint256);
+        function allowance(address owner, address spender) external view returns (uint256);
+    }
+
+    /// Permit2 token permissions, as hashed in the EIP-712 message.
+    #[derive(Debug)]
+    struct TokenPermissions {
+        address token;
+        uint256 amount;
+    }
+}
+
+/// Start of the `PermitWitnessTransferFrom` type, completed by the witness type string.
+const PERMIT_WITNESS_TRANSFER_FROM_STUB: &str = "PermitWitnessTransferFrom(TokenPermissions permitted,address spender,uint256 nonce,uint256 deadline,";
+
+/// EIP-712 `PermitWitnessTransferFrom` message signed by the payer, binding the witness `W`
+/// of a scheme to the transfer.
+///
+/// Permit2 builds the type of this message from the witness type string passed along with
+/// the transfer, see [`Self::witness_type_string`].
+#[derive(Debug, Clone)]
+pub struct PermitWitnessTransferFrom<W> {
+    pub permitted: TokenPermissions,
+    pub spender: Address,
+    pub nonce: U256,
+    pub deadline: U256,
+    pub witness: W,
+}
+
+impl<W: SolStruct> PermitWitnessTransferFrom<W> {
+    /// Message authorizing the transfer of `authorization`, bound to `witness`.
+    pub fn new(authorization: &Permit2Authorization, witness: W) -> Self {
+        Self {
+            permitted: TokenPermissions {
+                token: authorization.token,
+                amount: authorization.amount,
+            },
+            spender: authorization.spender,
+            nonce: authorization.nonce,
+            deadline: U256::from(authorization.deadline.as_secs()),
+            witness,
+        }
+    }
+
+    /// Witness type string appended by Permit2 to the `PermitWitnessTransferFrom` type:
+    /// the witness field, then the referenced types sorted by name.
+    pub fn witness_type_string() -> String {
+        let mut types = W::eip712_components();
+        types.push(W::eip712_root_type());
+        types.push(TokenPermissions::eip712_root_type());
+        types.sort();
+        types.dedup();
+        format!("{} witness){}", W::NAME, types.concat())
+    }
+
+    /// EIP-712 type of the message.
+    pub fn eip712_encode_type() -> String {
+        format!(
+            "{PERMIT_WITNESS_TRANSFER_FROM_STUB}{}",
+            Self::witness_type_string()
+        )
+    }
+
+    /// Hash of the message a payer signs, within the Permit2 domain of `chain_id`.
+    pub fn eip712_signing_hash(&self, chain_id: u64) -> B256 {
+        let hash_struct = keccak256(
+            (
+                keccak256(Self::eip712_encode_type()),
+                self.permitted.eip712_hash_struct(),
+                self.spender,
+                self.nonce,
+                self.deadline,
+                self.witness.eip712_hash_struct(),
+            )
+                .abi_encode(),
+        );
+        let domain_separator = permit2_domain(chain_id).hash_struct();
+        keccak256([&[0x19, 0x01], &domain_separator[..], &hash_struct[..]].concat())
+    }
+}
+
+/// EIP-712 domain of the Permit2 contract on `chain_id`.
+pub fn permit2_domain(chain_id: u64) -> Eip712Domain {
+    eip712_domain! {
+        name: "Permit2",
+        chain_id: chain_id,
+        verifying_contract: PERMIT2_ADDRESS,
+    }
+}
+
+/// Fields of the Permit2 `PermitWitnessTransferFrom` message common to all witnesses,
+/// with the recipient `to` that every witness binds.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Permit2Authorization {
+    pub from: Address,
+    pub token: Address,
+    /// Maximum amount the spender may transfer.
+    pub amount: U256,
+    pub spender: Address,
+    /// Permit2 unordered nonce.
+    pub nonce: U256,
+    pub deadline: UnixTimestamp,
+    pub to: Address,
+}
+
+/// Signed Permit2 witness transfer, as sent in the payment payload.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Permit2Payload<TPermit> {
+    pub signature: Bytes,
+    pub permit: TPermit,
+}
+
+/// Extra data of payment requirements paid through Permit2, as advertised in `/supported`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequirementsExtra {
+    /// Facilitator signer the payer must authorize as the Permit2 spender.
+    pub spender: ChecksummedAddress,
+}
+
+/// A signed Permit2 witness transfer, ready to be submitted by its spender.
+#[derive(Debug)]
+pub struct Permit2Transfer<'a> {
+    pub authorization: &'a Permit2Authorization,
+    /// EIP-712 hash of the witness.
+    pub witness: B256,
+    /// Witness type string appended by Permit2 to the `PermitWitnessTransferFrom` type.
+    pub witness_type_string: &'static str,
+    pub signature: &'a Bytes,
+}
+
+/// Checks the authorization against the payment requirements:
+/// - Recipient and token match.
+/// - Spender is one of the facilitator signers.
+/// - Permit deadline not reached.
+/// - Permitted amount covers the required amount.
+/// - Sufficient on-chain balance and Permit2 allowance.
+#[instrument(skip_all, err)]
+pub async fn assert_valid_authorization(
+    provider: &Eip155ChainProvider,
+    config: &Eip155ExactConfig,
+    authorization: &Permit2Authorization,
+    pay_to: Address,
+    asset: Address,
+    amount_required: U256,
+) -> Result<(), Eip155ExactError> {
+    if authorization.to != pay_to {
+        return Err(PaymentVerificationError::RecipientMismatch.into());
+    }
+    if authorization.token != asset {
+        return Err(PaymentVerificationError::AssetMismatch.into());
+    }
+    if !provider.is_signer(&authorization.spender) {
+        return Err(PaymentVerificationError::InvalidFormat(
+            "Permit spender is not a facilitator signer".to_string(),
+        )
+        .into());
+    }
+    assert_time(
+        UnixTimestamp::from_secs(0),
+        authorization.deadline,
+        config.clock_skew_secs,
+    )?;
+    config.assert_asset(&authorization.token)?;
+    config.assert_amount(&amount_required)?;
+    assert_enough_value(&authorization.amount, &amount_required)?;
+    assert_enough_permit2_funds(
+        provider.inner(),
+        &authorization.token,
+        &authorization.from,
+        amount_required,
+    )
+    .await
+}
+
+/// Checks that `owner` holds at least `amount` of `token` and has approved Permit2 for it.
+#[instrument(skip_all, err, fields(
+    owner = %owner,
+    amount = %amount,
+    token_contract = %token
+))]
+pub async fn assert_enough_permit2_funds<P: Provider>(
+    provider: P,
+    token: &Address,
+    owner: &Address,
+    amount: U256,
+) -> Result<(), Eip155ExactError> {
+    let token = IERC20::new(*token, provider);
+    let balance = token
+        .balanceOf(*owner)
+        .call()
+        .into_future()
+        .instrument(tracing::info_span!(
+            "fetch_token_balance",
+            token_contract = %token.address(),
+            sender = %owner,
+            otel.kind = "client"
+        ))
+        .await?;
+    if balance < amount {
+        return Err(PaymentVerificationError::InsufficientFunds.into());
+    }
+    let allowance = token
+        .allowance(*owner, PERMIT2_ADDRESS)
+        .call()
+        .into_future()
+        .instrument(tracing::info_span!(
+            "fetch_permit2_allowance",
+            token_contract = %token.address(),
+            sender = %owner,
+            otel.kind = "client"
+        ))
+        .await?;
+    if allowance < amount {
+        return Err(PaymentVerificationError::InsufficientAllowance.into());
+    }
+    Ok(())
+}
+
+/// Builds the Permit2 call transferring `amount` to the witnessed recipient, sent from the spender.
+fn permit_witness_transfer_call<'a, P: Provider>(
+    provider: &'a P,
+    transfer: &Permit2Transfer,
+    amount: U256,
+) -> SolCallBuilder<&'a P, IPermit2::permitWitnessTransferFromCall> {
+    let authorization = transfer.authorization;
+    let call = IPermit2::permitWitnessTransferFromCall {
+        permit: IPermit2::PermitTransferFrom {
+            permitted: IPermit2::TokenPermissions {
+                token: authorization.token,
+                amount: authorization.amount,
+            },
+            nonce: authorization.nonce,
+            deadline: U256::from(authorization.deadline.as_secs()),
+        },
+        transferDetails: IPermit2::SignatureTransferDetails {
+            to: authorization.to,
+            requestedAmount: amount,
+        },
+        owner: authorization.from,
+        witness: transfer.witness,
+        witnessTypeString: transfer.witness_type_string.to_string(),
+        signature: transfer.signature.clone(),
+    };
+    SolCallBuilder::new_sol(provider, &PERMIT2_ADDRESS, &call).from(authorization.spender)
+}
+
+/// Simulates the transfer of `amount` from the spender.
+pub async fn simulate_transfer<P: Provider>(
+    provider: &P,
+    transfer: &Permit2Transfer<'_>,
+    amount: U256,
+) -> Result<(), Eip155ExactError> {
+    let authorization = transfer.authorization;
+    permit_witness_transfer_call(provider, transfer, amount)
+        .call()
+        .into_future()
+        .instrument(tracing::info_span!("call_permitWitnessTransferFrom",
+            from = %authorization.from,
+            to = %authorization.to,
+            amount = %amount,
+            spender = %authorization.spender,
+            nonce = %authorization.nonce,
+            token_contract = %authorization.token,
+            otel.kind = "client",
+        ))
+        .await
+        .map_err(as_simulation_error)?;
+    Ok(())
+}
+
+/// Transfers `amount` through Permit2, sending the transaction from the authorized spender.
+pub async fn settle_transfer<P, E>(
+    provider: P,
+    transfer: &Permit2Transfer<'_>,
+    amount: U256,
+    confirmations: u64,
+) -> Result<TxHash, Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let authorization = transfer.authorization;
+    let call = permit_witness_transfer_call(provider.inner(), transfer, amount);
+    let receipt = Eip155MetaTransactionProvider::send_transaction(
+        &provider,
+        MetaTransaction {
+            to: PERMIT2_ADDRESS,
+            calldata: call.calldata().clone(),
+            confirmations,
+            from: Some(authorization.spender),
+        },
+    )
+    .instrument(tracing::info_span!("call_permitWitnessTransferFrom",
+        from = %authorization.from,
+        to = %authorization.to,
+        amount = %amount,
+        spender = %authorization.spender,
+        nonce = %authorization.nonce,
+        token_contract = %authorization.token,
+        otel.kind = "client",
+    ))
+    .await?;
+    if receipt.status() {
+        tracing::event!(Level::INFO,
+            status = "ok",
+            tx = %receipt.transaction_hash,
+            "permitWitnessTransferFrom succeeded"
+        );
+        Ok(receipt.transaction_hash)
+    } else {
+        tracing::event!(
+            Level::WARN,
+            status = "failed",
+            tx = %receipt.transaction_hash,
+            "permitWitnessTransferFrom failed"
+        );
+        Err(Eip155ExactError::TransactionReverted(
+            receipt.transaction_hash,
+        ))
+    }
+}
+
+/// Supported kind of a Permit2 `scheme`, advertising the next facilitator signer as the spender to authorize.
+pub fn supported(provider: &Eip155ChainProvider, scheme: &str) -> proto::SupportedResponse {
+    let chain_id = provider.chain_id();
+    let kinds = {
+        let spender = provider.next_signer_address().into();
+        let extra = Some(serde_json::to_value(PaymentRequirementsExtra { spender }).unwrap());
+        vec![proto::SupportedPaymentKind {
+            x402_version: proto::X402Version::v2().into(),
+            scheme: scheme.to_string(),
+            network: chain_id.clone().into(),
+            extra,
+        }]
+    };
+    let signers = {
+        let mut signers = HashMap::with_capacity(1);
+        signers.insert(chain_id, provider.signer_addresses());
+        signers
+    };
+    proto::SupportedResponse {
+        kinds,
+        extensions: Vec::new(),
+        signers,
+    }
+}
+
+/// Reports a reverted simulation as a verification failure, keeping transport errors as is.
+pub fn as_simulation_error(e: alloy_contract::Error) -> Eip155ExactError {
+    match e {
+        alloy_contract::Error::TransportError(e) if e.as_error_resp().is_some() => {
+            PaymentVerificationError::TransactionSimulation(e.to_string()).into()
+        }
+        e => e.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod concrete {
+        use super::super::TokenPermissions;
+        use alloy_sol_types::sol;
+
+        sol! {
+            #[derive(Debug)]
+            struct Witness {
+                address to;
+                string resource;
+            }
+
+            #[derive(Debug)]
+            struct PermitWitnessTransferFrom {
+                TokenPermissions permitted;
+                address spender;
+                uint256 nonce;
+                uint256 deadline;
+                Witness witness;
+            }
+        }
+    }
+
+    #[test]
+    fn test_signing_hash_matches_concrete_eip712_struct() {
+        let authorization = Permit2Authorization {
+            from: Address::repeat_byte(1),
+            token: Address::repeat_byte(2),
+            amount: U256::from(1000),
+            spender: Address::repeat_byte(3),
+            nonce: U256::from(7),
+            deadline: UnixTimestamp::from_secs(1_700_000_000),
+            to: Address::repeat_byte(4),
+        };
+        let witness = concrete::Witness {
+            to: authorization.to,
+            resource: "https://example.com".to_string(),
+        };
+        let message = PermitWitnessTransferFrom::new(&authorization, witness.clone());
+        let expected = concrete::PermitWitnessTransferFrom {
+            permitted: message.permitted.clone(),
+            spender: message.spender,
+            nonce: message.nonce,
+            deadline: message.deadline,
+            witness,
+        };
+        assert_eq!(
+            PermitWitnessTransferFrom::<concrete::Witness>::eip712_encode_type(),
+            concrete::PermitWitnessTransferFrom::eip712_encode_type()
+        );
+        assert_eq!(
+            message.eip712_signing_hash(8453),
+            expected.eip712_signing_hash(&permit2_domain(8453))
+        );
+    }
+}
@@ -0,0 +1,346 @@
+//! Out-of-process scheme handlers.
+//!
+//! The [`Sidecar`] blueprint forwards verify, settle and supported calls to a separate
+//! service over HTTP/JSON, so that experimental schemes can live outside this crate
+//! while still being routed through the facilitator registry, auth and metrics.
+//!
+//! A sidecar is configured as a regular scheme entry:
+//! ```json
+//! {
+//!   "id": "sidecar",
+//!   "chains": "eip155:8453",
+//!   "config": { "url": "http://localhost:9000", "scheme": "upto", "x402_version": 2 }
+//! }
+//! ```
+//!
+//! The sidecar exposes `POST /verify`, `POST /settle` and `GET /supported` with the same
+//! request and response bodies as the facilitator itself. Only the payment kinds and signers
+//! of the configured chain, scheme and version are kept from its `/supported` response.
+
+use reqwest::Client;
+use serde::Deserialize;
+use std::error::Error;
+use std::str::FromStr;
+use std::time::Duration;
+use url::Url;
+
+use crate::chain::{ChainId, ChainProvider, ChainProviderOps};
+use crate::proto;
+use crate::proto::{ErrorReason, PaymentVerificationError};
+use crate::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError, X402SchemeId,
+};
+
+pub struct Sidecar;
+
+impl X402SchemeId for Sidecar {
+    fn namespace(&self) -> &str {
+        "*"
+    }
+
+    fn scheme(&self) -> &str {
+        "sidecar"
+    }
+
+    fn id(&self) -> String {
+        "sidecar".to_string()
+    }
+}
+
+/// Configuration of a [`Sidecar`] scheme entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SidecarConfig {
+    /// Base URL of the sidecar service.
+    pub url: Url,
+    /// Name of the scheme served by the sidecar (e.g. "upto").
+    pub scheme: String,
+    /// x402 protocol version served by the sidecar.
+    #[serde(default = "sidecar_config_defaults::default_x402_version")]
+    pub x402_version: u8,
+    /// Timeout to connect to the sidecar, in seconds.
+    #[serde(default = "sidecar_config_defaults::default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Timeout of a whole request to the sidecar, in seconds.
+    #[serde(default = "sidecar_config_defaults::default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+mod sidecar_config_defaults {
+    pub fn default_x402_version() -> u8 {
+        2
+    }
+    pub fn default_connect_timeout_secs() -> u64 {
+        5
+    }
+    pub fn default_timeout_secs() -> u64 {
+        30
+    }
+}
+
+impl X402SchemeFacilitatorBuilder for Sidecar {
+    fn build(
+        &self,
+        provider: ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn Error>> {
+        let config = config.ok_or("Sidecar::build: config with a sidecar url is required")?;
+        let config: SidecarConfig = serde_json::from_value(config)?;
+        let facilitator = SidecarFacilitator::new(provider.chain_id(), config)?;
+        tracing::info!(chain_id = %facilitator.chain_id, scheme = %facilitator.scheme, url = %facilitator.base_url, "Using sidecar scheme handler");
+        Ok(Box::new(facilitator))
+    }
+}
+
+pub struct SidecarFacilitator {
+    client: Client,
+    base_url: Url,
+    chain_id: ChainId,
+    scheme: String,
+    x402_version: u8,
+}
+
+impl SidecarFacilitator {
+    pub fn new(chain_id: ChainId, config: SidecarConfig) -> Result<Self, Box<dyn Error>> {
+        if config.x402_version != 1 && config.x402_version != 2 {
+            return Err(format!(
+                "Sidecar::build: unsupported x402 version {}",
+                config.x402_version
+            )
+            .into());
+        }
+        // Make sure `join` appends to the base path instead of replacing its last segment.
+        let mut base_url = config.url;
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+        let client = Client::builder()
+            .connect_timeout(Duration::from_secs(config.connect_timeout_secs))
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()?;
+        Ok(Self {
+            client,
+            base_url,
+            chain_id,
+            scheme: config.scheme,
+            x402_version: config.x402_version,
+        })
+    }
+
+    /// Whether a payment kind advertised by the sidecar is the one this handler serves.
+    ///
+    /// Kinds use a CAIP-2 network for v2 and a network name for v1.
+    fn serves_kind(&self, kind: &proto::SupportedPaymentKind) -> bool {
+        let chain_id = ChainId::from_str(&kind.network)
+            .ok()
+            .or_else(|| ChainId::from_network_name(&kind.network));
+        kind.x402_version == self.x402_version
+            && kind.scheme == self.scheme
+            && chain_id.as_ref() == Some(&self.chain_id)
+    }
+
+    async fn send<Res>(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<Res, X402SchemeFacilitatorError>
+    where
+        Res: serde::de::DeserializeOwned,
+    {
+        let response = request.send().await.map_err(SidecarError::from)?;
+        let status = response.status();
+        if status.is_success() {
+            let response = response.json::<Res>().await.map_err(SidecarError::from)?;
+            return Ok(response);
+        }
+        let body = response
+            .json::<serde_json::Value>()
+            .await
+            .unwrap_or_default();
+        let reason = body
+            .get("invalidReason")
+            .or_else(|| body.get("errorReason"))
+            .and_then(|reason| ErrorReason::deserialize(reason).ok());
+        let details = body
+            .get("invalidReasonDetails")
+            .or_else(|| body.get("errorReasonDetails"))
+            .and_then(|details| details.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("Sidecar responded with {status}"));
+        Err(match reason {
+            Some(reason) if status.is_client_error() => as_facilitator_error(reason, details),
+            _ => X402SchemeFacilitatorError::OnchainFailure(details),
+        })
+    }
+
+    fn url(&self, path: &str) -> Result<Url, X402SchemeFacilitatorError> {
+        Ok(self.base_url.join(path).map_err(SidecarError::from)?)
+    }
+}
+
+#[async_trait::async_trait]
+impl X402SchemeFacilitator for SidecarFacilitator {
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let url = self.url("verify")?;
+        self.send(self.client.post(url).json(request)).await
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let url = self.url("settle")?;
+        self.send(self.client.post(url).json(request)).await
+    }
+
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        let url = self.url("supported")?;
+        let mut supported: proto::SupportedResponse = self.send(self.client.get(url)).await?;
+        supported.kinds.retain(|kind| self.serves_kind(kind));
+        supported
+            .signers
+            .retain(|chain_id, _| *chain_id == self.chain_id);
+        Ok(supported)
+    }
+
+    fn scheme_override(&self) -> Option<(u8, &str)> {
+        Some((self.x402_version, &self.scheme))
+    }
+}
+
+/// Maps an error reason reported by the sidecar back to a facilitator error.
+fn as_facilitator_error(reason: ErrorReason, details: String) -> X402SchemeFacilitatorError {
+    let error = match reason {
+        ErrorReason::InvalidFormat => PaymentVerificationError::InvalidFormat(details),
+        ErrorReason::InvalidPaymentAmount => PaymentVerificationError::InvalidPaymentAmount,
+        ErrorReason::InvalidPaymentEarly => PaymentVerificationError::Early,
+        ErrorReason::InvalidPaymentExpired => PaymentVerificationError::Expired,
+        ErrorReason::ChainIdMismatch => PaymentVerificationError::ChainIdMismatch,
+        ErrorReason::RecipientMismatch => PaymentVerificationError::RecipientMismatch,
+        ErrorReason::AssetMismatch => PaymentVerificationError::AssetMismatch,
+        ErrorReason::AcceptedRequirementsMismatch => {
+            PaymentVerificationError::AcceptedRequirementsMismatch
+        }
+        ErrorReason::InvalidSignature => PaymentVerificationError::InvalidSignature(details),
+        ErrorReason::TransactionSimulation => {
+            PaymentVerificationError::TransactionSimulation(details)
+        }
+        ErrorReason::InsufficientFunds => PaymentVerificationError::InsufficientFunds,
+        ErrorReason::InsufficientAllowance => PaymentVerificationError::InsufficientAllowance,
+        ErrorReason::UnsupportedChain => PaymentVerificationError::UnsupportedChain,
+        ErrorReason::UnsupportedScheme => PaymentVerificationError::UnsupportedScheme,
+        ErrorReason::UnexpectedError => {
+            return X402SchemeFacilitatorError::OnchainFailure(details);
+        }
+    };
+    error.into()
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SidecarError {
+    #[error("Invalid sidecar URL: {0}")]
+    Url(#[from] url::ParseError),
+    #[error("Sidecar request failed: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+impl From<SidecarError> for X402SchemeFacilitatorError {
+    fn from(value: SidecarError) -> Self {
+        X402SchemeFacilitatorError::OnchainFailure(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Json;
+    use axum::Router;
+    use axum::http::StatusCode;
+    use axum::routing::{get, post};
+    use serde_json::{Value, json};
+
+    async fn serve_sidecar() -> Url {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/sidecar",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+        let supported = json!({
+            "kinds": [
+                { "x402Version": 2, "scheme": "upto", "network": "eip155:8453" },
+                { "x402Version": 2, "scheme": "exact", "network": "eip155:8453" },
+                { "x402Version": 1, "scheme": "upto", "network": "base" },
+                { "x402Version": 2, "scheme": "upto", "network": "eip155:1" },
+            ],
+            "signers": { "eip155:8453": ["0x01"], "eip155:1": ["0x02"] },
+        });
+        let router = Router::new()
+            .route(
+                "/sidecar/verify",
+                post(|Json(request): Json<Value>| async move {
+                    Json(json!({ "isValid": true, "request": request }))
+                }),
+            )
+            .route(
+                "/sidecar/settle",
+                post(|| async {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(json!({ "errorReason": "recipient_mismatch" })),
+                    )
+                }),
+            )
+            .route(
+                "/sidecar/supported",
+                get(move || async move { Json(supported) }),
+            );
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        url
+    }
+
+    fn sidecar(url: Url) -> SidecarFacilitator {
+        let config = serde_json::from_value(json!({ "url": url, "scheme": "upto" })).unwrap();
+        SidecarFacilitator::new("eip155:8453".parse().unwrap(), config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sidecar_forwarding() {
+        let sidecar = sidecar(serve_sidecar().await);
+        let request: proto::VerifyRequest =
+            serde_json::from_value(json!({ "x402Version": 2 })).unwrap();
+
+        let response = sidecar.verify(&request).await.unwrap();
+        let response = serde_json::to_value(response).unwrap();
+        assert_eq!(response["request"]["x402Version"], 2);
+        assert!(matches!(
+            sidecar.settle(&request).await,
+            Err(X402SchemeFacilitatorError::PaymentVerification(
+                PaymentVerificationError::RecipientMismatch
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_sidecar_supported_is_filtered() {
+        let sidecar = sidecar(serve_sidecar().await);
+
+        let supported = sidecar.supported().await.unwrap();
+        assert_eq!(supported.kinds.len(), 1);
+        assert_eq!(supported.kinds[0].scheme, "upto");
+        assert_eq!(supported.kinds[0].network, "eip155:8453");
+        assert_eq!(supported.signers.len(), 1);
+    }
+
+    #[test]
+    fn test_sidecar_rejects_unknown_version() {
+        let config = serde_json::from_value(json!({
+            "url": "http://localhost:9000",
+            "scheme": "upto",
+            "x402_version": 3,
+        }))
+        .unwrap();
+        assert!(SidecarFacilitator::new("eip155:8453".parse().unwrap(), config).is_err());
+    }
+}
@@ -1,6 +1,6 @@
 use alloy_primitives::{Address, FixedBytes, Signature, U256};
 use alloy_signer_local::PrivateKeySigner;
-use alloy_sol_types::{SolStruct, eip712_domain};
+use alloy_sol_types::eip712_domain;
 use async_trait::async_trait;
 use rand::{Rng, rng};
 use serde::Deserialize;
@@ -94,11 +94,12 @@ pub struct Eip3009SigningParams {
     pub amount: U256,
     /// Maximum timeout in seconds for the authorization validity window
     pub max_timeout_seconds: u64,
-    /// Optional EIP-712 domain name and version override
+    /// Optional EIP-712 domain name and version override, and authorization method
     pub extra: Option<PaymentRequirementsExtra>,
 }
 
-/// Signs an ERC-3009 TransferWithAuthorization using EIP-712.
+/// Signs an ERC-3009 TransferWithAuthorization (or ReceiveWithAuthorization, if requested
+/// by `extra.authorizationMethod`) using EIP-712.
 ///
 /// This is the shared signing logic used by both v1 and v2 EIP-155 exact scheme clients.
 /// It constructs the EIP-712 domain, builds the authorization struct with appropriate
@@ -153,7 +154,12 @@ pub async fn sign_erc3009_authorization<S: SignerLike + Sync>(
         nonce: authorization.nonce,
     };
 
-    let eip712_hash = transfer_with_authorization.eip712_signing_hash(&domain);
+    let method = params
+        .extra
+        .as_ref()
+        .map(|extra| extra.authorization_method)
+        .unwrap_or_default();
+    let eip712_hash = transfer_with_authorization.signing_hash(method, &domain);
     let signature = signer
         .sign_hash(&eip712_hash)
         .await
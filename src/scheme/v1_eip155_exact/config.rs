@@ -0,0 +1,172 @@
+//! Typed configuration for the EVM `exact` schemes.
+
+use alloy_primitives::{Address, U256};
+use serde::Deserialize;
+
+use crate::proto::PaymentVerificationError;
+
+/// Configuration of the `v1-eip155-exact` and `v2-eip155-exact` schemes,
+/// read from the `config` field of a scheme entry and validated when the scheme is built.
+///
+/// Example JSON:
+/// ```json
+/// {
+///   "allowed_assets": ["0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"],
+///   "min_amount": "1000",
+///   "max_amount": "100000000",
+///   "confirmations": 2,
+///   "clock_skew_secs": 6
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Eip155ExactConfig {
+    /// Token contracts accepted for payment (optional, defaults to any asset).
+    #[serde(default)]
+    pub allowed_assets: Option<Vec<Address>>,
+    /// Minimum required payment amount, in token units (optional).
+    #[serde(default)]
+    pub min_amount: Option<U256>,
+    /// Maximum required payment amount, in token units (optional).
+    #[serde(default)]
+    pub max_amount: Option<U256>,
+    /// Number of block confirmations to wait for when settling.
+    #[serde(default = "eip155_exact_config_defaults::default_confirmations")]
+    pub confirmations: u64,
+    /// Grace period, in seconds, required before `validBefore` to account for latency and clock skew.
+    #[serde(default = "eip155_exact_config_defaults::default_clock_skew_secs")]
+    pub clock_skew_secs: u64,
+}
+
+mod eip155_exact_config_defaults {
+    pub fn default_confirmations() -> u64 {
+        1
+    }
+
+    pub fn default_clock_skew_secs() -> u64 {
+        6
+    }
+}
+
+impl Default for Eip155ExactConfig {
+    fn default() -> Self {
+        Self {
+            allowed_assets: None,
+            min_amount: None,
+            max_amount: None,
+            confirmations: eip155_exact_config_defaults::default_confirmations(),
+            clock_skew_secs: eip155_exact_config_defaults::default_clock_skew_secs(),
+        }
+    }
+}
+
+impl Eip155ExactConfig {
+    /// Parses and validates the scheme entry `config`, falling back to defaults if absent.
+    pub fn from_json(config: Option<serde_json::Value>) -> Result<Self, Eip155ExactConfigError> {
+        let config = match config {
+            Some(config) => serde_json::from_value::<Self>(config)?,
+            None => Self::default(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks constraints that span several fields.
+    pub fn validate(&self) -> Result<(), Eip155ExactConfigError> {
+        if self.allowed_assets.as_ref().is_some_and(Vec::is_empty) {
+            return Err(Eip155ExactConfigError::EmptyAllowedAssets);
+        }
+        if let (Some(min_amount), Some(max_amount)) = (self.min_amount, self.max_amount)
+            && min_amount > max_amount
+        {
+            return Err(Eip155ExactConfigError::InvalidAmountBounds);
+        }
+        if self.confirmations == 0 {
+            return Err(Eip155ExactConfigError::ZeroConfirmations);
+        }
+        Ok(())
+    }
+
+    /// Checks that the required asset is one of the allowed assets.
+    pub fn assert_asset(&self, asset: &Address) -> Result<(), PaymentVerificationError> {
+        match &self.allowed_assets {
+            Some(allowed_assets) if !allowed_assets.contains(asset) => {
+                Err(PaymentVerificationError::AssetMismatch)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks that the required amount is within the configured bounds.
+    pub fn assert_amount(&self, amount: &U256) -> Result<(), PaymentVerificationError> {
+        let below_min = self
+            .min_amount
+            .is_some_and(|min_amount| *amount < min_amount);
+        let above_max = self
+            .max_amount
+            .is_some_and(|max_amount| *amount > max_amount);
+        if below_min || above_max {
+            Err(PaymentVerificationError::InvalidPaymentAmount)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Eip155ExactConfigError {
+    #[error("Invalid scheme config: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("allowed_assets must not be empty")]
+    EmptyAllowedAssets,
+    #[error("min_amount must not exceed max_amount")]
+    InvalidAmountBounds,
+    #[error("confirmations must be at least 1")]
+    ZeroConfirmations,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::address;
+    use serde_json::json;
+
+    #[test]
+    fn test_defaults() {
+        let config = Eip155ExactConfig::from_json(None).unwrap();
+        assert_eq!(config, Eip155ExactConfig::default());
+        assert_eq!(config.confirmations, 1);
+        assert_eq!(config.clock_skew_secs, 6);
+    }
+
+    #[test]
+    fn test_bounds_and_assets() {
+        let usdc = address!("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913");
+        let config = Eip155ExactConfig::from_json(Some(json!({
+            "allowed_assets": [usdc],
+            "min_amount": "1000",
+            "max_amount": "0xf4240",
+            "confirmations": 3,
+        })))
+        .unwrap();
+        assert_eq!(config.confirmations, 3);
+        assert!(config.assert_asset(&usdc).is_ok());
+        assert!(config.assert_asset(&Address::ZERO).is_err());
+        assert!(config.assert_amount(&U256::from(999)).is_err());
+        assert!(config.assert_amount(&U256::from(1_000_000)).is_ok());
+        assert!(config.assert_amount(&U256::from(1_000_001)).is_err());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let invalid = [
+            json!({ "min_amount": "2", "max_amount": "1" }),
+            json!({ "allowed_assets": [] }),
+            json!({ "confirmations": 0 }),
+            json!({ "unknown": true }),
+        ];
+        for config in invalid {
+            assert!(Eip155ExactConfig::from_json(Some(config)).is_err());
+        }
+    }
+}
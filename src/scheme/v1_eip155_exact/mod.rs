@@ -1,9 +1,8 @@
-use alloy_contract::SolCallBuilder;
-use alloy_primitives::{Address, B256, Bytes, Signature, TxHash, U256, address, hex};
+use alloy_primitives::{Address, B256, Bytes, Signature, TxHash, U256, hex};
 use alloy_provider::bindings::IMulticall3;
-use alloy_provider::{
-    MULTICALL3_ADDRESS, MulticallError, MulticallItem, PendingTransactionError, Provider,
-};
+use alloy_provider::{MULTICALL3_ADDRESS, MulticallError, PendingTransactionError, Provider};
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_rpc_types_eth::simulate::{SimBlock, SimulatePayload};
 use alloy_sol_types::{Eip712Domain, SolCall, SolStruct, SolType, eip712_domain, sol};
 use alloy_transport::TransportError;
 use serde::{Deserialize, Serialize};
@@ -14,11 +13,13 @@ use tracing::instrument;
 use tracing_core::Level;
 
 pub mod client;
+pub mod config;
 pub mod types;
 
 use crate::chain::eip155::{
-    Eip155ChainProvider, Eip155ChainReference, Eip155MetaTransactionProvider, MetaTransaction,
-    MetaTransactionSendError,
+    Eip155ChainProvider, Eip155ChainReference, Eip155MetaTransactionProvider, Eip3009Authorization,
+    Eip6492Validation, MetaTransaction, MetaTransactionSendError, TokenMetadataCache,
+    TokenMetadataError, VALIDATOR_ADDRESS,
 };
 use crate::chain::{ChainId, ChainProvider, ChainProviderOps};
 use crate::proto;
@@ -28,11 +29,11 @@ use crate::scheme::{
 };
 use crate::timestamp::UnixTimestamp;
 
+pub use config::*;
 pub use types::*;
 
-/// Signature verifier for EIP-6492, EIP-1271, EOA, universally deployed on the supported EVM chains
-/// If absent on a target chain, verification will fail; you should deploy the validator there.
-pub const VALIDATOR_ADDRESS: Address = address!("0xdAcD51A54883eb67D95FAEb2BBfdC4a9a6BD2a3B");
+/// Value returned by ERC-1271 `isValidSignature` for a valid signature.
+const ERC1271_MAGIC_VALUE: [u8; 4] = hex!("1626ba7e");
 
 pub struct V1Eip155Exact;
 
@@ -52,19 +53,22 @@ impl X402SchemeFacilitatorBuilder for V1Eip155Exact {
     fn build(
         &self,
         provider: ChainProvider,
-        _config: Option<serde_json::Value>,
+        config: Option<serde_json::Value>,
     ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn std::error::Error>> {
         let provider = if let ChainProvider::Eip155(provider) = provider {
             provider
         } else {
             return Err("V1Eip155Exact::build: provider must be an Eip155ChainProvider".into());
         };
-        Ok(Box::new(V1Eip155ExactFacilitator { provider }))
+        let config = Eip155ExactConfig::from_json(config)
+            .map_err(|e| format!("V1Eip155Exact::build: {e}"))?;
+        Ok(Box::new(V1Eip155ExactFacilitator { provider, config }))
     }
 }
 
 pub struct V1Eip155ExactFacilitator {
     provider: Arc<Eip155ChainProvider>,
+    config: Eip155ExactConfig,
 }
 
 #[async_trait::async_trait]
@@ -79,13 +83,22 @@ impl X402SchemeFacilitator for V1Eip155ExactFacilitator {
         let (contract, payment, eip712_domain) = assert_valid_payment(
             self.provider.inner(),
             self.provider.chain(),
+            self.provider.token_metadata(),
+            &self.config,
             payload,
             requirements,
         )
         .await?;
+        assert_payee(self.provider.inner(), &payment).await?;
 
-        let payer =
-            verify_payment(self.provider.inner(), &contract, &payment, &eip712_domain).await?;
+        let payer = verify_payment(
+            self.provider.inner(),
+            &contract,
+            &payment,
+            &eip712_domain,
+            self.provider.eip6492_validation(),
+        )
+        .await?;
 
         Ok(v1::VerifyResponse::valid(payer.to_string()).into())
     }
@@ -94,23 +107,40 @@ impl X402SchemeFacilitator for V1Eip155ExactFacilitator {
         &self,
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let (finality, confirmations) = self
+            .provider
+            .settlement_finality(request.finality()?, self.config.confirmations)?;
         let request = types::SettleRequest::from_proto(request.clone())?;
         let payload = &request.payment_payload;
         let requirements = &request.payment_requirements;
         let (contract, payment, eip712_domain) = assert_valid_payment(
             self.provider.inner(),
             self.provider.chain(),
+            self.provider.token_metadata(),
+            &self.config,
             payload,
             requirements,
         )
         .await?;
+        assert_payee(self.provider.inner(), &payment).await?;
 
-        let tx_hash =
-            settle_payment(self.provider.as_ref(), &contract, &payment, &eip712_domain).await?;
+        let tx_hash = settle_payment(
+            self.provider.as_ref(),
+            &contract,
+            &payment,
+            &eip712_domain,
+            confirmations,
+        )
+        .await?;
+        self.provider
+            .wait_for_finality(tx_hash, finality)
+            .await
+            .map_err(Eip155ExactError::from)?;
         Ok(v1::SettleResponse::Success {
             payer: payment.from.to_string(),
             transaction: tx_hash.to_string(),
             network: payload.network.clone(),
+            finality: Some(finality),
         }
         .into())
     }
@@ -160,6 +190,8 @@ pub struct ExactEvmPayment {
     pub nonce: B256,
     /// Raw signature bytes (EIP-1271 or EIP-6492-wrapped).
     pub signature: Bytes,
+    /// ERC-3009 function the signature authorizes.
+    pub method: AuthorizationMethod,
 }
 
 sol!(
@@ -180,6 +212,50 @@ sol! {
     "abi/Validator6492.json"
 }
 
+sol! {
+    #[allow(missing_docs)]
+    interface IERC1271 {
+        function isValidSignature(bytes32 hash, bytes signature) external view returns (bytes4 magicValue);
+    }
+}
+
+sol! {
+    /// Implemented by contract payees settling with `receiveWithAuthorization`: the token only accepts
+    /// it from the payee, so the payee forwards the authorization to `token`, with itself as `to`.
+    #[allow(missing_docs)]
+    #[allow(clippy::too_many_arguments)]
+    interface IERC3009Payee {
+        function receiveWithAuthorization(address token, address from, uint256 value, uint256 validAfter, uint256 validBefore, bytes32 nonce, bytes signature) external;
+        function receiveWithAuthorization(address token, address from, uint256 value, uint256 validAfter, uint256 validBefore, bytes32 nonce, uint8 v, bytes32 r, bytes32 s) external;
+    }
+}
+
+/// Calls validating an EIP-6492 signature without the universal validator, to run in a single simulation:
+/// deploy the wallet with its factory, which fails harmlessly if it is already deployed,
+/// then check the inner signature with ERC-1271.
+fn deployless_eip6492_calls(
+    payer: Address,
+    hash: B256,
+    factory: Address,
+    factory_calldata: Bytes,
+    inner: Bytes,
+) -> [(Address, Bytes); 2] {
+    let is_valid_signature = IERC1271::isValidSignatureCall {
+        hash,
+        signature: inner,
+    };
+    [
+        (factory, factory_calldata),
+        (payer, is_valid_signature.abi_encode().into()),
+    ]
+}
+
+/// Whether the output of ERC-1271 `isValidSignature` reports a valid signature.
+fn is_erc1271_magic_value(output: &[u8]) -> bool {
+    IERC1271::isValidSignatureCall::abi_decode_returns(output)
+        .is_ok_and(|magic_value| magic_value.0 == ERC1271_MAGIC_VALUE)
+}
+
 /// Runs all preconditions needed for a successful payment:
 /// - Valid scheme, network, and receiver.
 /// - Valid time window (validAfter/validBefore).
@@ -190,6 +266,8 @@ sol! {
 async fn assert_valid_payment<P: Provider>(
     provider: P,
     chain: &Eip155ChainReference,
+    token_metadata: &TokenMetadataCache,
+    config: &Eip155ExactConfig,
     payload: &types::PaymentPayload,
     requirements: &types::PaymentRequirements,
 ) -> Result<(IEIP3009::IEIP3009Instance<P>, ExactEvmPayment, Eip712Domain), Eip155ExactError> {
@@ -210,13 +288,22 @@ async fn assert_valid_payment<P: Provider>(
     }
     let valid_after = authorization.valid_after;
     let valid_before = authorization.valid_before;
-    assert_time(valid_after, valid_before)?;
+    assert_time(valid_after, valid_before, config.clock_skew_secs)?;
     let asset_address = requirements.asset;
+    config.assert_asset(&asset_address)?;
+    let amount_required = requirements.max_amount_required;
+    config.assert_amount(&amount_required)?;
     let contract = IEIP3009::new(asset_address, provider);
 
-    let domain = assert_domain(chain, &contract, &asset_address, &requirements.extra).await?;
+    let domain = assert_domain(
+        chain,
+        &contract,
+        &asset_address,
+        &requirements.extra,
+        token_metadata,
+    )
+    .await?;
 
-    let amount_required = requirements.max_amount_required;
     assert_enough_balance(&contract, &authorization.from, amount_required).await?;
     assert_enough_value(&authorization.value, &amount_required)?;
 
@@ -228,6 +315,11 @@ async fn assert_valid_payment<P: Provider>(
         valid_before: authorization.valid_before,
         nonce: authorization.nonce,
         signature: payload.payload.signature.clone(),
+        method: requirements
+            .extra
+            .as_ref()
+            .map(|extra| extra.authorization_method)
+            .unwrap_or_default(),
     };
 
     Ok((contract, payment, domain))
@@ -235,14 +327,15 @@ async fn assert_valid_payment<P: Provider>(
 
 /// Validates that the current time is within the `validAfter` and `validBefore` bounds.
 ///
-/// Adds a 6-second grace buffer when checking expiration to account for latency.
+/// Requires `grace_secs` before expiration to account for latency and clock skew.
 #[instrument(skip_all, err)]
 pub fn assert_time(
     valid_after: UnixTimestamp,
     valid_before: UnixTimestamp,
+    grace_secs: u64,
 ) -> Result<(), PaymentVerificationError> {
     let now = UnixTimestamp::now();
-    if valid_before < now + 6 {
+    if valid_before < now + grace_secs {
         return Err(PaymentVerificationError::Expired);
     }
     if valid_after > now {
@@ -252,6 +345,8 @@ pub fn assert_time(
 }
 
 /// Constructs the correct EIP-712 domain for signature verification.
+///
+/// Name and version come from `extra` when present, and from the `token_metadata` cache otherwise.
 #[instrument(skip_all, err, fields(
     network = %chain.as_chain_id(),
     asset = %asset_address
@@ -261,34 +356,16 @@ pub async fn assert_domain<P: Provider>(
     token_contract: &IEIP3009::IEIP3009Instance<P>,
     asset_address: &Address,
     extra: &Option<PaymentRequirementsExtra>,
+    token_metadata: &TokenMetadataCache,
 ) -> Result<Eip712Domain, Eip155ExactError> {
-    let name = extra.as_ref().map(|extra| extra.name.clone());
-    let name = if let Some(name) = name {
-        name
-    } else {
-        token_contract
-            .name()
-            .call()
-            .into_future()
-            .instrument(tracing::info_span!(
-                "fetch_eip712_name",
-                otel.kind = "client",
-            ))
-            .await?
-    };
-    let version = extra.as_ref().map(|extra| extra.version.clone());
-    let version = if let Some(version) = version {
-        version
-    } else {
-        token_contract
-            .version()
-            .call()
-            .into_future()
-            .instrument(tracing::info_span!(
-                "fetch_eip712_version",
-                otel.kind = "client",
-            ))
-            .await?
+    let (name, version) = match extra {
+        Some(extra) => (extra.name.clone(), extra.version.clone()),
+        None => {
+            let metadata = token_metadata
+                .get(token_contract.provider(), *asset_address)
+                .await?;
+            (metadata.name, metadata.version)
+        }
     };
     let domain = eip712_domain! {
         name: name,
@@ -379,14 +456,47 @@ sol!(
         uint256 validBefore;
         bytes32 nonce;
     }
+
+    /// Solidity-compatible struct definition for ERC-3009 `receiveWithAuthorization`.
+    ///
+    /// Same fields as [`TransferWithAuthorization`], under a distinct EIP-712 type,
+    /// so that a signature for one function cannot be used with the other.
+    #[derive(Serialize, Deserialize)]
+    struct ReceiveWithAuthorization {
+        address from;
+        address to;
+        uint256 value;
+        uint256 validAfter;
+        uint256 validBefore;
+        bytes32 nonce;
+    }
 );
 
+impl TransferWithAuthorization {
+    /// EIP-712 signing hash of the authorization, typed for the ERC-3009 function it is used with.
+    pub fn signing_hash(&self, method: AuthorizationMethod, domain: &Eip712Domain) -> B256 {
+        match method {
+            AuthorizationMethod::TransferWithAuthorization => self.eip712_signing_hash(domain),
+            AuthorizationMethod::ReceiveWithAuthorization => ReceiveWithAuthorization {
+                from: self.from,
+                to: self.to,
+                value: self.value,
+                validAfter: self.validAfter,
+                validBefore: self.validBefore,
+                nonce: self.nonce,
+            }
+            .eip712_signing_hash(domain),
+        }
+    }
+}
+
 impl SignedMessage {
     /// Construct a [`SignedMessage`] from an [`ExactEvmPayment`] and its
     /// corresponding [`Eip712Domain`].
     ///
     /// This helper ties together:
-    /// - The **payment intent** (an ERC-3009 `TransferWithAuthorization` struct),
+    /// - The **payment intent** (an ERC-3009 `TransferWithAuthorization` or
+    ///   `ReceiveWithAuthorization` struct, depending on the payment method),
     /// - The **EIP-712 domain** used for signing,
     /// - And the raw signature bytes attached to the payment.
     ///
@@ -412,7 +522,7 @@ impl SignedMessage {
             validBefore: U256::from(payment.valid_before.as_secs()),
             nonce: payment.nonce,
         };
-        let eip712_hash = transfer_with_authorization.eip712_signing_hash(domain);
+        let eip712_hash = transfer_with_authorization.signing_hash(payment.method, domain);
         let structured_signature: StructuredSignature = StructuredSignature::try_from_bytes(
             payment.signature.clone(),
             payment.from,
@@ -555,111 +665,111 @@ impl TryFrom<Bytes> for StructuredSignature {
     }
 }
 
-pub struct TransferWithAuthorization0Call<P>(
-    pub TransferWithAuthorizationCall<P, IEIP3009::transferWithAuthorization_0Call, Bytes>,
-);
+pub struct TransferWithAuthorization0Call(pub TransferWithAuthorizationCall<Bytes>);
 
-impl<'a, P: Provider> TransferWithAuthorization0Call<&'a P> {
-    /// Constructs a full `transferWithAuthorization` call for a verified payment payload.
-    ///
-    /// This function prepares the transaction builder with gas pricing adapted to the network's
-    /// capabilities (EIP-1559 or legacy) and packages it together with signature metadata
-    /// into a [`TransferWithAuthorization0Call`] structure.
+impl TransferWithAuthorization0Call {
+    /// Constructs a full ERC-3009 call for a verified payment payload, for the function
+    /// the payment is authorized with, see [`TransferWithAuthorizationCall::new`].
     ///
     /// This function does not perform any validation — it assumes inputs are already checked.
-    pub fn new(
-        contract: &'a IEIP3009::IEIP3009Instance<P>,
+    pub fn new<P: Provider>(
+        contract: &IEIP3009::IEIP3009Instance<P>,
         payment: &ExactEvmPayment,
         signature: Bytes,
     ) -> Self {
-        let from = payment.from;
-        let to = payment.to;
-        let value = payment.value;
-        let valid_after = U256::from(payment.valid_after.as_secs());
-        let valid_before = U256::from(payment.valid_before.as_secs());
-        let nonce = payment.nonce;
-        let tx = contract.transferWithAuthorization_0(
-            from,
-            to,
-            value,
-            valid_after,
-            valid_before,
-            nonce,
-            signature.clone(),
-        );
-        TransferWithAuthorization0Call(TransferWithAuthorizationCall {
-            tx,
-            from,
-            to,
-            value,
-            valid_after,
-            valid_before,
-            nonce,
-            signature,
-            contract_address: *contract.address(),
-        })
+        let call = TransferWithAuthorizationCall::new(*contract.address(), payment, signature);
+        let calldata = match payment.method {
+            AuthorizationMethod::TransferWithAuthorization => {
+                IEIP3009::transferWithAuthorization_0Call {
+                    from: call.from,
+                    to: call.to,
+                    value: call.value,
+                    validAfter: call.valid_after,
+                    validBefore: call.valid_before,
+                    nonce: call.nonce,
+                    signature: call.signature.clone(),
+                }
+                .abi_encode()
+            }
+            AuthorizationMethod::ReceiveWithAuthorization => {
+                IERC3009Payee::receiveWithAuthorization_0Call {
+                    token: call.contract_address,
+                    from: call.from,
+                    value: call.value,
+                    validAfter: call.valid_after,
+                    validBefore: call.valid_before,
+                    nonce: call.nonce,
+                    signature: call.signature.clone(),
+                }
+                .abi_encode()
+            }
+        };
+        TransferWithAuthorization0Call(call.with_calldata(calldata))
     }
 }
 
-pub struct TransferWithAuthorization1Call<P>(
-    pub TransferWithAuthorizationCall<P, IEIP3009::transferWithAuthorization_1Call, Signature>,
-);
+pub struct TransferWithAuthorization1Call(pub TransferWithAuthorizationCall<Signature>);
 
-impl<'a, P: Provider> TransferWithAuthorization1Call<&'a P> {
-    /// Constructs a full `transferWithAuthorization` call for a verified payment payload
-    /// using split signature components (v, r, s).
-    ///
-    /// This function prepares the transaction builder with gas pricing adapted to the network's
-    /// capabilities (EIP-1559 or legacy) and packages it together with signature metadata
-    /// into a [`TransferWithAuthorization1Call`] structure.
+impl TransferWithAuthorization1Call {
+    /// Constructs a full ERC-3009 call for a verified payment payload using split signature
+    /// components (v, r, s), for the function the payment is authorized with,
+    /// see [`TransferWithAuthorizationCall::new`].
     ///
     /// This function does not perform any validation — it assumes inputs are already checked.
-    pub fn new(
-        contract: &'a IEIP3009::IEIP3009Instance<P>,
+    pub fn new<P: Provider>(
+        contract: &IEIP3009::IEIP3009Instance<P>,
         payment: &ExactEvmPayment,
         signature: Signature,
     ) -> Self {
-        let from = payment.from;
-        let to = payment.to;
-        let value = payment.value;
-        let valid_after = U256::from(payment.valid_after.as_secs());
-        let valid_before = U256::from(payment.valid_before.as_secs());
-        let nonce = payment.nonce;
+        let call = TransferWithAuthorizationCall::new(*contract.address(), payment, signature);
         let v = 27 + (signature.v() as u8);
         let r = B256::from(signature.r());
         let s = B256::from(signature.s());
-        let tx = contract.transferWithAuthorization_1(
-            from,
-            to,
-            value,
-            valid_after,
-            valid_before,
-            nonce,
-            v,
-            r,
-            s,
-        );
-        TransferWithAuthorization1Call(TransferWithAuthorizationCall {
-            tx,
-            from,
-            to,
-            value,
-            valid_after,
-            valid_before,
-            nonce,
-            signature,
-            contract_address: *contract.address(),
-        })
+        let calldata = match payment.method {
+            AuthorizationMethod::TransferWithAuthorization => {
+                IEIP3009::transferWithAuthorization_1Call {
+                    from: call.from,
+                    to: call.to,
+                    value: call.value,
+                    validAfter: call.valid_after,
+                    validBefore: call.valid_before,
+                    nonce: call.nonce,
+                    v,
+                    r,
+                    s,
+                }
+                .abi_encode()
+            }
+            AuthorizationMethod::ReceiveWithAuthorization => {
+                IERC3009Payee::receiveWithAuthorization_1Call {
+                    token: call.contract_address,
+                    from: call.from,
+                    value: call.value,
+                    validAfter: call.valid_after,
+                    validBefore: call.valid_before,
+                    nonce: call.nonce,
+                    v,
+                    r,
+                    s,
+                }
+                .abi_encode()
+            }
+        };
+        TransferWithAuthorization1Call(call.with_calldata(calldata))
     }
 }
 
-/// A prepared call to `transferWithAuthorization` (ERC-3009) including all derived fields.
+/// A prepared ERC-3009 call including all derived fields.
 ///
-/// This struct wraps the assembled call builder, making it reusable across verification
-/// (`.call()`) and settlement (`.send()`) flows, along with context useful for tracing/logging.
-pub struct TransferWithAuthorizationCall<P, TCall, TSignature> {
-    /// The prepared call builder that can be `.call()`ed or `.send()`ed.
-    pub tx: SolCallBuilder<P, TCall>,
+/// `transferWithAuthorization` is called on the token. `receiveWithAuthorization` is called on
+/// the payee contract, which forwards it to the token (see [`IERC3009Payee`]), as the token only
+/// accepts it from the payee. Either way, the call can be simulated or sent as is,
+/// including through Multicall3, and carries context useful for tracing/logging.
+pub struct TransferWithAuthorizationCall<TSignature> {
+    /// Address the call is sent to: the token, or the payee for `receiveWithAuthorization`.
+    pub target: Address,
+    /// ABI-encoded call.
+    pub calldata: Bytes,
     /// The sender (`from`) address for the authorization.
     pub from: Address,
     /// The recipient (`to`) address for the authorization.
@@ -676,6 +786,119 @@ pub struct TransferWithAuthorizationCall<P, TCall, TSignature> {
     pub signature: TSignature,
     /// Address of the token contract used for this transfer.
     pub contract_address: Address,
+    /// ERC-3009 function the authorization is signed for.
+    pub method: AuthorizationMethod,
+}
+
+impl<TSignature> TransferWithAuthorizationCall<TSignature> {
+    fn new(contract_address: Address, payment: &ExactEvmPayment, signature: TSignature) -> Self {
+        let target = match payment.method {
+            AuthorizationMethod::TransferWithAuthorization => contract_address,
+            AuthorizationMethod::ReceiveWithAuthorization => payment.to,
+        };
+        Self {
+            target,
+            calldata: Bytes::new(),
+            from: payment.from,
+            to: payment.to,
+            value: payment.value,
+            valid_after: U256::from(payment.valid_after.as_secs()),
+            valid_before: U256::from(payment.valid_before.as_secs()),
+            nonce: payment.nonce,
+            signature,
+            contract_address,
+            method: payment.method,
+        }
+    }
+
+    fn with_calldata(self, calldata: Vec<u8>) -> Self {
+        Self {
+            calldata: calldata.into(),
+            ..self
+        }
+    }
+
+    /// The authorization the call executes.
+    fn authorization(&self) -> Eip3009Authorization {
+        Eip3009Authorization {
+            token: self.contract_address,
+            authorizer: self.from,
+            nonce: self.nonce,
+        }
+    }
+
+    /// The call as a [`TransactionRequest`], for simulation.
+    fn request(&self) -> TransactionRequest {
+        TransactionRequest::default()
+            .to(self.target)
+            .input(self.calldata.clone().into())
+    }
+}
+
+/// Runs `calls` one after the other within a single simulation, so that each one sees the effects
+/// of the previous ones, returning whether each call succeeded along with its output.
+///
+/// With the universal validator, the calls are batched through Multicall3. Otherwise they are
+/// simulated with `eth_simulateV1`, which neither needs the validator nor Multicall3 to be deployed.
+async fn simulate_in_sequence<P: Provider>(
+    provider: P,
+    eip6492_validation: Eip6492Validation,
+    calls: Vec<(Address, Bytes)>,
+) -> Result<Vec<(bool, Bytes)>, Eip155ExactError> {
+    match eip6492_validation {
+        Eip6492Validation::Validator => {
+            let calls = calls
+                .into_iter()
+                .map(|(target, call_data)| IMulticall3::Call3 {
+                    target,
+                    allowFailure: true,
+                    callData: call_data,
+                })
+                .collect();
+            let aggregate_call = IMulticall3::aggregate3Call { calls };
+            let output = provider
+                .call(
+                    TransactionRequest::default()
+                        .to(MULTICALL3_ADDRESS)
+                        .input(aggregate_call.abi_encode().into()),
+                )
+                .await?;
+            let results = IMulticall3::aggregate3Call::abi_decode_returns(&output)
+                .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
+            Ok(results
+                .into_iter()
+                .map(|result| (result.success, result.returnData))
+                .collect())
+        }
+        Eip6492Validation::Deployless => {
+            let calls = calls
+                .into_iter()
+                .map(|(target, call_data)| {
+                    TransactionRequest::default()
+                        .to(target)
+                        .input(call_data.into())
+                })
+                .collect();
+            let payload = SimulatePayload {
+                block_state_calls: vec![SimBlock {
+                    block_overrides: None,
+                    state_overrides: None,
+                    calls,
+                }],
+                trace_transfers: false,
+                validation: false,
+                return_full_transactions: false,
+            };
+            let results = provider
+                .simulate(&payload)
+                .await?
+                .into_iter()
+                .flat_map(|block| block.calls)
+                .map(|result| (result.status, result.return_data))
+                .collect();
+            Ok(results)
+        }
+    }
 }
 
 /// Check whether contract code is present at `address`.
@@ -703,6 +926,7 @@ pub async fn verify_payment<P: Provider>(
     contract: &IEIP3009::IEIP3009Instance<P>,
     payment: &ExactEvmPayment,
     eip712_domain: &Eip712Domain,
+    eip6492_validation: Eip6492Validation,
 ) -> Result<Address, Eip155ExactError> {
     let signed_message = SignedMessage::extract(payment, eip712_domain)?;
 
@@ -710,24 +934,36 @@ pub async fn verify_payment<P: Provider>(
     let hash = signed_message.hash;
     match signed_message.signature {
         StructuredSignature::EIP6492 {
-            factory: _,
-            factory_calldata: _,
+            factory,
+            factory_calldata,
             inner,
             original,
         } => {
-            // Prepare the call to validate EIP-6492 signature
-            let validator6492 = Validator6492::new(VALIDATOR_ADDRESS, &provider);
-            let is_valid_signature_call =
-                validator6492.isValidSigWithSideEffects(payer, hash, original);
-            // Prepare the call to simulate transfer the funds
-            let transfer_call = TransferWithAuthorization0Call::new(contract, payment, inner);
-            let transfer_call = transfer_call.0;
-            // Execute both calls in a single transaction simulation to accommodate for possible smart wallet creation
-            let (is_valid_signature_result, transfer_result) = provider
-                .multicall()
-                .add(is_valid_signature_call)
-                .add(transfer_call.tx)
-                .aggregate3()
+            // Calls validating the signature, the last one of which reports whether it is valid
+            let signature_calls = match eip6492_validation {
+                Eip6492Validation::Validator => {
+                    let is_valid_signature_call = Validator6492::isValidSigWithSideEffectsCall {
+                        _signer: payer,
+                        _hash: hash,
+                        _signature: original,
+                    };
+                    vec![(
+                        VALIDATOR_ADDRESS,
+                        is_valid_signature_call.abi_encode().into(),
+                    )]
+                }
+                Eip6492Validation::Deployless => {
+                    deployless_eip6492_calls(payer, hash, factory, factory_calldata, inner.clone())
+                        .to_vec()
+                }
+            };
+            let transfer_call = TransferWithAuthorization0Call::new(contract, payment, inner).0;
+            let calls = signature_calls
+                .into_iter()
+                .chain([(transfer_call.target, transfer_call.calldata.clone())])
+                .collect();
+            // Execute all calls in a single simulation to accommodate for possible smart wallet creation
+            let results = simulate_in_sequence(&provider, eip6492_validation, calls)
                 .instrument(tracing::info_span!("call_transferWithAuthorization_0",
                         from = %transfer_call.from,
                         to = %transfer_call.to,
@@ -737,27 +973,54 @@ pub async fn verify_payment<P: Provider>(
                         nonce = %transfer_call.nonce,
                         signature = %transfer_call.signature,
                         token_contract = %transfer_call.contract_address,
+                        method = ?transfer_call.method,
+                        sig_kind = "EIP6492",
+                        eip6492_validation = ?eip6492_validation,
                         otel.kind = "client",
                 ))
                 .await?;
-            let is_valid_signature_result = is_valid_signature_result
-                .map_err(|e| PaymentVerificationError::InvalidSignature(e.to_string()))?;
-            if !is_valid_signature_result {
+            let [
+                ..,
+                (is_valid_signature_success, is_valid_signature_output),
+                (transfer_success, _),
+            ] = results.as_slice()
+            else {
+                return Err(PaymentVerificationError::TransactionSimulation(
+                    "Unexpected simulation result".to_string(),
+                )
+                .into());
+            };
+            let is_valid_signature = *is_valid_signature_success
+                && match eip6492_validation {
+                    Eip6492Validation::Validator => {
+                        Validator6492::isValidSigWithSideEffectsCall::abi_decode_returns(
+                            is_valid_signature_output,
+                        )
+                        .unwrap_or(false)
+                    }
+                    Eip6492Validation::Deployless => {
+                        is_erc1271_magic_value(is_valid_signature_output)
+                    }
+                };
+            if !is_valid_signature {
                 return Err(PaymentVerificationError::InvalidSignature(
                     "Chain reported signature to be invalid".to_string(),
                 )
                 .into());
             }
-            transfer_result
-                .map_err(|e| PaymentVerificationError::TransactionSimulation(e.to_string()))?;
+            if !transfer_success {
+                return Err(PaymentVerificationError::TransactionSimulation(format!(
+                    "{:?} reverted",
+                    transfer_call.method
+                ))
+                .into());
+            }
         }
         StructuredSignature::EIP1271(signature) => {
             // It is EIP-1271 signature, which we can pass to the transfer simulation
-            let transfer_call = TransferWithAuthorization0Call::new(contract, payment, signature);
-            let transfer_call = transfer_call.0;
-            transfer_call
-                .tx
-                .call()
+            let transfer_call = TransferWithAuthorization0Call::new(contract, payment, signature).0;
+            provider
+                .call(transfer_call.request())
                 .into_future()
                 .instrument(tracing::info_span!("call_transferWithAuthorization_0",
                         from = %transfer_call.from,
@@ -768,17 +1031,16 @@ pub async fn verify_payment<P: Provider>(
                         nonce = %transfer_call.nonce,
                         signature = %transfer_call.signature,
                         token_contract = %transfer_call.contract_address,
+                        method = ?transfer_call.method,
                         otel.kind = "client",
                 ))
                 .await?;
         }
         StructuredSignature::EOA(signature) => {
             // It is EOA signature, which we can pass to the transfer simulation of (r,s,v)-based transferWithAuthorization function
-            let transfer_call = TransferWithAuthorization1Call::new(contract, payment, signature);
-            let transfer_call = transfer_call.0;
-            transfer_call
-                .tx
-                .call()
+            let transfer_call = TransferWithAuthorization1Call::new(contract, payment, signature).0;
+            provider
+                .call(transfer_call.request())
                 .into_future()
                 .instrument(tracing::info_span!("call_transferWithAuthorization_1",
                         from = %transfer_call.from,
@@ -789,6 +1051,7 @@ pub async fn verify_payment<P: Provider>(
                         nonce = %transfer_call.nonce,
                         signature = %transfer_call.signature,
                         token_contract = %transfer_call.contract_address,
+                        method = ?transfer_call.method,
                         otel.kind = "client",
                 ))
                 .await?;
@@ -803,6 +1066,7 @@ pub async fn settle_payment<P, E>(
     contract: &IEIP3009::IEIP3009Instance<&P::Inner>,
     payment: &ExactEvmPayment,
     eip712_domain: &Eip712Domain,
+    confirmations: u64,
 ) -> Result<TxHash, Eip155ExactError>
 where
     P: Eip155MetaTransactionProvider<Error = E>,
@@ -822,13 +1086,15 @@ where
             let transfer_call = transfer_call.0;
             if is_contract_deployed {
                 // transferWithAuthorization with inner signature
-                Eip155MetaTransactionProvider::send_transaction(
+                Eip155MetaTransactionProvider::send_batchable_transaction(
                     &provider,
                     MetaTransaction {
-                        to: transfer_call.tx.target(),
-                        calldata: transfer_call.tx.calldata().clone(),
-                        confirmations: 1,
+                        to: transfer_call.target,
+                        calldata: transfer_call.calldata.clone(),
+                        confirmations,
+                        from: None,
                     },
+                    transfer_call.authorization(),
                 )
                 .instrument(
                     tracing::info_span!("call_transferWithAuthorization_0",
@@ -840,6 +1106,7 @@ where
                         nonce = %transfer_call.nonce,
                         signature = %transfer_call.signature,
                         token_contract = %transfer_call.contract_address,
+                        method = ?transfer_call.method,
                         sig_kind="EIP6492.deployed",
                         otel.kind = "client",
                     ),
@@ -853,19 +1120,21 @@ where
                 };
                 let transfer_with_authorization_call = IMulticall3::Call3 {
                     allowFailure: false,
-                    target: transfer_call.tx.target(),
-                    callData: transfer_call.tx.calldata().clone(),
+                    target: transfer_call.target,
+                    callData: transfer_call.calldata.clone(),
                 };
                 let aggregate_call = IMulticall3::aggregate3Call {
                     calls: vec![deployment_call, transfer_with_authorization_call],
                 };
-                Eip155MetaTransactionProvider::send_transaction(
+                Eip155MetaTransactionProvider::send_batchable_transaction(
                     &provider,
                     MetaTransaction {
                         to: MULTICALL3_ADDRESS,
                         calldata: aggregate_call.abi_encode().into(),
-                        confirmations: 1,
+                        confirmations,
+                        from: None,
                     },
+                    transfer_call.authorization(),
                 )
                 .instrument(
                     tracing::info_span!("call_transferWithAuthorization_0",
@@ -877,6 +1146,7 @@ where
                         nonce = %transfer_call.nonce,
                         signature = %transfer_call.signature,
                         token_contract = %transfer_call.contract_address,
+                        method = ?transfer_call.method,
                         sig_kind="EIP6492.counterfactual",
                         otel.kind = "client",
                     ),
@@ -888,13 +1158,15 @@ where
                 TransferWithAuthorization0Call::new(contract, payment, eip1271_signature);
             let transfer_call = transfer_call.0;
             // transferWithAuthorization with eip1271 signature
-            Eip155MetaTransactionProvider::send_transaction(
+            Eip155MetaTransactionProvider::send_batchable_transaction(
                 &provider,
                 MetaTransaction {
-                    to: transfer_call.tx.target(),
-                    calldata: transfer_call.tx.calldata().clone(),
-                    confirmations: 1,
+                    to: transfer_call.target,
+                    calldata: transfer_call.calldata.clone(),
+                    confirmations,
+                    from: None,
                 },
+                transfer_call.authorization(),
             )
             .instrument(tracing::info_span!("call_transferWithAuthorization_0",
                 from = %transfer_call.from,
@@ -905,6 +1177,7 @@ where
                 nonce = %transfer_call.nonce,
                 signature = %transfer_call.signature,
                 token_contract = %transfer_call.contract_address,
+                method = ?transfer_call.method,
                 sig_kind="EIP1271",
                 otel.kind = "client",
             ))
@@ -913,13 +1186,15 @@ where
             let transfer_call = TransferWithAuthorization1Call::new(contract, payment, signature);
             let transfer_call = transfer_call.0;
             // transferWithAuthorization with EOA signature
-            Eip155MetaTransactionProvider::send_transaction(
+            Eip155MetaTransactionProvider::send_batchable_transaction(
                 &provider,
                 MetaTransaction {
-                    to: transfer_call.tx.target(),
-                    calldata: transfer_call.tx.calldata().clone(),
-                    confirmations: 1,
+                    to: transfer_call.target,
+                    calldata: transfer_call.calldata.clone(),
+                    confirmations,
+                    from: None,
                 },
+                transfer_call.authorization(),
             )
             .instrument(tracing::info_span!("call_transferWithAuthorization_1",
                 from = %transfer_call.from,
@@ -930,6 +1205,7 @@ where
                 nonce = %transfer_call.nonce,
                 signature = %transfer_call.signature,
                 token_contract = %transfer_call.contract_address,
+                method = ?transfer_call.method,
                 sig_kind="EOA",
                 otel.kind = "client",
             ))
@@ -957,6 +1233,25 @@ where
     }
 }
 
+/// Checks that the payee can take the payment.
+///
+/// `receiveWithAuthorization` is forwarded through `payTo`, which must be a contract implementing
+/// [`IERC3009Payee`]: a call to an account without code would succeed without moving any funds.
+pub async fn assert_payee<P: Provider>(
+    provider: P,
+    payment: &ExactEvmPayment,
+) -> Result<(), Eip155ExactError> {
+    if payment.method == AuthorizationMethod::ReceiveWithAuthorization
+        && !is_contract_deployed(provider, &payment.to).await?
+    {
+        return Err(PaymentVerificationError::InvalidFormat(
+            "payTo must be a contract to settle with receiveWithAuthorization".to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Eip155ExactError {
     #[error(transparent)]
@@ -994,6 +1289,7 @@ impl From<MetaTransactionSendError> for Eip155ExactError {
         match e {
             MetaTransactionSendError::Transport(e) => Self::Transport(e),
             MetaTransactionSendError::PendingTransaction(e) => Self::PendingTransaction(e),
+            MetaTransactionSendError::Batch(e) => Self::ContractCall(e),
         }
     }
 }
@@ -1018,6 +1314,15 @@ impl From<MulticallError> for Eip155ExactError {
     }
 }
 
+impl From<TokenMetadataError> for Eip155ExactError {
+    fn from(e: TokenMetadataError) -> Self {
+        match e {
+            TokenMetadataError::Contract(e) => e.into(),
+            TokenMetadataError::CallFailed { .. } => Self::ContractCall(e.to_string()),
+        }
+    }
+}
+
 impl From<alloy_contract::Error> for Eip155ExactError {
     fn from(e: alloy_contract::Error) -> Self {
         match e {
@@ -1032,3 +1337,141 @@ impl From<alloy_contract::Error> for Eip155ExactError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::v1_eip155_exact::client::{
+        Eip3009SigningParams, sign_erc3009_authorization,
+    };
+    use alloy_primitives::address;
+    use alloy_signer_local::PrivateKeySigner;
+
+    #[tokio::test]
+    async fn test_receive_with_authorization_signature() {
+        let signer = PrivateKeySigner::random();
+        let extra = PaymentRequirementsExtra {
+            name: "USD Coin".to_string(),
+            version: "2".to_string(),
+            authorization_method: AuthorizationMethod::ReceiveWithAuthorization,
+        };
+        let params = Eip3009SigningParams {
+            chain_id: 8453,
+            asset_address: address!("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"),
+            pay_to: address!("0x1111111111111111111111111111111111111111"),
+            amount: U256::from(1_000_000),
+            max_timeout_seconds: 300,
+            extra: Some(extra.clone()),
+        };
+        let payload = sign_erc3009_authorization(&signer, &params).await.unwrap();
+        let domain = eip712_domain! {
+            name: extra.name,
+            version: extra.version,
+            chain_id: params.chain_id,
+            verifying_contract: params.asset_address,
+        };
+        let authorization = payload.authorization;
+        let mut payment = ExactEvmPayment {
+            from: authorization.from,
+            to: authorization.to,
+            value: authorization.value,
+            valid_after: authorization.valid_after,
+            valid_before: authorization.valid_before,
+            nonce: authorization.nonce,
+            signature: payload.signature,
+            method: AuthorizationMethod::ReceiveWithAuthorization,
+        };
+        let signed_message = SignedMessage::extract(&payment, &domain).unwrap();
+        assert!(matches!(
+            signed_message.signature,
+            StructuredSignature::EOA(_)
+        ));
+
+        // The signature does not authorize transferWithAuthorization.
+        payment.method = AuthorizationMethod::TransferWithAuthorization;
+        let signed_message = SignedMessage::extract(&payment, &domain).unwrap();
+        assert!(matches!(
+            signed_message.signature,
+            StructuredSignature::EIP1271(_)
+        ));
+    }
+
+    #[test]
+    fn test_receive_with_authorization_goes_through_payee() {
+        let token = address!("0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913");
+        let contract = IEIP3009::new(
+            token,
+            alloy_provider::ProviderBuilder::new()
+                .connect_http("http://127.0.0.1:1".parse().unwrap()),
+        );
+        let mut payment = ExactEvmPayment {
+            from: Address::repeat_byte(0x11),
+            to: Address::repeat_byte(0x22),
+            value: U256::from(1_000_000),
+            valid_after: UnixTimestamp::from_secs(1),
+            valid_before: UnixTimestamp::from_secs(2),
+            nonce: B256::repeat_byte(0x33),
+            signature: Bytes::new(),
+            method: AuthorizationMethod::TransferWithAuthorization,
+        };
+        let signature = Bytes::from_static(&[1, 2, 3]);
+
+        let call = TransferWithAuthorization0Call::new(&contract, &payment, signature.clone()).0;
+        assert_eq!(call.target, token);
+        let decoded =
+            IEIP3009::transferWithAuthorization_0Call::abi_decode(&call.calldata).unwrap();
+        assert_eq!(decoded.to, payment.to);
+
+        payment.method = AuthorizationMethod::ReceiveWithAuthorization;
+        let call = TransferWithAuthorization0Call::new(&contract, &payment, signature.clone()).0;
+        assert_eq!(call.target, payment.to);
+        let decoded =
+            IERC3009Payee::receiveWithAuthorization_0Call::abi_decode(&call.calldata).unwrap();
+        assert_eq!(decoded.token, token);
+        assert_eq!(decoded.from, payment.from);
+        assert_eq!(decoded.signature, signature);
+    }
+
+    #[test]
+    fn test_authorization_method_extra() {
+        let extra: PaymentRequirementsExtra =
+            serde_json::from_str(r#"{"name":"USD Coin","version":"2"}"#).unwrap();
+        assert_eq!(
+            extra.authorization_method,
+            AuthorizationMethod::TransferWithAuthorization
+        );
+        let extra: PaymentRequirementsExtra = serde_json::from_str(
+            r#"{"name":"USD Coin","version":"2","authorizationMethod":"receiveWithAuthorization"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            extra.authorization_method,
+            AuthorizationMethod::ReceiveWithAuthorization
+        );
+    }
+
+    #[test]
+    fn test_deployless_eip6492_calls() {
+        let payer = Address::repeat_byte(0x11);
+        let factory = Address::repeat_byte(0x22);
+        let hash = B256::repeat_byte(0x33);
+        let [deployment, is_valid_signature] = deployless_eip6492_calls(
+            payer,
+            hash,
+            factory,
+            Bytes::from_static(&[1, 2, 3]),
+            Bytes::from_static(&[4, 5]),
+        );
+        assert_eq!(deployment, (factory, Bytes::from_static(&[1, 2, 3])));
+        assert_eq!(is_valid_signature.0, payer);
+        let call = IERC1271::isValidSignatureCall::abi_decode(&is_valid_signature.1).unwrap();
+        assert_eq!(call.hash, hash);
+        assert_eq!(call.signature, Bytes::from_static(&[4, 5]));
+
+        let valid = IERC1271::isValidSignatureCall::abi_encode_returns(&ERC1271_MAGIC_VALUE.into());
+        assert!(is_erc1271_magic_value(&valid));
+        let invalid = IERC1271::isValidSignatureCall::abi_encode_returns(&[0xff; 4].into());
+        assert!(!is_erc1271_magic_value(&invalid));
+        assert!(!is_erc1271_magic_value(&[]));
+    }
+}
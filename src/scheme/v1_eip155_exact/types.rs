@@ -42,4 +42,23 @@ pub type PaymentRequirements =
 pub struct PaymentRequirementsExtra {
     pub name: String,
     pub version: String,
+    #[serde(default, skip_serializing_if = "AuthorizationMethod::is_transfer")]
+    pub authorization_method: AuthorizationMethod,
+}
+
+/// ERC-3009 function the authorization is signed for and settled with.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuthorizationMethod {
+    /// `transferWithAuthorization`, which anyone holding the signature can submit.
+    #[default]
+    TransferWithAuthorization,
+    /// `receiveWithAuthorization`, which only the payee can submit.
+    ReceiveWithAuthorization,
+}
+
+impl AuthorizationMethod {
+    pub fn is_transfer(&self) -> bool {
+        matches!(self, AuthorizationMethod::TransferWithAuthorization)
+    }
 }
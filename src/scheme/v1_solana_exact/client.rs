@@ -9,23 +9,29 @@ use solana_client::rpc_response::{RpcPrioritizationFee, RpcResult, RpcSimulateTr
 use solana_compute_budget_interface::ComputeBudgetInstruction;
 use solana_message::v0::Message as MessageV0;
 use solana_message::{Hash, VersionedMessage};
+use solana_nonce::state::State as NonceState;
+use solana_nonce::versions::Versions as NonceVersions;
 use solana_pubkey::Pubkey;
 use solana_signature::Signature;
 use solana_signer::Signer;
-use solana_transaction::Instruction;
 use solana_transaction::versioned::VersionedTransaction;
+use solana_transaction::{AccountMeta, Instruction};
 use spl_token::solana_program::program_pack::Pack;
 
 use crate::chain::ChainId;
-use crate::chain::solana::Address;
+use crate::chain::solana::{
+    ATA_PROGRAM_PUBKEY, Address, RECENT_BLOCKHASHES_SYSVAR_PUBKEY,
+    SYSTEM_ADVANCE_NONCE_DISCRIMINATOR, SYSTEM_PROGRAM_PUBKEY,
+};
 use crate::proto::PaymentRequired;
 use crate::proto::client::{PaymentCandidate, PaymentCandidateSigner, X402Error, X402SchemeClient};
+use crate::proto::util::U64String;
 use crate::proto::v1::X402Version1;
 use crate::scheme::X402SchemeId;
 use crate::scheme::v1_solana_exact::types::{
     ExactScheme, ExactSolanaPayload, PaymentPayload, PaymentRequirements,
 };
-use crate::scheme::v1_solana_exact::{ATA_PROGRAM_PUBKEY, TransactionInt, V1SolanaExact};
+use crate::scheme::v1_solana_exact::{NATIVE_MINT_PUBKEY, TransactionInt, V1SolanaExact};
 use crate::util::Base64Bytes;
 
 /// Mint information for SPL tokens
@@ -78,11 +84,14 @@ pub async fn fetch_mint<R: RpcClientLike>(
 }
 
 /// Build the message we want to simulate (priority fee + transfer Ixs).
+///
+/// `advance_nonce` is placed first in the simulated message, but is not part of the returned instructions.
 pub fn build_message_to_simulate(
     fee_payer: Pubkey,
     transfer_instructions: &[Instruction],
     priority_micro_lamports: u64,
     recent_blockhash: Hash,
+    advance_nonce: Option<&Instruction>,
 ) -> Result<(MessageV0, Vec<Instruction>), X402Error> {
     let set_price = ComputeBudgetInstruction::set_compute_unit_price(priority_micro_lamports);
 
@@ -91,7 +100,8 @@ pub fn build_message_to_simulate(
     ixs.extend(transfer_instructions.to_owned());
 
     let with_cu_limit = {
-        let mut ixs_mod = ixs.clone();
+        let mut ixs_mod = advance_nonce.into_iter().cloned().collect::<Vec<_>>();
+        ixs_mod.extend(ixs.clone());
         update_or_append_set_compute_unit_limit(&mut ixs_mod, 1e5 as u32);
         ixs_mod
     };
@@ -167,16 +177,81 @@ pub fn update_or_append_set_compute_unit_limit(ixs: &mut Vec<Instruction>, units
     }
 }
 
-/// Build and sign a Solana token transfer transaction.
-/// Returns the base64-encoded signed transaction.
-pub async fn build_signed_transfer_transaction<S: Signer, R: RpcClientLike>(
-    signer: &S,
+/// Build a System Program `Transfer` instruction of native SOL.
+pub fn system_transfer_instruction(from: &Pubkey, to: &Pubkey, lamports: u64) -> Instruction {
+    let mut data = Vec::with_capacity(12);
+    data.extend_from_slice(&2u32.to_le_bytes());
+    data.extend_from_slice(&lamports.to_le_bytes());
+    Instruction::new_with_bytes(
+        SYSTEM_PROGRAM_PUBKEY,
+        &data,
+        vec![AccountMeta::new(*from, true), AccountMeta::new(*to, false)],
+    )
+}
+
+/// Build a System Program `AdvanceNonceAccount` instruction, which must be the first instruction
+/// of a transaction using the durable nonce of `nonce_account` as its blockhash.
+pub fn advance_nonce_instruction(nonce_account: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction::new_with_bytes(
+        SYSTEM_PROGRAM_PUBKEY,
+        &SYSTEM_ADVANCE_NONCE_DISCRIMINATOR,
+        vec![
+            AccountMeta::new(*nonce_account, false),
+            AccountMeta::new_readonly(RECENT_BLOCKHASHES_SYSVAR_PUBKEY, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+    )
+}
+
+/// Fetch the current durable nonce of `nonce_account`, checking that `authority` can advance it.
+pub async fn fetch_durable_nonce<R: RpcClientLike>(
     rpc_client: &R,
-    fee_payer: &Pubkey,
+    nonce_account: &Pubkey,
+    authority: &Pubkey,
+) -> Result<Hash, X402Error> {
+    let account = rpc_client.get_account(nonce_account).await.map_err(|e| {
+        X402Error::SigningError(format!(
+            "failed to fetch nonce account {nonce_account}: {e}"
+        ))
+    })?;
+    let versions = bincode::deserialize::<NonceVersions>(&account.data).map_err(|e| {
+        X402Error::SigningError(format!(
+            "failed to decode nonce account {nonce_account}: {e}"
+        ))
+    })?;
+    match versions {
+        NonceVersions::Current(state) => match *state {
+            NonceState::Initialized(data) if data.authority == *authority => Ok(data.blockhash()),
+            NonceState::Initialized(_) => Err(X402Error::SigningError(format!(
+                "nonce account {nonce_account} has a different authority"
+            ))),
+            NonceState::Uninitialized => Err(X402Error::SigningError(format!(
+                "nonce account {nonce_account} is not initialized"
+            ))),
+        },
+        NonceVersions::Legacy(_) => Err(X402Error::SigningError(format!(
+            "nonce account {nonce_account} is a legacy nonce account"
+        ))),
+    }
+}
+
+/// Build the transfer instruction for `amount` of `asset`, along with the writable accounts
+/// used to estimate the priority fee.
+///
+/// The native mint requests native SOL, sent to `pay_to` with a System Program transfer.
+/// Any other asset is sent with an SPL `TransferChecked` to the `pay_to` associated token account.
+async fn build_transfer_instruction<R: RpcClientLike>(
+    rpc_client: &R,
+    client_pubkey: &Pubkey,
     pay_to: &Address,
     asset: &Address,
     amount: u64,
-) -> Result<String, X402Error> {
+) -> Result<(Instruction, Vec<Pubkey>), X402Error> {
+    if *asset.pubkey() == NATIVE_MINT_PUBKEY {
+        let instruction = system_transfer_instruction(client_pubkey, pay_to.pubkey(), amount);
+        return Ok((instruction, vec![*pay_to.pubkey(), *client_pubkey]));
+    }
+
     let mint = fetch_mint(asset, rpc_client).await?;
 
     let (ata, _) = Pubkey::find_program_address(
@@ -188,7 +263,6 @@ pub async fn build_signed_transfer_transaction<S: Signer, R: RpcClientLike>(
         &ATA_PROGRAM_PUBKEY,
     );
 
-    let client_pubkey = signer.pubkey();
     let (source_ata, _) = Pubkey::find_program_address(
         &[
             client_pubkey.as_ref(),
@@ -208,7 +282,7 @@ pub async fn build_signed_transfer_transaction<S: Signer, R: RpcClientLike>(
             &source_ata,
             asset.pubkey(),
             &destination_ata,
-            &client_pubkey,
+            client_pubkey,
             &[],
             amount,
             decimals,
@@ -222,31 +296,71 @@ pub async fn build_signed_transfer_transaction<S: Signer, R: RpcClientLike>(
             &source_ata,
             asset.pubkey(),
             &destination_ata,
-            &client_pubkey,
+            client_pubkey,
             &[],
             amount,
             decimals,
         )
         .map_err(|e| X402Error::SigningError(format!("{e}")))?,
     };
+    Ok((transfer_instruction, vec![destination_ata, source_ata]))
+}
 
-    let recent_blockhash = rpc_client
-        .get_latest_blockhash()
-        .await
-        .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+/// Build and sign a Solana transfer transaction, of SPL tokens or native SOL.
+/// Returns the base64-encoded signed transaction.
+///
+/// With a `durable_nonce` account, whose authority is the signer, the transaction uses its current nonce
+/// instead of a recent blockhash, so it does not expire until the nonce is advanced.
+///
+/// The priority fee is capped at `max_compute_unit_price` when the facilitator advertises one.
+#[allow(clippy::too_many_arguments)]
+pub async fn build_signed_transfer_transaction<S: Signer, R: RpcClientLike>(
+    signer: &S,
+    rpc_client: &R,
+    fee_payer: &Pubkey,
+    pay_to: &Address,
+    asset: &Address,
+    amount: u64,
+    durable_nonce: Option<&Pubkey>,
+    max_compute_unit_price: Option<u64>,
+) -> Result<String, X402Error> {
+    let client_pubkey = signer.pubkey();
+    let (transfer_instruction, writable_accounts) =
+        build_transfer_instruction(rpc_client, &client_pubkey, pay_to, asset, amount).await?;
+
+    let (recent_blockhash, advance_nonce) = match durable_nonce {
+        Some(nonce_account) => {
+            let nonce = fetch_durable_nonce(rpc_client, nonce_account, &client_pubkey).await?;
+            let advance_nonce = advance_nonce_instruction(nonce_account, &client_pubkey);
+            (nonce, Some(advance_nonce))
+        }
+        None => {
+            let recent_blockhash = rpc_client
+                .get_latest_blockhash()
+                .await
+                .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+            (recent_blockhash, None)
+        }
+    };
 
-    let fee =
-        get_priority_fee_micro_lamports(rpc_client, &[*fee_payer, destination_ata, source_ata])
-            .await?;
+    let fee_accounts = [vec![*fee_payer], writable_accounts].concat();
+    let fee = get_priority_fee_micro_lamports(rpc_client, &fee_accounts).await?;
+    let fee = max_compute_unit_price.map_or(fee, |max| fee.min(max));
 
-    let (msg_to_sim, instructions) =
-        build_message_to_simulate(*fee_payer, &[transfer_instruction], fee, recent_blockhash)?;
+    let (msg_to_sim, instructions) = build_message_to_simulate(
+        *fee_payer,
+        &[transfer_instruction],
+        fee,
+        recent_blockhash,
+        advance_nonce.as_ref(),
+    )?;
 
     let estimated_cu = estimate_compute_units(rpc_client, &msg_to_sim).await?;
 
     let cu_ix = ComputeBudgetInstruction::set_compute_unit_limit(estimated_cu);
     let msg = {
-        let mut final_instructions = Vec::with_capacity(instructions.len() + 1);
+        let mut final_instructions = Vec::with_capacity(instructions.len() + 2);
+        final_instructions.extend(advance_nonce);
         final_instructions.push(cu_ix);
         final_instructions.extend(instructions);
         MessageV0::try_compile(fee_payer, &final_instructions, &[], recent_blockhash)
@@ -279,12 +393,24 @@ pub async fn build_signed_transfer_transaction<S: Signer, R: RpcClientLike>(
 pub struct V1SolanaExactClient<S, R> {
     signer: S,
     rpc_client: R,
+    durable_nonce: Option<Pubkey>,
 }
 
 #[allow(dead_code)] // Public for consumption by downstream crates.
 impl<S, R> V1SolanaExactClient<S, R> {
     pub fn new(signer: S, rpc_client: R) -> Self {
-        Self { signer, rpc_client }
+        Self {
+            signer,
+            rpc_client,
+            durable_nonce: None,
+        }
+    }
+
+    /// Builds payment transactions on the durable nonce of `nonce_account`, whose authority is the signer,
+    /// so that they stay valid until settled.
+    pub fn with_durable_nonce(mut self, nonce_account: Pubkey) -> Self {
+        self.durable_nonce = Some(nonce_account);
+        self
     }
 }
 
@@ -334,6 +460,7 @@ where
                         signer: self.signer.clone(),
                         rpc_client: self.rpc_client.clone(),
                         requirements,
+                        durable_nonce: self.durable_nonce,
                     }),
                 };
                 Some(candidate)
@@ -347,6 +474,7 @@ pub struct PayloadSigner<S, R> {
     signer: S,
     rpc_client: R,
     requirements: PaymentRequirements,
+    durable_nonce: Option<Pubkey>,
 }
 
 #[allow(dead_code)] // Public for consumption by downstream crates.
@@ -362,6 +490,12 @@ impl<S: Signer + Sync, R: RpcClientLike + Sync> PaymentCandidateSigner for Paylo
                 "missing fee_payer in extra".to_string(),
             ))?;
         let fee_payer_pubkey: Pubkey = fee_payer.into();
+        let max_compute_unit_price = self
+            .requirements
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.max_compute_unit_price.as_ref())
+            .map(U64String::inner);
 
         let amount = self.requirements.max_amount_required.inner();
         let tx_b64 = build_signed_transfer_transaction(
@@ -371,6 +505,8 @@ impl<S: Signer + Sync, R: RpcClientLike + Sync> PaymentCandidateSigner for Paylo
             &self.requirements.pay_to,
             &self.requirements.asset,
             amount,
+            self.durable_nonce.as_ref(),
+            max_compute_unit_price,
         )
         .await?;
 
@@ -0,0 +1,306 @@
+//! Typed configuration for the Solana `exact` schemes.
+
+use serde::{Deserialize, Deserializer};
+use solana_pubkey::{Pubkey, pubkey};
+use spl_token_2022_interface::extension::ExtensionType;
+use std::str::FromStr;
+
+use crate::chain::Finality;
+use crate::chain::solana::{ATA_PROGRAM_PUBKEY, Address, SYSTEM_PROGRAM_PUBKEY};
+use crate::proto::PaymentVerificationError;
+
+/// Token-2022 mint extensions accepted by default: none of them lets an authority block or move
+/// the tokens, or runs additional programs on transfer. Transfer fees are deducted from the
+/// received amount.
+///
+/// `Pausable`, `DefaultAccountState` (which can freeze new token accounts) and the confidential
+/// transfer extensions (whose authority can approve or block accounts) have to be allowed explicitly.
+pub const DEFAULT_ALLOWED_MINT_EXTENSIONS: &[ExtensionType] = &[
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MintCloseAuthority,
+    ExtensionType::InterestBearingConfig,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::GroupPointer,
+    ExtensionType::TokenGroup,
+    ExtensionType::GroupMemberPointer,
+    ExtensionType::TokenGroupMember,
+    ExtensionType::ScaledUiAmount,
+];
+
+/// SPL Memo program.
+pub const MEMO_PROGRAM_PUBKEY: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+/// Legacy SPL Memo program, still used by some wallets.
+pub const MEMO_V1_PROGRAM_PUBKEY: Pubkey = pubkey!("Memo1UhkJRfHyvLMcVucJwxXeuD728EVVDDwQDxFMNo");
+/// Lighthouse assertion program, injected by wallets such as Phantom.
+pub const LIGHTHOUSE_PROGRAM_PUBKEY: Pubkey =
+    pubkey!("L2TExMFKdjpN9kozasaurPirfHy9P8sbXoAN1qA3S95");
+
+/// Configuration of the `v1-solana-exact` and `v2-solana-exact` schemes,
+/// read from the `config` field of a scheme entry and validated when the scheme is built.
+///
+/// Example JSON:
+/// ```json
+/// {
+///   "allowed_mints": ["EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"],
+///   "min_amount": 1000,
+///   "max_amount": 100000000,
+///   "commitment": "finalized",
+///   "allowed_mint_extensions": ["TransferFeeConfig", "MetadataPointer", "TokenMetadata"],
+///   "auxiliary_programs": [
+///     { "program": "memo", "max_data_length": 64 },
+///     { "program": "lighthouse", "max_instructions": 4 }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SolanaExactConfig {
+    /// Token mints accepted for payment (optional, defaults to any mint).
+    #[serde(default)]
+    pub allowed_mints: Option<Vec<Address>>,
+    /// Minimum required payment amount, in token units (optional).
+    #[serde(default)]
+    pub min_amount: Option<u64>,
+    /// Maximum required payment amount, in token units (optional).
+    #[serde(default)]
+    pub max_amount: Option<u64>,
+    /// Commitment to wait for when settling: `processed`, `confirmed` or `finalized`
+    /// (optional, defaults to `confirmed`). The chain `finality`, if set, takes precedence.
+    #[serde(default)]
+    pub commitment: Option<Finality>,
+    /// Token-2022 mint extensions accepted for payment
+    /// (optional, defaults to [`DEFAULT_ALLOWED_MINT_EXTENSIONS`]).
+    /// Mints with a transfer hook or non-transferable mints are always rejected.
+    #[serde(default)]
+    pub allowed_mint_extensions: Option<Vec<MintExtension>>,
+    /// Programs whose instructions may appear in the payment transaction besides the compute budget,
+    /// create-ATA and transfer instructions (optional, defaults to none).
+    #[serde(default)]
+    pub auxiliary_programs: Vec<AuxiliaryProgram>,
+}
+
+/// A program allowed to add instructions to the payment transaction, with the rules they must follow.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuxiliaryProgram {
+    /// `"memo"`, `"lighthouse"`, or the address of the program.
+    pub program: AuxiliaryProgramId,
+    /// Maximum number of instructions of this program in a transaction.
+    #[serde(default = "auxiliary_program_defaults::default_max_instructions")]
+    pub max_instructions: usize,
+    /// Maximum instruction data length, in bytes (optional).
+    #[serde(default)]
+    pub max_data_length: Option<usize>,
+    /// Whether the instructions may reference writable accounts.
+    /// Defaults to `false`, so that the program can not move funds.
+    #[serde(default)]
+    pub allow_writable_accounts: bool,
+}
+
+mod auxiliary_program_defaults {
+    pub fn default_max_instructions() -> usize {
+        1
+    }
+}
+
+/// Program of an [`AuxiliaryProgram`]. Memo instructions must be valid UTF-8,
+/// and are returned in the verification response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuxiliaryProgramId {
+    Memo,
+    Lighthouse,
+    Program(Pubkey),
+}
+
+impl AuxiliaryProgramId {
+    /// Whether instructions of `program_id` belong to this program.
+    pub fn matches(&self, program_id: &Pubkey) -> bool {
+        match self {
+            AuxiliaryProgramId::Memo => {
+                *program_id == MEMO_PROGRAM_PUBKEY || *program_id == MEMO_V1_PROGRAM_PUBKEY
+            }
+            AuxiliaryProgramId::Lighthouse => *program_id == LIGHTHOUSE_PROGRAM_PUBKEY,
+            AuxiliaryProgramId::Program(program) => program == program_id,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AuxiliaryProgramId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        match name.as_str() {
+            "memo" => Ok(AuxiliaryProgramId::Memo),
+            "lighthouse" => Ok(AuxiliaryProgramId::Lighthouse),
+            address => Pubkey::from_str(address)
+                .map(AuxiliaryProgramId::Program)
+                .map_err(|_| serde::de::Error::custom(format!("unknown program {address}"))),
+        }
+    }
+}
+
+/// A Token-2022 mint extension, named as its [`ExtensionType`] variant, e.g. `"TransferFeeConfig"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MintExtension(pub ExtensionType);
+
+impl Eq for MintExtension {}
+
+impl<'de> Deserialize<'de> for MintExtension {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        (0..=u8::MAX as u16)
+            .filter_map(|value| ExtensionType::try_from(value).ok())
+            .find(|extension| format!("{extension:?}") == name)
+            .map(MintExtension)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown mint extension {name}")))
+    }
+}
+
+impl SolanaExactConfig {
+    /// Parses and validates the scheme entry `config`, falling back to defaults if absent.
+    pub fn from_json(config: Option<serde_json::Value>) -> Result<Self, SolanaExactConfigError> {
+        let config = match config {
+            Some(config) => serde_json::from_value::<Self>(config)?,
+            None => Self::default(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<(), SolanaExactConfigError> {
+        if self.allowed_mints.as_ref().is_some_and(Vec::is_empty) {
+            return Err(SolanaExactConfigError::EmptyAllowedMints);
+        }
+        if let (Some(min_amount), Some(max_amount)) = (self.min_amount, self.max_amount)
+            && min_amount > max_amount
+        {
+            return Err(SolanaExactConfigError::InvalidAmountBounds);
+        }
+        if let Some(commitment) = self.commitment
+            && !matches!(
+                commitment,
+                Finality::Processed | Finality::Confirmed | Finality::Finalized
+            )
+        {
+            return Err(SolanaExactConfigError::InvalidCommitment(commitment));
+        }
+        for auxiliary in &self.auxiliary_programs {
+            if let AuxiliaryProgramId::Program(program) = auxiliary.program
+                && is_reserved_program(&program)
+            {
+                return Err(SolanaExactConfigError::ReservedAuxiliaryProgram(program));
+            }
+        }
+        Ok(())
+    }
+
+    /// Commitment to settle with, unless the chain or the settle request sets a finality.
+    pub fn commitment(&self) -> Finality {
+        self.commitment.unwrap_or(Finality::Confirmed)
+    }
+
+    /// Checks that the required mint is one of the allowed mints.
+    pub fn assert_mint(&self, mint: &Address) -> Result<(), PaymentVerificationError> {
+        match &self.allowed_mints {
+            Some(allowed_mints) if !allowed_mints.contains(mint) => {
+                Err(PaymentVerificationError::AssetMismatch)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether mints with the given Token-2022 extension are accepted for payment.
+    pub fn is_mint_extension_allowed(&self, extension: ExtensionType) -> bool {
+        match &self.allowed_mint_extensions {
+            Some(allowed) => allowed.contains(&MintExtension(extension)),
+            None => DEFAULT_ALLOWED_MINT_EXTENSIONS.contains(&extension),
+        }
+    }
+
+    /// Checks that the required amount is within the configured bounds.
+    pub fn assert_amount(&self, amount: u64) -> Result<(), PaymentVerificationError> {
+        let below_min = self
+            .min_amount
+            .is_some_and(|min_amount| amount < min_amount);
+        let above_max = self
+            .max_amount
+            .is_some_and(|max_amount| amount > max_amount);
+        if below_min || above_max {
+            Err(PaymentVerificationError::InvalidPaymentAmount)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SolanaExactConfigError {
+    #[error("Invalid scheme config: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("allowed_mints must not be empty")]
+    EmptyAllowedMints,
+    #[error("min_amount must not exceed max_amount")]
+    InvalidAmountBounds,
+    #[error("commitment must be processed, confirmed or finalized, not {0}")]
+    InvalidCommitment(Finality),
+    #[error("Program {0} can not be an auxiliary program")]
+    ReservedAuxiliaryProgram(Pubkey),
+}
+
+/// Programs whose instructions are verified by the scheme itself.
+fn is_reserved_program(program: &Pubkey) -> bool {
+    *program == solana_compute_budget_interface::ID
+        || *program == spl_token::ID
+        || *program == spl_token_2022::ID
+        || *program == SYSTEM_PROGRAM_PUBKEY
+        || *program == ATA_PROGRAM_PUBKEY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_mint_extensions() {
+        let config = SolanaExactConfig::from_json(None).unwrap();
+        assert!(config.is_mint_extension_allowed(ExtensionType::TransferFeeConfig));
+        assert!(!config.is_mint_extension_allowed(ExtensionType::PermanentDelegate));
+
+        let config = SolanaExactConfig::from_json(Some(json!({
+            "allowed_mint_extensions": ["MetadataPointer", "PermanentDelegate"],
+        })))
+        .unwrap();
+        assert!(config.is_mint_extension_allowed(ExtensionType::PermanentDelegate));
+        assert!(!config.is_mint_extension_allowed(ExtensionType::TransferFeeConfig));
+
+        let config = SolanaExactConfig::from_json(Some(json!({
+            "allowed_mint_extensions": ["NoSuchExtension"],
+        })));
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn test_commitment() {
+        let config = SolanaExactConfig::from_json(None).unwrap();
+        assert_eq!(config.commitment(), Finality::Confirmed);
+
+        let config =
+            SolanaExactConfig::from_json(Some(json!({ "commitment": "finalized" }))).unwrap();
+        assert_eq!(config.commitment(), Finality::Finalized);
+
+        for commitment in [json!("submitted"), json!(2)] {
+            let config = SolanaExactConfig::from_json(Some(json!({ "commitment": commitment })));
+            assert!(matches!(
+                config,
+                Err(SolanaExactConfigError::InvalidCommitment(_))
+            ));
+        }
+    }
+}
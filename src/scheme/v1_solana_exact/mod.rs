@@ -1,4 +1,5 @@
 pub mod client;
+pub mod config;
 pub mod types;
 
 use solana_client::rpc_config::RpcSimulateTransactionConfig;
@@ -6,27 +7,47 @@ use solana_client::rpc_response::UiTransactionError;
 use solana_commitment_config::CommitmentConfig;
 use solana_compute_budget_interface::ID as ComputeBudgetInstructionId;
 use solana_message::compiled_instruction::CompiledInstruction;
+use solana_nonce::versions::Versions as NonceVersions;
 use solana_pubkey::{Pubkey, pubkey};
 use solana_signature::Signature;
 use solana_signer::Signer;
 use solana_transaction::TransactionError;
 use solana_transaction::versioned::VersionedTransaction;
+use spl_token_2022_interface::extension::transfer_fee::{TransferFee, TransferFeeConfig};
+use spl_token_2022_interface::extension::{
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use spl_token_2022_interface::state::Mint;
 use std::collections::HashMap;
 use std::error::Error;
 use std::sync::Arc;
 use tracing_core::Level;
 
-use crate::chain::solana::{Address, SolanaChainProvider, SolanaChainProviderError};
-use crate::chain::{ChainId, ChainProvider, ChainProviderOps};
+use crate::chain::solana::{
+    ATA_PROGRAM_PUBKEY, Address, RECENT_BLOCKHASHES_SYSVAR_PUBKEY,
+    SYSTEM_ADVANCE_NONCE_DISCRIMINATOR, SYSTEM_PROGRAM_PUBKEY, SolanaChainProvider,
+    SolanaChainProviderError,
+};
+use crate::chain::{ChainId, ChainProvider, ChainProviderOps, Finality};
 use crate::proto;
 use crate::proto::PaymentVerificationError;
+use crate::scheme::v1_solana_exact::config::{
+    AuxiliaryProgram, AuxiliaryProgramId, SolanaExactConfig,
+};
 use crate::scheme::v1_solana_exact::types::SupportedPaymentKindExtra;
 use crate::scheme::{
     X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError, X402SchemeId,
 };
 use crate::util::Base64Bytes;
 
-pub const ATA_PROGRAM_PUBKEY: Pubkey = pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+/// Wrapped SOL mint, used as the requirements asset to request payment in native SOL.
+pub const NATIVE_MINT_PUBKEY: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+
+/// Decimals of native SOL, in lamports.
+const NATIVE_MINT_DECIMALS: u8 = 9;
+
+/// Discriminator of the System Program `Transfer` instruction (bincode-encoded `u32`).
+const SYSTEM_TRANSFER_DISCRIMINATOR: [u8; 4] = 2u32.to_le_bytes();
 
 pub struct V1SolanaExact;
 
@@ -48,19 +69,22 @@ impl X402SchemeFacilitatorBuilder for V1SolanaExact {
     fn build(
         &self,
         provider: ChainProvider,
-        _config: Option<serde_json::Value>,
+        config: Option<serde_json::Value>,
     ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn Error>> {
         let provider = if let ChainProvider::Solana(provider) = provider {
             provider
         } else {
             return Err("V1SolanaExact::build: provider must be a SolanaChainProvider".into());
         };
-        Ok(Box::new(V1SolanaExactFacilitator { provider }))
+        let config = SolanaExactConfig::from_json(config)
+            .map_err(|e| format!("V1SolanaExact::build: {e}"))?;
+        Ok(Box::new(V1SolanaExactFacilitator { provider, config }))
     }
 }
 
 pub struct V1SolanaExactFacilitator {
     provider: Arc<SolanaChainProvider>,
+    config: SolanaExactConfig,
 }
 
 #[async_trait::async_trait]
@@ -70,43 +94,58 @@ impl X402SchemeFacilitator for V1SolanaExactFacilitator {
         request: &proto::VerifyRequest,
     ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
         let request = types::VerifyRequest::from_proto(request.clone())?;
-        let verification = verify_transfer(&self.provider, &request).await?;
-        Ok(proto::v1::VerifyResponse::valid(verification.payer.to_string()).into())
+        let verification = verify_transfer(&self.provider, &self.config, &request).await?;
+        Ok(
+            proto::v1::VerifyResponse::valid(verification.payer.to_string())
+                .with_memos(verification.memos)
+                .into(),
+        )
     }
 
     async fn settle(
         &self,
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let finality = self
+            .provider
+            .settlement_finality(request.finality()?, self.config.commitment())?;
         let request = types::SettleRequest::from_proto(request.clone())?;
-        let verification = verify_transfer(&self.provider, &request).await?;
+        let verification = verify_transfer(&self.provider, &self.config, &request).await?;
         let payer = verification.payer.to_string();
-        let tx_sig = settle_transaction(&self.provider, verification).await?;
+        let tx_sig = settle_transaction(&self.provider, verification, finality).await?;
         Ok(proto::v1::SettleResponse::Success {
             payer,
             transaction: tx_sig.to_string(),
             network: self.provider.chain_id().to_string(),
+            finality: Some(finality),
         }
         .into())
     }
 
     async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
         let chain_id = self.provider.chain_id();
-        let kinds: Vec<proto::SupportedPaymentKind> = {
-            let mut kinds = Vec::with_capacity(1);
-            let fee_payer = self.provider.fee_payer();
-            let extra =
-                Some(serde_json::to_value(SupportedPaymentKindExtra { fee_payer }).unwrap());
-            let network = chain_id.as_network_name();
-            if let Some(network) = network {
-                kinds.push(proto::SupportedPaymentKind {
-                    x402_version: proto::v1::X402Version1.into(),
-                    scheme: types::ExactScheme.to_string(),
-                    network: network.to_string(),
-                    extra,
-                });
+        // One kind per fee payer, see `SolanaChainProvider::advertised_fee_payers`
+        let kinds: Vec<proto::SupportedPaymentKind> = match chain_id.as_network_name() {
+            Some(network) => {
+                let max_compute_unit_price = self.provider.max_compute_unit_price();
+                self.provider
+                    .advertised_fee_payers()
+                    .into_iter()
+                    .map(|fee_payer| proto::SupportedPaymentKind {
+                        x402_version: proto::v1::X402Version1.into(),
+                        scheme: types::ExactScheme.to_string(),
+                        network: network.to_string(),
+                        extra: Some(
+                            serde_json::to_value(SupportedPaymentKindExtra {
+                                fee_payer,
+                                max_compute_unit_price: Some(max_compute_unit_price.into()),
+                            })
+                            .unwrap(),
+                        ),
+                    })
+                    .collect()
             }
-            kinds
+            None => Vec::new(),
         };
         let signers = {
             let mut signers = HashMap::with_capacity(1);
@@ -167,14 +206,46 @@ impl InstructionInt {
     }
 }
 
+#[derive(Clone)]
 pub struct TransactionInt {
     inner: VersionedTransaction,
+    /// Static account keys, followed by the writable and read-only addresses
+    /// loaded from address lookup tables, if resolved.
+    account_keys: Vec<Pubkey>,
 }
 
 impl TransactionInt {
+    /// Wraps a transaction, resolving account indexes against its static account keys only.
     pub fn new(transaction: VersionedTransaction) -> Self {
-        Self { inner: transaction }
+        let account_keys = transaction.message.static_account_keys().to_vec();
+        Self {
+            inner: transaction,
+            account_keys,
+        }
+    }
+
+    /// Wraps a transaction, loading the addresses it references through address lookup tables,
+    /// so that every account index resolves.
+    pub async fn resolve(
+        transaction: VersionedTransaction,
+        provider: &SolanaChainProvider,
+    ) -> Result<Self, SolanaChainProviderError> {
+        let mut account_keys = transaction.message.static_account_keys().to_vec();
+        if let Some(lookups) = transaction.message.address_table_lookups() {
+            let loaded = provider.load_addresses(lookups).await?;
+            account_keys.extend(loaded.writable);
+            account_keys.extend(loaded.readonly);
+        }
+        Ok(Self {
+            inner: transaction,
+            account_keys,
+        })
+    }
+
+    pub fn account_keys(&self) -> &[Pubkey] {
+        &self.account_keys
     }
+
     pub fn instruction(&self, index: usize) -> Result<InstructionInt, SolanaExactError> {
         let instruction = self
             .inner
@@ -183,7 +254,7 @@ impl TransactionInt {
             .get(index)
             .cloned()
             .ok_or(SolanaExactError::NoInstructionAtIndex(index))?;
-        let account_keys = self.inner.message.static_account_keys().to_vec();
+        let account_keys = self.account_keys.clone();
 
         Ok(InstructionInt {
             index,
@@ -208,7 +279,10 @@ impl TransactionInt {
 
     pub fn sign(self, provider: &SolanaChainProvider) -> Result<Self, SolanaChainProviderError> {
         let tx = provider.sign(self.inner)?;
-        Ok(Self { inner: tx })
+        Ok(Self {
+            inner: tx,
+            account_keys: self.account_keys,
+        })
     }
 
     /// Sign the transaction with any Signer.
@@ -238,17 +312,18 @@ impl TransactionInt {
             tx.signatures.resize(num_required, Signature::default());
         }
         tx.signatures[pos] = signature;
-        Ok(Self { inner: tx })
+        Ok(Self {
+            inner: tx,
+            account_keys: self.account_keys,
+        })
     }
 
-    pub async fn send_and_confirm(
+    pub async fn settle(
         &self,
         provider: &SolanaChainProvider,
-        commitment_config: CommitmentConfig,
+        finality: Finality,
     ) -> Result<Signature, SolanaChainProviderError> {
-        provider
-            .send_and_confirm(&self.inner, commitment_config)
-            .await
+        provider.settle(&self.inner, finality).await
     }
 
     #[allow(dead_code)] // Public for consumption by downstream crates.
@@ -273,14 +348,22 @@ pub struct TransactionSignError(pub String);
 pub struct VerifyTransferResult {
     pub payer: Address,
     pub transaction: VersionedTransaction,
+    /// Contents of the memo instructions of the transaction.
+    pub memos: Vec<String>,
 }
 
+/// A verified transfer instruction: an SPL `TransferChecked`, or a System Program `Transfer`
+/// of native SOL, where `source` and `authority` are both the sender, `destination` the payee,
+/// and `mint` the native mint.
 #[derive(Debug)]
 pub struct TransferCheckedInstruction {
     pub amount: u64,
     pub source: Pubkey,
     pub destination: Pubkey,
     pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub decimals: u8,
+    /// Program executing the transfer: a token program, or the System Program.
     pub token_program: Pubkey,
 }
 
@@ -337,11 +420,10 @@ pub fn verify_compute_price_instruction(
 }
 
 pub fn verify_create_ata_instruction(
-    transaction: &VersionedTransaction,
+    tx: &TransactionInt,
     index: usize,
     transfer_requirement: &TransferRequirement,
 ) -> Result<(), PaymentVerificationError> {
-    let tx = TransactionInt::new(transaction.clone());
     let instruction = tx.instruction(index)?;
     instruction.assert_not_empty()?;
 
@@ -388,6 +470,7 @@ pub fn verify_create_ata_instruction(
 
 pub async fn verify_transfer(
     provider: &SolanaChainProvider,
+    config: &SolanaExactConfig,
     request: &types::VerifyRequest,
 ) -> Result<VerifyTransferResult, PaymentVerificationError> {
     let payload = &request.payment_payload;
@@ -405,19 +488,27 @@ pub async fn verify_transfer(
     if requirements_chain_id != chain_id {
         return Err(PaymentVerificationError::ChainIdMismatch);
     }
+    config.assert_mint(&requirements.asset)?;
+    config.assert_amount(requirements.max_amount_required.inner())?;
     let transaction_b64_string = payload.payload.transaction.clone();
     let transfer_requirement = TransferRequirement {
         pay_to: &requirements.pay_to,
         asset: &requirements.asset,
         amount: requirements.max_amount_required.inner(),
     };
-    let result =
-        verify_transaction(provider, transaction_b64_string, &transfer_requirement).await?;
+    let result = verify_transaction(
+        provider,
+        config,
+        transaction_b64_string,
+        &transfer_requirement,
+    )
+    .await?;
     Ok(result)
 }
 
 pub async fn verify_transaction(
     provider: &SolanaChainProvider,
+    config: &SolanaExactConfig,
     transaction_b64_string: String,
     transfer_requirement: &TransferRequirement<'_>,
 ) -> Result<VerifyTransferResult, PaymentVerificationError> {
@@ -427,46 +518,64 @@ pub async fn verify_transaction(
     let transaction = bincode::deserialize::<VersionedTransaction>(bytes.as_slice())
         .map_err(|e| SolanaExactError::TransactionDecoding(e.to_string()))?;
 
+    // Rule 1: The fee payer must be one of the keys managed by the facilitator
+    let fee_payer = transaction
+        .message
+        .static_account_keys()
+        .first()
+        .ok_or(SolanaExactError::NoAccountAtIndex(0))?;
+    if !provider.is_fee_payer(fee_payer) {
+        return Err(SolanaExactError::UnknownFeePayer(*fee_payer).into());
+    }
+    // Accounts loaded from address lookup tables are resolved so that every account index is checked
+    let tx = TransactionInt::resolve(transaction.clone(), provider).await?;
+
     // perform transaction introspection to validate the transaction structure and details
-    let instructions = transaction.message.instructions();
-    let compute_units = verify_compute_limit_instruction(&transaction, 0)?;
+    let layout = locate_instructions(&transaction, config)?;
+    let compute_units = verify_compute_limit_instruction(&transaction, layout.compute_limit)?;
     if compute_units > provider.max_compute_unit_limit() {
         return Err(SolanaExactError::MaxComputeUnitLimitExceeded.into());
     }
     tracing::debug!(compute_units = compute_units, "Verified compute unit limit");
-    verify_compute_price_instruction(provider.max_compute_unit_price(), &transaction, 1)?;
-    let transfer_instruction = if instructions.len() == 3 {
-        // verify that the transfer instruction is valid
-        // this expects the destination ATA to already exist
-        verify_transfer_instruction(provider, &transaction, 2, transfer_requirement, false).await?
-    } else if instructions.len() == 4 {
-        // verify that the transfer instruction is valid
-        // this expects the destination ATA to be created in the same transaction
-        verify_create_ata_instruction(&transaction, 2, transfer_requirement)?;
-        verify_transfer_instruction(provider, &transaction, 3, transfer_requirement, true).await?
-    } else {
-        return Err(SolanaExactError::InvalidTransactionInstructionsCount.into());
-    };
+    verify_compute_price_instruction(
+        provider.max_compute_unit_price(),
+        &transaction,
+        layout.compute_price,
+    )?;
+    if let Some(advance_nonce) = layout.advance_nonce {
+        verify_advance_nonce_instruction(provider, &tx, advance_nonce).await?;
+    }
+    // if the destination ATA is created in the same transaction, it must be created before the transfer
+    if let Some(create_ata) = layout.create_ata {
+        verify_create_ata_instruction(&tx, create_ata, transfer_requirement)?;
+    }
+    let transfer_instruction = verify_transfer_instruction(
+        provider,
+        config,
+        &tx,
+        layout.transfer,
+        transfer_requirement,
+        layout.create_ata.is_some(),
+    )
+    .await?;
 
     // Rule 2: Fee payer safety check
-    // Verify that the fee payer is not included in any instruction's accounts
+    // Verify that no fee payer is included in any instruction's accounts
     // This single check covers all cases: authority, source, or any other role
-    let fee_payer_pubkey = provider.pubkey();
     for instruction in transaction.message.instructions().iter() {
         for account_idx in instruction.accounts.iter() {
-            let account = transaction
-                .message
-                .static_account_keys()
+            let account = tx
+                .account_keys()
                 .get(*account_idx as usize)
                 .ok_or(SolanaExactError::NoAccountAtIndex(*account_idx))?;
 
-            if *account == fee_payer_pubkey {
+            if provider.is_fee_payer(account) {
                 return Err(SolanaExactError::FeePayerIncludedInInstructionAccounts.into());
             }
         }
     }
 
-    let tx = TransactionInt::new(transaction.clone()).sign(provider)?;
+    let tx = tx.sign(provider)?;
     let cfg = RpcSimulateTransactionConfig {
         sig_verify: false,
         replace_recent_blockhash: false,
@@ -480,7 +589,194 @@ pub async fn verify_transaction(
         .simulate_transaction_with_config(&tx.inner, cfg)
         .await?;
     let payer: Address = transfer_instruction.authority.into();
-    Ok(VerifyTransferResult { payer, transaction })
+    Ok(VerifyTransferResult {
+        payer,
+        transaction,
+        memos: layout.memos,
+    })
+}
+
+/// Positions of the instructions of a payment transaction, located by program id.
+#[derive(Debug, PartialEq, Eq)]
+pub struct InstructionLayout {
+    /// `AdvanceNonceAccount`, present if the transaction uses a durable nonce. Always first.
+    pub advance_nonce: Option<usize>,
+    pub compute_limit: usize,
+    pub compute_price: usize,
+    pub create_ata: Option<usize>,
+    pub transfer: usize,
+    /// Contents of the memo instructions, in transaction order.
+    pub memos: Vec<String>,
+}
+
+/// Locates the compute budget, create-ATA and transfer instructions of the transaction,
+/// and checks every other instruction against the configured auxiliary programs.
+///
+/// Each of the compute limit, compute price and transfer instructions must appear exactly once,
+/// and the create-ATA instruction at most once, before the transfer. A transaction using a durable
+/// nonce starts with an `AdvanceNonceAccount` instruction, as the runtime requires.
+pub fn locate_instructions(
+    transaction: &VersionedTransaction,
+    config: &SolanaExactConfig,
+) -> Result<InstructionLayout, SolanaExactError> {
+    let message = &transaction.message;
+    let account_keys = message.static_account_keys();
+    let mut advance_nonce = None;
+    let mut compute_limit = None;
+    let mut compute_price = None;
+    let mut create_ata = None;
+    let mut transfer = None;
+    let mut memos = Vec::new();
+    let mut auxiliary_counts = vec![0usize; config.auxiliary_programs.len()];
+    for (index, instruction) in message.instructions().iter().enumerate() {
+        let program_id = account_keys
+            .get(instruction.program_id_index as usize)
+            .ok_or(SolanaExactError::NoAccountAtIndex(
+                instruction.program_id_index,
+            ))?;
+        let position = if *program_id == ComputeBudgetInstructionId {
+            match instruction.data.first() {
+                Some(2) => &mut compute_limit,
+                Some(3) => &mut compute_price,
+                _ => return Err(SolanaExactError::UnexpectedInstruction(index)),
+            }
+        } else if *program_id == SYSTEM_PROGRAM_PUBKEY
+            && instruction
+                .data
+                .starts_with(&SYSTEM_ADVANCE_NONCE_DISCRIMINATOR)
+        {
+            if index != 0 {
+                return Err(SolanaExactError::UnexpectedInstruction(index));
+            }
+            &mut advance_nonce
+        } else if *program_id == ATA_PROGRAM_PUBKEY {
+            &mut create_ata
+        } else if *program_id == spl_token::ID
+            || *program_id == spl_token_2022::ID
+            || *program_id == SYSTEM_PROGRAM_PUBKEY
+        {
+            &mut transfer
+        } else {
+            let (auxiliary_index, auxiliary) = config
+                .auxiliary_programs
+                .iter()
+                .enumerate()
+                .find(|(_, auxiliary)| auxiliary.program.matches(program_id))
+                .ok_or(SolanaExactError::UnexpectedInstruction(index))?;
+            auxiliary_counts[auxiliary_index] += 1;
+            if auxiliary_counts[auxiliary_index] > auxiliary.max_instructions {
+                return Err(SolanaExactError::AuxiliaryInstructionLimitExceeded(
+                    *program_id,
+                ));
+            }
+            if let Some(memo) = verify_auxiliary_instruction(transaction, index, auxiliary)? {
+                memos.push(memo);
+            }
+            continue;
+        };
+        if position.replace(index).is_some() {
+            return Err(SolanaExactError::DuplicateInstruction(index));
+        }
+    }
+    let compute_limit = compute_limit.ok_or(SolanaExactError::InvalidComputeLimitInstruction)?;
+    let compute_price = compute_price.ok_or(SolanaExactError::InvalidComputePriceInstruction)?;
+    let transfer = transfer.ok_or(SolanaExactError::MissingTransferInstruction)?;
+    if create_ata.is_some_and(|create_ata| create_ata > transfer) {
+        return Err(SolanaExactError::InvalidCreateATAInstruction);
+    }
+    Ok(InstructionLayout {
+        advance_nonce,
+        compute_limit,
+        compute_price,
+        create_ata,
+        transfer,
+        memos,
+    })
+}
+
+/// Checks the `AdvanceNonceAccount` instruction of a transaction using a durable nonce:
+/// the nonce account must hold the transaction blockhash as its current nonce,
+/// and the nonce authority must be the one recorded in the account and sign the transaction.
+async fn verify_advance_nonce_instruction(
+    provider: &SolanaChainProvider,
+    tx: &TransactionInt,
+    index: usize,
+) -> Result<(), PaymentVerificationError> {
+    let instruction = tx.instruction(index)?;
+    if instruction.data_slice() != SYSTEM_ADVANCE_NONCE_DISCRIMINATOR {
+        return Err(SolanaExactError::InvalidAdvanceNonceInstruction.into());
+    }
+    // Nonce account = 0
+    let nonce_account = instruction.account(0)?;
+    // RecentBlockhashes sysvar = 1
+    if instruction.account(1)? != RECENT_BLOCKHASHES_SYSVAR_PUBKEY {
+        return Err(SolanaExactError::InvalidAdvanceNonceInstruction.into());
+    }
+    // Nonce authority = 2
+    let authority = instruction.account(2)?;
+    let message = &tx.inner.message;
+    let num_signers = message.header().num_required_signatures as usize;
+    let is_authority_signer = message
+        .static_account_keys()
+        .iter()
+        .take(num_signers)
+        .any(|key| *key == authority);
+    if !is_authority_signer {
+        return Err(SolanaExactError::InvalidNonceAuthority.into());
+    }
+
+    let account = provider
+        .get_multiple_accounts(&[nonce_account])
+        .await?
+        .into_iter()
+        .next()
+        .flatten()
+        .filter(|account| account.owner == SYSTEM_PROGRAM_PUBKEY)
+        .ok_or(SolanaExactError::InvalidNonceAccount(nonce_account))?;
+    let versions = bincode::deserialize::<NonceVersions>(&account.data)
+        .map_err(|_| SolanaExactError::InvalidNonceAccount(nonce_account))?;
+    let nonce = versions
+        .verify_recent_blockhash(message.recent_blockhash())
+        .ok_or(SolanaExactError::NonceMismatch)?;
+    if nonce.authority != authority {
+        return Err(SolanaExactError::InvalidNonceAuthority.into());
+    }
+    Ok(())
+}
+
+/// Checks an instruction of an auxiliary program against its rules,
+/// returning the memo if it is a memo instruction.
+fn verify_auxiliary_instruction(
+    transaction: &VersionedTransaction,
+    index: usize,
+    auxiliary: &AuxiliaryProgram,
+) -> Result<Option<String>, SolanaExactError> {
+    let tx = TransactionInt::new(transaction.clone());
+    let instruction = tx.instruction(index)?;
+    let program_id = instruction.program_id();
+    let data = instruction.data_slice();
+    if auxiliary
+        .max_data_length
+        .is_some_and(|max_data_length| data.len() > max_data_length)
+    {
+        return Err(SolanaExactError::InvalidAuxiliaryInstruction(program_id));
+    }
+    let has_writable_account = instruction.instruction.accounts.iter().any(|account| {
+        transaction
+            .message
+            .is_maybe_writable(*account as usize, None)
+    });
+    if has_writable_account && !auxiliary.allow_writable_accounts {
+        return Err(SolanaExactError::InvalidAuxiliaryInstruction(program_id));
+    }
+    match auxiliary.program {
+        AuxiliaryProgramId::Memo => {
+            let memo = String::from_utf8(data.to_vec())
+                .map_err(|_| SolanaExactError::InvalidAuxiliaryInstruction(program_id))?;
+            Ok(Some(memo))
+        }
+        AuxiliaryProgramId::Lighthouse | AuxiliaryProgramId::Program(_) => Ok(None),
+    }
 }
 
 pub struct TransferRequirement<'a> {
@@ -491,12 +787,12 @@ pub struct TransferRequirement<'a> {
 
 pub async fn verify_transfer_instruction(
     provider: &SolanaChainProvider,
-    transaction: &VersionedTransaction,
+    config: &SolanaExactConfig,
+    tx: &TransactionInt,
     instruction_index: usize,
     transfer_requirement: &TransferRequirement<'_>,
     has_dest_ata: bool,
 ) -> Result<TransferCheckedInstruction, PaymentVerificationError> {
-    let tx = TransactionInt::new(transaction.clone());
     let instruction = tx.instruction(instruction_index)?;
     instruction.assert_not_empty()?;
     let program_id = instruction.program_id();
@@ -504,17 +800,16 @@ pub async fn verify_transfer_instruction(
         let token_instruction =
             spl_token::instruction::TokenInstruction::unpack(instruction.data_slice())
                 .map_err(|_| SolanaExactError::InvalidTokenInstruction)?;
-        let amount = match token_instruction {
-            spl_token::instruction::TokenInstruction::TransferChecked {
-                amount,
-                decimals: _,
-            } => amount,
+        let (amount, decimals) = match token_instruction {
+            spl_token::instruction::TokenInstruction::TransferChecked { amount, decimals } => {
+                (amount, decimals)
+            }
             _ => return Err(SolanaExactError::InvalidTokenInstruction.into()),
         };
         // Source = 0
         let source = instruction.account(0)?;
         // Mint = 1
-        let _mint = instruction.account(1)?;
+        let mint = instruction.account(1)?;
         // Destination = 2
         let destination = instruction.account(2)?;
         // Authority = 3
@@ -524,23 +819,24 @@ pub async fn verify_transfer_instruction(
             source,
             destination,
             authority,
+            mint,
+            decimals,
             token_program: spl_token::ID,
         }
     } else if spl_token_2022::ID.eq(&program_id) {
         let token_instruction =
             spl_token_2022::instruction::TokenInstruction::unpack(instruction.data_slice())
                 .map_err(|_| SolanaExactError::InvalidTokenInstruction)?;
-        let amount = match token_instruction {
-            spl_token_2022::instruction::TokenInstruction::TransferChecked {
-                amount,
-                decimals: _,
-            } => amount,
+        let (amount, decimals) = match token_instruction {
+            spl_token_2022::instruction::TokenInstruction::TransferChecked { amount, decimals } => {
+                (amount, decimals)
+            }
             _ => return Err(SolanaExactError::InvalidTokenInstruction.into()),
         };
         // Source = 0
         let source = instruction.account(0)?;
         // Mint = 1
-        let _mint = instruction.account(1)?;
+        let mint = instruction.account(1)?;
         // Destination = 2
         let destination = instruction.account(2)?;
         // Authority = 3
@@ -550,18 +846,58 @@ pub async fn verify_transfer_instruction(
             source,
             destination,
             authority,
+            mint,
+            decimals,
             token_program: spl_token_2022::ID,
         }
+    } else if SYSTEM_PROGRAM_PUBKEY.eq(&program_id) {
+        // Native SOL can only be requested with the native mint as the asset
+        if *transfer_requirement.asset.pubkey() != NATIVE_MINT_PUBKEY {
+            return Err(PaymentVerificationError::AssetMismatch);
+        }
+        let data = instruction.data_slice();
+        if data.len() != 12 || data[..4] != SYSTEM_TRANSFER_DISCRIMINATOR {
+            return Err(SolanaExactError::InvalidSystemTransferInstruction.into());
+        }
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(&data[4..]);
+        let amount = u64::from_le_bytes(buf);
+        // From = 0
+        let from = instruction.account(0)?;
+        // To = 1
+        let to = instruction.account(1)?;
+        TransferCheckedInstruction {
+            amount,
+            source: from,
+            destination: to,
+            authority: from,
+            mint: NATIVE_MINT_PUBKEY,
+            decimals: NATIVE_MINT_DECIMALS,
+            token_program: SYSTEM_PROGRAM_PUBKEY,
+        }
     } else {
         return Err(SolanaExactError::InvalidTokenInstruction.into());
     };
 
     // Verify that the fee payer is not transferring funds (not the authority)
-    let fee_payer_pubkey = provider.pubkey();
-    if transfer_checked_instruction.authority == fee_payer_pubkey {
+    if provider.is_fee_payer(&transfer_checked_instruction.authority) {
         return Err(SolanaExactError::FeePayerTransferringFunds.into());
     }
 
+    if transfer_checked_instruction.token_program == SYSTEM_PROGRAM_PUBKEY {
+        return verify_native_transfer(
+            provider,
+            transfer_checked_instruction,
+            transfer_requirement,
+            has_dest_ata,
+        )
+        .await;
+    }
+
+    if Address::new(transfer_checked_instruction.mint) != *transfer_requirement.asset {
+        return Err(PaymentVerificationError::AssetMismatch);
+    }
+
     let token_program = transfer_checked_instruction.token_program;
     // findAssociatedTokenPda
     let (ata, _) = Pubkey::find_program_address(
@@ -575,9 +911,14 @@ pub async fn verify_transfer_instruction(
     if transfer_checked_instruction.destination != ata {
         return Err(PaymentVerificationError::RecipientMismatch);
     }
-    let accounts = provider
-        .get_multiple_accounts(&[transfer_checked_instruction.source, ata])
-        .await?;
+    // The mint account is fetched along with the sender and payee accounts, unless cached
+    let mint = *transfer_requirement.asset.pubkey();
+    let cached_mint_account = provider.cached_mint(&mint);
+    let mut pubkeys = vec![transfer_checked_instruction.source, ata];
+    if cached_mint_account.is_none() {
+        pubkeys.push(mint);
+    }
+    let accounts = provider.get_multiple_accounts(&pubkeys).await?;
     let is_sender_missing = accounts.first().cloned().is_none_or(|a| a.is_none());
     if is_sender_missing {
         return Err(SolanaExactError::MissingSenderAccount.into());
@@ -586,16 +927,143 @@ pub async fn verify_transfer_instruction(
     if is_receiver_missing && !has_dest_ata {
         return Err(PaymentVerificationError::RecipientMismatch);
     }
+    let mint_account = match cached_mint_account {
+        Some(mint_account) => mint_account,
+        None => {
+            let mint_account = accounts
+                .get(2)
+                .cloned()
+                .flatten()
+                .ok_or(SolanaExactError::MissingMintAccount)?;
+            provider.cache_mint(mint, mint_account.clone());
+            mint_account
+        }
+    };
+    if mint_account.owner != token_program {
+        return Err(PaymentVerificationError::AssetMismatch);
+    }
+    let decimals = mint_decimals(&mint_account.data)?;
+    if transfer_checked_instruction.decimals != decimals {
+        return Err(SolanaExactError::DecimalsMismatch {
+            expected: decimals,
+            actual: transfer_checked_instruction.decimals,
+        }
+        .into());
+    }
     let instruction_amount = transfer_checked_instruction.amount;
+    if token_program == spl_token_2022::ID
+        && let Some(transfer_fee) =
+            assert_token_2022_mint(provider, config, &mint_account.data).await?
+    {
+        assert_transfer_fee_amount(
+            &transfer_fee,
+            instruction_amount,
+            transfer_requirement.amount,
+        )?;
+        return Ok(transfer_checked_instruction);
+    }
     if instruction_amount != transfer_requirement.amount {
         return Err(PaymentVerificationError::InvalidPaymentAmount);
     }
     Ok(transfer_checked_instruction)
 }
 
+/// Reads the decimals of a mint account. Token-2022 mints share the SPL Token base layout.
+fn mint_decimals(mint_data: &[u8]) -> Result<u8, SolanaExactError> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)
+        .map_err(|_| SolanaExactError::InvalidMintAccount)?;
+    Ok(mint.base.decimals)
+}
+
+/// Checks the extensions of a Token-2022 mint, returning its transfer fee for the current epoch, if any.
+async fn assert_token_2022_mint(
+    provider: &SolanaChainProvider,
+    config: &SolanaExactConfig,
+    mint_data: &[u8],
+) -> Result<Option<TransferFee>, PaymentVerificationError> {
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)
+        .map_err(|_| SolanaExactError::InvalidMintAccount)?;
+    let extensions = mint
+        .get_extension_types()
+        .map_err(|_| SolanaExactError::InvalidMintAccount)?;
+    for extension in extensions {
+        match extension {
+            ExtensionType::TransferHook => {
+                return Err(SolanaExactError::MintWithTransferHook.into());
+            }
+            ExtensionType::NonTransferable => {
+                return Err(SolanaExactError::NonTransferableMint.into());
+            }
+            extension if !config.is_mint_extension_allowed(extension) => {
+                return Err(SolanaExactError::MintExtensionNotAllowed(extension).into());
+            }
+            _ => {}
+        }
+    }
+    let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(None);
+    };
+    let epoch = provider.get_epoch().await?;
+    Ok(Some(*transfer_fee_config.get_epoch_fee(epoch)))
+}
+
+/// Checks that a transfer of `amount` withholding `transfer_fee` is the least amount
+/// from which the payee receives `required`: the payer covers the fee, and no more.
+fn assert_transfer_fee_amount(
+    transfer_fee: &TransferFee,
+    amount: u64,
+    required: u64,
+) -> Result<(), SolanaExactError> {
+    let received = |amount: u64| {
+        transfer_fee
+            .calculate_fee(amount)
+            .map(|fee| amount.saturating_sub(fee))
+            .ok_or(SolanaExactError::InvalidMintAccount)
+    };
+    let received_from_amount = received(amount)?;
+    if received_from_amount < required {
+        return Err(SolanaExactError::TransferFeeNotCovered {
+            received: received_from_amount,
+            required,
+        });
+    }
+    if amount > 0 && received(amount - 1)? >= required {
+        return Err(SolanaExactError::TransferFeeExceeded { amount, required });
+    }
+    Ok(())
+}
+
+/// Checks a System Program transfer of native SOL, which goes to `payTo` directly.
+async fn verify_native_transfer(
+    provider: &SolanaChainProvider,
+    transfer_instruction: TransferCheckedInstruction,
+    transfer_requirement: &TransferRequirement<'_>,
+    has_dest_ata: bool,
+) -> Result<TransferCheckedInstruction, PaymentVerificationError> {
+    // There is no token account to create for native SOL
+    if has_dest_ata {
+        return Err(SolanaExactError::InvalidCreateATAInstruction.into());
+    }
+    if transfer_instruction.destination != *transfer_requirement.pay_to.pubkey() {
+        return Err(PaymentVerificationError::RecipientMismatch);
+    }
+    if transfer_instruction.amount != transfer_requirement.amount {
+        return Err(PaymentVerificationError::InvalidPaymentAmount);
+    }
+    let accounts = provider
+        .get_multiple_accounts(&[transfer_instruction.source])
+        .await?;
+    let is_sender_missing = accounts.first().cloned().is_none_or(|a| a.is_none());
+    if is_sender_missing {
+        return Err(SolanaExactError::MissingSenderAccount.into());
+    }
+    Ok(transfer_instruction)
+}
+
 pub async fn settle_transaction(
     provider: &SolanaChainProvider,
     verification: VerifyTransferResult,
+    finality: Finality,
 ) -> Result<Signature, SolanaChainProviderError> {
     let tx = TransactionInt::new(verification.transaction).sign(provider)?;
     // Verify if fully signed
@@ -605,9 +1073,7 @@ pub async fn settle_transaction(
             UiTransactionError::from(TransactionError::SignatureFailure),
         ));
     }
-    let tx_sig = tx
-        .send_and_confirm(provider, CommitmentConfig::confirmed())
-        .await?;
+    let tx_sig = tx.settle(provider, finality).await?;
     Ok(tx_sig)
 }
 
@@ -619,12 +1085,30 @@ pub enum SolanaExactError {
     MaxComputeUnitLimitExceeded,
     #[error("Compute unit price exceeds facilitator maximum")]
     MaxComputeUnitPriceExceeded,
-    #[error("Invalid transaction instructions count")]
-    InvalidTransactionInstructionsCount,
+    #[error("Unexpected instruction at index {0}")]
+    UnexpectedInstruction(usize),
+    #[error("Duplicate instruction at index {0}")]
+    DuplicateInstruction(usize),
+    #[error("Missing transfer instruction")]
+    MissingTransferInstruction,
+    #[error("Invalid AdvanceNonceAccount instruction")]
+    InvalidAdvanceNonceInstruction,
+    #[error("Invalid nonce account {0}")]
+    InvalidNonceAccount(Pubkey),
+    #[error("Transaction blockhash does not match the current durable nonce")]
+    NonceMismatch,
+    #[error("Nonce authority does not sign the transaction")]
+    InvalidNonceAuthority,
+    #[error("Too many instructions of program {0}")]
+    AuxiliaryInstructionLimitExceeded(Pubkey),
+    #[error("Instruction of program {0} violates the configured rules")]
+    InvalidAuxiliaryInstruction(Pubkey),
     #[error("Fee payer included in instruction accounts")]
     FeePayerIncludedInInstructionAccounts,
     #[error("Fee payer found transferring funds")]
     FeePayerTransferringFunds,
+    #[error("Fee payer {0} is not managed by the facilitator")]
+    UnknownFeePayer(Pubkey),
     #[error("Instruction at index {0} not found")]
     NoInstructionAtIndex(usize),
     #[error("No account at index {0}")]
@@ -639,8 +1123,26 @@ pub enum SolanaExactError {
     InvalidCreateATAInstruction,
     #[error("Invalid token instruction")]
     InvalidTokenInstruction,
+    #[error("Invalid System Program transfer instruction")]
+    InvalidSystemTransferInstruction,
     #[error("Missing sender account in transaction")]
     MissingSenderAccount,
+    #[error("Mint account not found")]
+    MissingMintAccount,
+    #[error("Can not decode mint account")]
+    InvalidMintAccount,
+    #[error("Transfer decimals {actual} do not match mint decimals {expected}")]
+    DecimalsMismatch { expected: u8, actual: u8 },
+    #[error("Mints with a transfer hook are not supported")]
+    MintWithTransferHook,
+    #[error("Mint is non-transferable")]
+    NonTransferableMint,
+    #[error("Mint extension {0:?} is not allowed")]
+    MintExtensionNotAllowed(ExtensionType),
+    #[error("Payee receives {received} after transfer fee, {required} required")]
+    TransferFeeNotCovered { received: u64, required: u64 },
+    #[error("Transfer of {amount} covers more than the transfer fee on {required}")]
+    TransferFeeExceeded { amount: u64, required: u64 },
 }
 
 impl From<SolanaExactError> for PaymentVerificationError {
@@ -649,18 +1151,38 @@ impl From<SolanaExactError> for PaymentVerificationError {
             SolanaExactError::TransactionDecoding(_) => {
                 PaymentVerificationError::InvalidFormat(e.to_string())
             }
+            SolanaExactError::TransferFeeNotCovered { .. }
+            | SolanaExactError::TransferFeeExceeded { .. } => {
+                PaymentVerificationError::InvalidPaymentAmount
+            }
             SolanaExactError::InvalidCreateATAInstruction
             | SolanaExactError::MaxComputeUnitLimitExceeded
             | SolanaExactError::MaxComputeUnitPriceExceeded
-            | SolanaExactError::InvalidTransactionInstructionsCount
+            | SolanaExactError::UnexpectedInstruction(_)
+            | SolanaExactError::DuplicateInstruction(_)
+            | SolanaExactError::MissingTransferInstruction
+            | SolanaExactError::InvalidAdvanceNonceInstruction
+            | SolanaExactError::InvalidNonceAccount(_)
+            | SolanaExactError::NonceMismatch
+            | SolanaExactError::InvalidNonceAuthority
+            | SolanaExactError::AuxiliaryInstructionLimitExceeded(_)
+            | SolanaExactError::InvalidAuxiliaryInstruction(_)
             | SolanaExactError::FeePayerIncludedInInstructionAccounts
             | SolanaExactError::NoInstructionAtIndex(_)
             | SolanaExactError::InvalidComputeLimitInstruction
             | SolanaExactError::NoAccountAtIndex(_)
             | SolanaExactError::InvalidTokenInstruction
+            | SolanaExactError::InvalidSystemTransferInstruction
             | SolanaExactError::EmptyInstructionAtIndex(_)
             | SolanaExactError::FeePayerTransferringFunds
+            | SolanaExactError::UnknownFeePayer(_)
             | SolanaExactError::MissingSenderAccount
+            | SolanaExactError::MissingMintAccount
+            | SolanaExactError::InvalidMintAccount
+            | SolanaExactError::DecimalsMismatch { .. }
+            | SolanaExactError::MintWithTransferHook
+            | SolanaExactError::NonTransferableMint
+            | SolanaExactError::MintExtensionNotAllowed(_)
             | SolanaExactError::InvalidComputePriceInstruction => {
                 PaymentVerificationError::TransactionSimulation(e.to_string())
             }
@@ -673,3 +1195,313 @@ impl From<SolanaChainProviderError> for PaymentVerificationError {
         Self::TransactionSimulation(value.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::solana::SolanaChainReference;
+    use crate::networks::KnownNetworkSolana;
+    use crate::scheme::v1_solana_exact::client::{
+        advance_nonce_instruction, system_transfer_instruction,
+    };
+    use crate::scheme::v1_solana_exact::config::MEMO_PROGRAM_PUBKEY;
+    use serde_json::json;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_client::rpc_request::RpcRequest;
+    use solana_compute_budget_interface::ComputeBudgetInstruction;
+    use solana_keypair::Keypair;
+    use solana_message::v0::Message as MessageV0;
+    use solana_message::{Hash, VersionedMessage};
+    use solana_transaction::{AccountMeta, Instruction};
+    use std::collections::HashMap;
+
+    fn transaction(instructions: &[Instruction]) -> VersionedTransaction {
+        let fee_payer = Pubkey::new_unique();
+        let message =
+            MessageV0::try_compile(&fee_payer, instructions, &[], Hash::default()).unwrap();
+        VersionedTransaction {
+            signatures: Vec::new(),
+            message: VersionedMessage::V0(message),
+        }
+    }
+
+    /// A provider whose RPC answers `getMultipleAccounts` once, with `accounts`.
+    async fn provider(fee_payer: Keypair, accounts: serde_json::Value) -> SolanaChainProvider {
+        let mocks = HashMap::from([(
+            RpcRequest::GetMultipleAccounts,
+            json!({ "context": { "slot": 1 }, "value": accounts }),
+        )]);
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        SolanaChainProvider::new(
+            vec![fee_payer],
+            rpc_client,
+            None,
+            SolanaChainReference::solana_devnet(),
+            200_000,
+            1_000,
+        )
+        .await
+        .unwrap()
+    }
+
+    fn account(owner: &Pubkey, data: &[u8]) -> serde_json::Value {
+        json!({
+            "lamports": 1_000_000,
+            "data": [Base64Bytes::encode(data).to_string(), "base64"],
+            "owner": owner.to_string(),
+            "executable": false,
+            "rentEpoch": 0,
+            "space": data.len(),
+        })
+    }
+
+    fn system_account() -> serde_json::Value {
+        account(&SYSTEM_PROGRAM_PUBKEY, &[])
+    }
+
+    /// An initialized SPL Token mint account of `decimals`, owned by `owner`.
+    fn mint_account(owner: &Pubkey, decimals: u8) -> serde_json::Value {
+        let mut data = [0u8; 82];
+        data[44] = decimals;
+        data[45] = 1;
+        account(owner, &data)
+    }
+
+    #[test]
+    fn test_transfer_fee_amount() {
+        // 1% fee, capped at 5
+        let transfer_fee = TransferFee {
+            transfer_fee_basis_points: 100.into(),
+            maximum_fee: 5.into(),
+            ..Default::default()
+        };
+        // The fee on 101 is 2 (rounded up), so 102 is the least amount netting 100
+        assert!(assert_transfer_fee_amount(&transfer_fee, 102, 100).is_ok());
+        assert!(matches!(
+            assert_transfer_fee_amount(&transfer_fee, 101, 100),
+            Err(SolanaExactError::TransferFeeNotCovered { received: 99, .. })
+        ));
+        assert!(matches!(
+            assert_transfer_fee_amount(&transfer_fee, 200, 100),
+            Err(SolanaExactError::TransferFeeExceeded { .. })
+        ));
+        // Past the cap, the fee is flat
+        assert!(assert_transfer_fee_amount(&transfer_fee, 10_005, 10_000).is_ok());
+        assert!(assert_transfer_fee_amount(&transfer_fee, 10_006, 10_000).is_err());
+        assert!(assert_transfer_fee_amount(&TransferFee::default(), 100, 100).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_token_transfer_mint() {
+        let config = SolanaExactConfig::from_json(None).unwrap();
+        let sender = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let asset = Address::new(mint);
+        let pay_to = Address::new(Pubkey::new_unique());
+        let (ata, _) = Pubkey::find_program_address(
+            &[pay_to.as_ref(), spl_token::ID.as_ref(), mint.as_ref()],
+            &ATA_PROGRAM_PUBKEY,
+        );
+        let requirement = TransferRequirement {
+            asset: &asset,
+            pay_to: &pay_to,
+            amount: 1_000,
+        };
+        let verify = async |mint_account: serde_json::Value, transfer_mint: &Pubkey, decimals| {
+            let token_account = account(&spl_token::ID, &[]);
+            let accounts = json!([token_account, token_account, mint_account]);
+            let provider = provider(Keypair::new(), accounts).await;
+            let transfer = spl_token::instruction::transfer_checked(
+                &spl_token::ID,
+                &sender,
+                transfer_mint,
+                &ata,
+                &sender,
+                &[],
+                1_000,
+                decimals,
+            )
+            .unwrap();
+            let tx = TransactionInt::new(transaction(&[transfer]));
+            let result =
+                verify_transfer_instruction(&provider, &config, &tx, 0, &requirement, false).await;
+            (result, provider)
+        };
+
+        let (result, provider) = verify(mint_account(&spl_token::ID, 6), &mint, 6).await;
+        assert_eq!(result.unwrap().decimals, 6);
+        assert!(provider.cached_mint(&mint).is_some());
+
+        let (result, _) = verify(mint_account(&spl_token::ID, 6), &mint, 9).await;
+        assert!(matches!(
+            result,
+            Err(PaymentVerificationError::TransactionSimulation(message))
+                if message == SolanaExactError::DecimalsMismatch { expected: 6, actual: 9 }.to_string()
+        ));
+        // The mint is not owned by the program executing the transfer
+        let (result, _) = verify(mint_account(&spl_token_2022::ID, 6), &mint, 6).await;
+        assert!(matches!(
+            result,
+            Err(PaymentVerificationError::AssetMismatch)
+        ));
+        // The transfer is not of the required mint
+        let (result, _) = verify(mint_account(&spl_token::ID, 6), &Pubkey::new_unique(), 6).await;
+        assert!(matches!(
+            result,
+            Err(PaymentVerificationError::AssetMismatch)
+        ));
+        let (result, _) = verify(account(&spl_token::ID, &[1, 2, 3]), &mint, 6).await;
+        assert!(matches!(
+            result,
+            Err(PaymentVerificationError::TransactionSimulation(message))
+                if message == SolanaExactError::InvalidMintAccount.to_string()
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_verify_native_transfer() {
+        let config = SolanaExactConfig::from_json(None).unwrap();
+        let fee_payer = Keypair::new();
+        let fee_payer_pubkey = fee_payer.pubkey();
+        let provider = provider(fee_payer, json!([system_account()])).await;
+        let sender = Pubkey::new_unique();
+        let asset = Address::new(NATIVE_MINT_PUBKEY);
+        let pay_to = Address::new(Pubkey::new_unique());
+        let requirement = TransferRequirement {
+            asset: &asset,
+            pay_to: &pay_to,
+            amount: 1_000,
+        };
+        let verify = |from: &Pubkey, to: &Pubkey, amount: u64, has_dest_ata: bool| {
+            let tx = TransactionInt::new(transaction(&[system_transfer_instruction(
+                from, to, amount,
+            )]));
+            let provider = &provider;
+            let config = &config;
+            let requirement = &requirement;
+            async move {
+                verify_transfer_instruction(provider, config, &tx, 0, requirement, has_dest_ata)
+                    .await
+            }
+        };
+
+        let transfer = verify(&sender, pay_to.pubkey(), 1_000, false)
+            .await
+            .unwrap();
+        assert_eq!(transfer.authority, sender);
+        assert_eq!(transfer.token_program, SYSTEM_PROGRAM_PUBKEY);
+
+        assert!(matches!(
+            verify(&sender, &Pubkey::new_unique(), 1_000, false).await,
+            Err(PaymentVerificationError::RecipientMismatch)
+        ));
+        assert!(matches!(
+            verify(&sender, pay_to.pubkey(), 999, false).await,
+            Err(PaymentVerificationError::InvalidPaymentAmount)
+        ));
+        // There is no token account to create for native SOL
+        assert!(matches!(
+            verify(&sender, pay_to.pubkey(), 1_000, true).await,
+            Err(PaymentVerificationError::TransactionSimulation(message))
+                if message == SolanaExactError::InvalidCreateATAInstruction.to_string()
+        ));
+        assert!(matches!(
+            verify(&fee_payer_pubkey, pay_to.pubkey(), 1_000, false).await,
+            Err(PaymentVerificationError::TransactionSimulation(message))
+                if message == SolanaExactError::FeePayerTransferringFunds.to_string()
+        ));
+    }
+
+    #[test]
+    fn test_locate_instructions() {
+        let sender = Pubkey::new_unique();
+        let memo = Instruction::new_with_bytes(MEMO_PROGRAM_PUBKEY, b"order-42", Vec::new());
+        let tx = transaction(&[
+            memo.clone(),
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1),
+            system_transfer_instruction(&sender, &Pubkey::new_unique(), 1_000),
+        ]);
+
+        let config = SolanaExactConfig::from_json(None).unwrap();
+        assert!(matches!(
+            locate_instructions(&tx, &config),
+            Err(SolanaExactError::UnexpectedInstruction(0))
+        ));
+
+        let config = SolanaExactConfig::from_json(Some(json!({
+            "auxiliary_programs": [{ "program": "memo", "max_data_length": 32 }],
+        })))
+        .unwrap();
+        let layout = locate_instructions(&tx, &config).unwrap();
+        assert_eq!(
+            layout,
+            InstructionLayout {
+                advance_nonce: None,
+                compute_limit: 1,
+                compute_price: 2,
+                create_ata: None,
+                transfer: 3,
+                memos: vec!["order-42".to_string()],
+            }
+        );
+
+        let writable_memo = Instruction::new_with_bytes(
+            MEMO_PROGRAM_PUBKEY,
+            b"order-42",
+            vec![AccountMeta::new(sender, true)],
+        );
+        let tx = transaction(&[
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1),
+            system_transfer_instruction(&sender, &Pubkey::new_unique(), 1_000),
+            writable_memo,
+        ]);
+        assert!(matches!(
+            locate_instructions(&tx, &config),
+            Err(SolanaExactError::InvalidAuxiliaryInstruction(_))
+        ));
+
+        let tx = transaction(&[
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1),
+            memo.clone(),
+            memo,
+            system_transfer_instruction(&sender, &Pubkey::new_unique(), 1_000),
+        ]);
+        assert!(matches!(
+            locate_instructions(&tx, &config),
+            Err(SolanaExactError::AuxiliaryInstructionLimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn test_locate_advance_nonce_instruction() {
+        let sender = Pubkey::new_unique();
+        let advance_nonce = advance_nonce_instruction(&Pubkey::new_unique(), &sender);
+        let transfer = system_transfer_instruction(&sender, &Pubkey::new_unique(), 1_000);
+        let config = SolanaExactConfig::from_json(None).unwrap();
+
+        let tx = transaction(&[
+            advance_nonce.clone(),
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            ComputeBudgetInstruction::set_compute_unit_price(1),
+            transfer.clone(),
+        ]);
+        let layout = locate_instructions(&tx, &config).unwrap();
+        assert_eq!(layout.advance_nonce, Some(0));
+        assert_eq!(layout.transfer, 3);
+
+        // The runtime only honors a durable nonce advanced by the first instruction
+        let tx = transaction(&[
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+            advance_nonce,
+            ComputeBudgetInstruction::set_compute_unit_price(1),
+            transfer,
+        ]);
+        assert!(matches!(
+            locate_instructions(&tx, &config),
+            Err(SolanaExactError::UnexpectedInstruction(1))
+        ));
+    }
+}
@@ -22,4 +22,7 @@ pub type PaymentRequirements =
 #[serde(rename_all = "camelCase")]
 pub struct SupportedPaymentKindExtra {
     pub fee_payer: Address,
+    /// Highest compute unit price, in micro-lamports, the facilitator currently accepts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_compute_unit_price: Option<U64String>,
 }
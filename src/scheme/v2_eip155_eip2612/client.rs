@@ -0,0 +1,63 @@
+use alloy_primitives::{Address, U256};
+use alloy_sol_types::eip712_domain;
+
+use crate::proto::client::X402Error;
+use crate::scheme::v1_eip155_exact::client::SignerLike;
+use crate::scheme::v2_eip155_eip2612::permit_signing_hash;
+use crate::scheme::v2_eip155_eip2612::types::{Eip2612EvmPayload, Eip2612EvmPermit};
+use crate::timestamp::UnixTimestamp;
+
+/// EIP-712 signing parameters for an EIP-2612 permit.
+///
+/// Unlike ERC-3009 nonces, EIP-2612 nonces are sequential, so `nonce` must be read
+/// from the token (`nonces(owner)`) right before signing.
+#[derive(Debug, Clone)]
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub struct Eip2612SigningParams {
+    /// The EIP-155 chain ID (numeric)
+    pub chain_id: u64,
+    /// The token contract address (verifying contract for EIP-712)
+    pub asset_address: Address,
+    /// EIP-712 domain name of the token
+    pub name: String,
+    /// EIP-712 domain version of the token
+    pub version: String,
+    /// The facilitator signer to approve, from the requirements `extra.spender`
+    pub spender: Address,
+    /// The amount to approve
+    pub amount: U256,
+    /// Current permit nonce of the signer on the token
+    pub nonce: U256,
+    /// Maximum timeout in seconds for the permit deadline
+    pub max_timeout_seconds: u64,
+}
+
+/// Signs an EIP-2612 permit approving `params.spender` for `params.amount`.
+#[allow(dead_code)] // Public for consumption by downstream crates.
+pub async fn sign_eip2612_permit<S: SignerLike + Sync>(
+    signer: &S,
+    params: &Eip2612SigningParams,
+) -> Result<Eip2612EvmPayload, X402Error> {
+    let domain = eip712_domain! {
+        name: params.name.clone(),
+        version: params.version.clone(),
+        chain_id: params.chain_id,
+        verifying_contract: params.asset_address,
+    };
+    let permit = Eip2612EvmPermit {
+        owner: signer.address(),
+        spender: params.spender,
+        value: params.amount,
+        nonce: params.nonce,
+        deadline: UnixTimestamp::now() + params.max_timeout_seconds,
+    };
+    let hash = permit_signing_hash(&permit, &domain);
+    let signature = signer
+        .sign_hash(&hash)
+        .await
+        .map_err(|e| X402Error::SigningError(format!("{e:?}")))?;
+    Ok(Eip2612EvmPayload {
+        signature: signature.as_bytes().into(),
+        permit,
+    })
+}
@@ -0,0 +1,552 @@
+//! Exact payments in tokens supporting EIP-2612 `permit` but not ERC-3009.
+//!
+//! The payer signs an EIP-2612 permit approving a facilitator signer (the spender,
+//! advertised as `extra.spender` in `/supported`) for exactly the required amount, with
+//! the current `nonces(owner)` of the token. The facilitator then calls `permit` and
+//! `transferFrom(owner, payTo, amount)` from that signer. Verification simulates both
+//! calls in sequence with `eth_simulateV1`, so the RPC endpoints of the chain must support it.
+//!
+//! Every settlement submits its permit, which consumes the nonce: a payload can only
+//! be settled once, and never on an allowance left by an earlier permit. A signed permit is
+//! public once in the mempool, so anyone can submit it first: the facilitator permit then
+//! reverts, and settlement carries on with `transferFrom` if the allowance is in place.
+//!
+//! The two calls are NOT atomic. They are sent as consecutive transactions rather than
+//! batched through Multicall3 as first requested, because `transferFrom` would then come
+//! from Multicall3: the permit would have to approve Multicall3, and anyone replaying it
+//! from the mempool could batch their own `transferFrom` and drain the allowance. If
+//! `transferFrom` fails once the permit is mined, the allowance is left to the facilitator
+//! signer only, and the payload can not be settled again.
+//!
+//! Unlike ERC-3009 or Permit2 witness transfers, the permit does not bind the recipient:
+//! the payer trusts the facilitator to transfer to `payTo`.
+
+pub mod client;
+pub mod types;
+
+use alloy_primitives::{Address, B256, Signature, TxHash, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::TransactionRequest;
+use alloy_rpc_types_eth::simulate::{SimBlock, SimulatePayload};
+use alloy_sol_types::{Eip712Domain, SolCall, SolStruct, sol};
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use tracing::{Instrument, instrument};
+use tracing_core::Level;
+
+use crate::chain::eip155::{Eip155ChainProvider, Eip155MetaTransactionProvider, MetaTransaction};
+use crate::chain::{ChainId, ChainProvider, ChainProviderOps};
+use crate::proto;
+use crate::proto::PaymentVerificationError;
+use crate::proto::v2;
+use crate::scheme::v1_eip155_exact::{
+    Eip155ExactConfig, Eip155ExactError, IEIP3009, assert_domain, assert_enough_balance,
+    assert_time,
+};
+use crate::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError, X402SchemeId,
+};
+use crate::timestamp::UnixTimestamp;
+
+#[allow(unused)]
+pub use types::*;
+
+sol! {
+    #[allow(missing_docs)]
+    #[allow(clippy::too_many_arguments)]
+    #[derive(Debug)]
+    #[sol(rpc)]
+    interface IERC2612 {
+        function permit(
+            address owner,
+            address spender,
+            uint256 value,
+            uint256 deadline,
+            uint8 v,
+            bytes32 r,
+            bytes32 s
+        ) external;
+        function transferFrom(address from, address to, uint256 value) external returns (bool);
+        function nonces(address owner) external view returns (uint256);
+        function allowance(address owner, address spender) external view returns (uint256);
+    }
+}
+
+sol! {
+    /// EIP-712 message of an EIP-2612 permit.
+    struct Permit {
+        address owner;
+        address spender;
+        uint256 value;
+        uint256 nonce;
+        uint256 deadline;
+    }
+}
+
+pub struct V2Eip155Eip2612;
+
+impl X402SchemeId for V2Eip155Eip2612 {
+    fn namespace(&self) -> &str {
+        "eip155"
+    }
+
+    fn scheme(&self) -> &str {
+        types::Eip2612Scheme.as_ref()
+    }
+}
+
+impl X402SchemeFacilitatorBuilder for V2Eip155Eip2612 {
+    fn build(
+        &self,
+        provider: ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn Error>> {
+        let provider = if let ChainProvider::Eip155(provider) = provider {
+            provider
+        } else {
+            return Err("V2Eip155Eip2612::build: provider must be an Eip155ChainProvider".into());
+        };
+        let config = Eip155ExactConfig::from_json(config)
+            .map_err(|e| format!("V2Eip155Eip2612::build: {e}"))?;
+        Ok(Box::new(V2Eip155Eip2612Facilitator { provider, config }))
+    }
+}
+
+pub struct V2Eip155Eip2612Facilitator {
+    provider: Arc<Eip155ChainProvider>,
+    config: Eip155ExactConfig,
+}
+
+#[async_trait::async_trait]
+impl X402SchemeFacilitator for V2Eip155Eip2612Facilitator {
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request.clone())?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let payment =
+            assert_valid_payment(&self.provider, &self.config, payload, requirements).await?;
+        verify_payment(self.provider.inner(), &payment).await?;
+        Ok(v2::VerifyResponse::valid(payment.permit.owner.to_string()).into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let (finality, confirmations) = self
+            .provider
+            .settlement_finality(request.finality()?, self.config.confirmations)?;
+        let request = types::SettleRequest::from_proto(request.clone())?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let payment =
+            assert_valid_payment(&self.provider, &self.config, payload, requirements).await?;
+
+        let tx_hash = settle_payment(self.provider.as_ref(), &payment, confirmations).await?;
+        self.provider
+            .wait_for_finality(tx_hash, finality)
+            .await
+            .map_err(Eip155ExactError::from)?;
+
+        Ok(v2::SettleResponse::Success {
+            payer: payment.permit.owner.to_string(),
+            transaction: tx_hash.to_string(),
+            network: payload.accepted.network.to_string(),
+            finality: Some(finality),
+        }
+        .into())
+    }
+
+    /// Advertises the next facilitator signer as the permit spender to approve.
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        let chain_id = self.provider.chain_id();
+        let kinds = {
+            let spender = self.provider.next_signer_address().into();
+            let extra = PaymentRequirementsExtra {
+                spender,
+                name: None,
+                version: None,
+            };
+            vec![proto::SupportedPaymentKind {
+                x402_version: proto::X402Version::v2().into(),
+                scheme: types::Eip2612Scheme.to_string(),
+                network: chain_id.clone().into(),
+                extra: Some(serde_json::to_value(extra).unwrap()),
+            }]
+        };
+        let signers = {
+            let mut signers = HashMap::with_capacity(1);
+            signers.insert(chain_id, self.provider.signer_addresses());
+            signers
+        };
+        Ok(proto::SupportedResponse {
+            kinds,
+            extensions: Vec::new(),
+            signers,
+        })
+    }
+}
+
+/// A signed EIP-2612 permit that passed the static and balance checks.
+#[derive(Debug)]
+pub struct Eip2612EvmPayment {
+    pub permit: Eip2612EvmPermit,
+    pub signature: Signature,
+    pub token: Address,
+    pub pay_to: Address,
+    /// Amount to transfer, as required by the payment requirements.
+    pub amount: U256,
+}
+
+impl Eip2612EvmPayment {
+    fn permit_call(&self) -> IERC2612::permitCall {
+        IERC2612::permitCall {
+            owner: self.permit.owner,
+            spender: self.permit.spender,
+            value: self.permit.value,
+            deadline: U256::from(self.permit.deadline.as_secs()),
+            v: 27 + u8::from(self.signature.v()),
+            r: self.signature.r().into(),
+            s: self.signature.s().into(),
+        }
+    }
+
+    fn transfer_from_call(&self) -> IERC2612::transferFromCall {
+        IERC2612::transferFromCall {
+            from: self.permit.owner,
+            to: self.pay_to,
+            value: self.amount,
+        }
+    }
+}
+
+/// Runs all preconditions needed for a successful payment:
+/// - Valid scheme, network and token.
+/// - Spender is one of the facilitator signers.
+/// - Permit deadline not reached.
+/// - Permit signed by the owner, with the token EIP-712 domain.
+/// - Permitted value is exactly the required amount.
+/// - Permit nonce is the current nonce of the owner, so the permit has not been used.
+/// - Sufficient on-chain balance.
+#[instrument(skip_all, err)]
+async fn assert_valid_payment(
+    provider: &Eip155ChainProvider,
+    config: &Eip155ExactConfig,
+    payload: &types::PaymentPayload,
+    requirements: &types::PaymentRequirements,
+) -> Result<Eip2612EvmPayment, Eip155ExactError> {
+    let accepted = &payload.accepted;
+    if accepted != requirements {
+        return Err(PaymentVerificationError::AcceptedRequirementsMismatch.into());
+    }
+    let chain_id: ChainId = provider.chain().into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+    let permit = payload.payload.permit;
+    if !provider.is_signer(&permit.spender) {
+        return Err(PaymentVerificationError::InvalidFormat(
+            "Permit spender is not a facilitator signer".to_string(),
+        )
+        .into());
+    }
+    assert_time(
+        UnixTimestamp::from_secs(0),
+        permit.deadline,
+        config.clock_skew_secs,
+    )?;
+    let asset_address = accepted.asset.into();
+    config.assert_asset(&asset_address)?;
+    let amount_required = accepted.amount.into();
+    config.assert_amount(&amount_required)?;
+
+    let signature = Signature::try_from(payload.payload.signature.as_ref())
+        .map_err(|e| PaymentVerificationError::InvalidSignature(e.to_string()))?;
+    let contract = IEIP3009::new(asset_address, provider.inner());
+    let domain_extra = accepted.extra.as_ref().and_then(|extra| {
+        Some(crate::scheme::v1_eip155_exact::PaymentRequirementsExtra {
+            name: extra.name.clone()?,
+            version: extra.version.clone()?,
+            authorization_method: Default::default(),
+        })
+    });
+    let domain = assert_domain(
+        provider.chain(),
+        &contract,
+        &asset_address,
+        &domain_extra,
+        provider.token_metadata(),
+    )
+    .await?;
+    let signer = signature
+        .recover_address_from_prehash(&permit_signing_hash(&permit, &domain))
+        .map_err(|e| PaymentVerificationError::InvalidSignature(e.to_string()))?;
+    if signer != permit.owner {
+        return Err(PaymentVerificationError::InvalidSignature(
+            "Permit is not signed by its owner".to_string(),
+        )
+        .into());
+    }
+
+    if permit.value != amount_required {
+        return Err(PaymentVerificationError::InvalidPaymentAmount.into());
+    }
+    assert_current_nonce(provider.inner(), asset_address, &permit).await?;
+    assert_enough_balance(&contract, &permit.owner, amount_required).await?;
+
+    Ok(Eip2612EvmPayment {
+        permit,
+        signature,
+        token: asset_address,
+        pay_to: accepted.pay_to.into(),
+        amount: amount_required,
+    })
+}
+
+/// Checks that the permit uses the current nonce of its owner, i.e. it has not been submitted yet.
+async fn assert_current_nonce<P: Provider>(
+    provider: P,
+    token: Address,
+    permit: &Eip2612EvmPermit,
+) -> Result<(), Eip155ExactError> {
+    let nonce = IERC2612::new(token, provider)
+        .nonces(permit.owner)
+        .call()
+        .into_future()
+        .instrument(tracing::info_span!(
+            "fetch_permit_nonce",
+            token_contract = %token,
+            owner = %permit.owner,
+            otel.kind = "client"
+        ))
+        .await?;
+    if nonce != permit.nonce {
+        return Err(PaymentVerificationError::InvalidSignature(format!(
+            "Permit nonce {} is not the current nonce {nonce} of the owner",
+            permit.nonce
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Simulates `permit` followed by `transferFrom`, both sent from the spender.
+#[instrument(skip_all, err, fields(
+    owner = %payment.permit.owner,
+    spender = %payment.permit.spender,
+    token_contract = %payment.token
+))]
+pub async fn verify_payment<P: Provider>(
+    provider: P,
+    payment: &Eip2612EvmPayment,
+) -> Result<(), Eip155ExactError> {
+    let calls = [
+        payment.permit_call().abi_encode(),
+        payment.transfer_from_call().abi_encode(),
+    ];
+    let calls = calls
+        .into_iter()
+        .map(|calldata| {
+            TransactionRequest::default()
+                .from(payment.permit.spender)
+                .to(payment.token)
+                .input(calldata.into())
+        })
+        .collect();
+    let payload = SimulatePayload {
+        block_state_calls: vec![SimBlock {
+            block_overrides: None,
+            state_overrides: None,
+            calls,
+        }],
+        trace_transfers: false,
+        validation: false,
+        return_full_transactions: false,
+    };
+    let blocks = provider
+        .simulate(&payload)
+        .into_future()
+        .instrument(tracing::info_span!(
+            "simulate_permit_transferFrom",
+            otel.kind = "client"
+        ))
+        .await?;
+    let results = blocks.into_iter().flat_map(|block| block.calls);
+    for result in results {
+        if !result.status {
+            let reason = result
+                .error
+                .map(|error| error.message)
+                .unwrap_or_else(|| "Simulated call reverted".to_string());
+            return Err(PaymentVerificationError::TransactionSimulation(reason).into());
+        }
+    }
+    Ok(())
+}
+
+/// Submits `permit` and then `transferFrom`, both from the spender,
+/// returning the hash of the `transferFrom` transaction.
+pub async fn settle_payment<P, E>(
+    provider: P,
+    payment: &Eip2612EvmPayment,
+    confirmations: u64,
+) -> Result<TxHash, Eip155ExactError>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let permit = &payment.permit;
+    let outcome = Eip155MetaTransactionProvider::send_transaction(
+        &provider,
+        MetaTransaction {
+            to: payment.token,
+            calldata: payment.permit_call().abi_encode().into(),
+            // transferFrom relies on the allowance, so the permit is always awaited
+            confirmations: confirmations.max(1),
+            from: Some(permit.spender),
+        },
+    )
+    .instrument(tracing::info_span!("call_permit",
+        owner = %permit.owner,
+        spender = %permit.spender,
+        value = %permit.value,
+        deadline = %permit.deadline,
+        token_contract = %payment.token,
+        otel.kind = "client",
+    ))
+    .await;
+    match outcome {
+        Ok(receipt) if receipt.status() => {}
+        outcome => {
+            // Anyone can submit the permit first, consuming the nonce but granting the allowance
+            if !has_allowance(provider.inner(), payment).await? {
+                return match outcome {
+                    Ok(receipt) => {
+                        tracing::event!(
+                            Level::WARN,
+                            status = "failed",
+                            tx = %receipt.transaction_hash,
+                            "permit failed"
+                        );
+                        Err(Eip155ExactError::TransactionReverted(
+                            receipt.transaction_hash,
+                        ))
+                    }
+                    Err(error) => Err(error.into()),
+                };
+            }
+            tracing::event!(
+                Level::INFO,
+                "permit failed, proceeding on the allowance already granted"
+            );
+        }
+    }
+    let receipt = Eip155MetaTransactionProvider::send_transaction(
+        &provider,
+        MetaTransaction {
+            to: payment.token,
+            calldata: payment.transfer_from_call().abi_encode().into(),
+            confirmations,
+            from: Some(permit.spender),
+        },
+    )
+    .instrument(tracing::info_span!("call_transferFrom",
+        from = %permit.owner,
+        to = %payment.pay_to,
+        value = %payment.amount,
+        token_contract = %payment.token,
+        otel.kind = "client",
+    ))
+    .await?;
+    if receipt.status() {
+        tracing::event!(Level::INFO,
+            status = "ok",
+            tx = %receipt.transaction_hash,
+            "transferFrom succeeded"
+        );
+        Ok(receipt.transaction_hash)
+    } else {
+        tracing::event!(
+            Level::WARN,
+            status = "failed",
+            tx = %receipt.transaction_hash,
+            "transferFrom failed"
+        );
+        Err(Eip155ExactError::TransactionReverted(
+            receipt.transaction_hash,
+        ))
+    }
+}
+
+/// Checks that the spender is allowed to transfer the payment amount from the owner.
+async fn has_allowance<P: Provider>(
+    provider: P,
+    payment: &Eip2612EvmPayment,
+) -> Result<bool, Eip155ExactError> {
+    let permit = &payment.permit;
+    let allowance = IERC2612::new(payment.token, provider)
+        .allowance(permit.owner, permit.spender)
+        .call()
+        .into_future()
+        .instrument(tracing::info_span!(
+            "fetch_allowance",
+            token_contract = %payment.token,
+            owner = %permit.owner,
+            spender = %permit.spender,
+            otel.kind = "client"
+        ))
+        .await?;
+    Ok(allowance >= payment.amount)
+}
+
+/// Hash of the message a payer signs to authorize `permit` on a token with the given EIP-712 `domain`.
+pub fn permit_signing_hash(permit: &Eip2612EvmPermit, domain: &Eip712Domain) -> B256 {
+    let message = Permit {
+        owner: permit.owner,
+        spender: permit.spender,
+        value: permit.value,
+        nonce: permit.nonce,
+        deadline: U256::from(permit.deadline.as_secs()),
+    };
+    message.eip712_signing_hash(domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheme::v2_eip155_eip2612::client::{Eip2612SigningParams, sign_eip2612_permit};
+    use alloy_primitives::address;
+    use alloy_signer_local::PrivateKeySigner;
+    use alloy_sol_types::eip712_domain;
+
+    #[tokio::test]
+    async fn test_signed_permit_recovers_owner() {
+        let signer = PrivateKeySigner::random();
+        let params = Eip2612SigningParams {
+            chain_id: 8453,
+            asset_address: address!("0x50c5725949A6F0c72E6C4a641F24049A917DB0Cb"),
+            name: "Dai Stablecoin".to_string(),
+            version: "1".to_string(),
+            spender: address!("0x1111111111111111111111111111111111111111"),
+            amount: U256::from(1_000_000),
+            nonce: U256::from(3),
+            max_timeout_seconds: 300,
+        };
+        let payload = sign_eip2612_permit(&signer, &params).await.unwrap();
+        let domain = eip712_domain! {
+            name: params.name.clone(),
+            version: params.version.clone(),
+            chain_id: params.chain_id,
+            verifying_contract: params.asset_address,
+        };
+        let signature = Signature::try_from(payload.signature.as_ref()).unwrap();
+        let hash = permit_signing_hash(&payload.permit, &domain);
+        let recovered = signature.recover_address_from_prehash(&hash).unwrap();
+        assert_eq!(recovered, signer.address());
+        assert_eq!(payload.permit.owner, signer.address());
+    }
+}
@@ -0,0 +1,55 @@
+use alloy_primitives::{Address, Bytes, U256};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+use crate::chain::eip155::{ChecksummedAddress, TokenAmount};
+use crate::lit_str;
+use crate::proto::v2;
+use crate::timestamp::UnixTimestamp;
+
+lit_str!(Eip2612Scheme, "eip2612");
+
+pub type VerifyRequest = v2::VerifyRequest<PaymentPayload, PaymentRequirements>;
+pub type SettleRequest = VerifyRequest;
+pub type PaymentPayload = v2::PaymentPayload<PaymentRequirements, Eip2612EvmPayload>;
+pub type PaymentRequirements = v2::PaymentRequirements<
+    Eip2612Scheme,
+    TokenAmount,
+    ChecksummedAddress,
+    PaymentRequirementsExtra,
+>;
+
+/// Signed EIP-2612 permit approving the facilitator signer to transfer the payment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip2612EvmPayload {
+    /// 65-byte EOA signature over the permit.
+    pub signature: Bytes,
+    pub permit: Eip2612EvmPermit,
+}
+
+/// Fields of the EIP-2612 `Permit` message.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip2612EvmPermit {
+    pub owner: Address,
+    pub spender: Address,
+    pub value: U256,
+    /// Current `nonces(owner)` of the token.
+    pub nonce: U256,
+    pub deadline: UnixTimestamp,
+}
+
+/// Extra data of `eip2612` payment requirements.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequirementsExtra {
+    /// Facilitator signer the payer must approve as the permit spender, as advertised in `/supported`.
+    pub spender: ChecksummedAddress,
+    /// EIP-712 domain name of the token (optional, fetched from the token if absent).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// EIP-712 domain version of the token (optional, fetched from the token if absent).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
@@ -0,0 +1,239 @@
+//! Minimal JSON-RPC client for an ERC-4337 bundler.
+
+use alloy_primitives::{Address, B256};
+use reqwest::Client;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use url::Url;
+
+use crate::scheme::v2_eip155_erc4337::types::UserOperation;
+
+/// How often to poll the bundler for a user operation receipt.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Submits user operations to a bundler and waits for their inclusion.
+#[derive(Debug, Clone)]
+pub struct BundlerClient {
+    client: Client,
+    url: Url,
+}
+
+/// The subset of `eth_getUserOperationReceipt` used to report settlement.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    pub success: bool,
+    #[serde(default)]
+    pub reason: Option<String>,
+    pub receipt: UserOperationTransactionReceipt,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationTransactionReceipt {
+    pub transaction_hash: B256,
+}
+
+#[derive(Serialize)]
+struct JsonRpcRequest<'a, P> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: P,
+}
+
+#[derive(Deserialize)]
+struct JsonRpcResponse<R> {
+    result: Option<R>,
+    error: Option<JsonRpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+impl BundlerClient {
+    pub fn new(url: Url) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+        }
+    }
+
+    async fn call<P, R>(&self, method: &str, params: P) -> Result<Option<R>, BundlerError>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let request = JsonRpcRequest {
+            jsonrpc: "2.0",
+            id: 1,
+            method,
+            params,
+        };
+        let response = self
+            .client
+            .post(self.url.clone())
+            .json(&request)
+            .send()
+            .await?
+            .json::<JsonRpcResponse<R>>()
+            .await?;
+        match response.error {
+            Some(error) => Err(BundlerError::Rpc {
+                code: error.code,
+                message: error.message,
+            }),
+            None => Ok(response.result),
+        }
+    }
+
+    /// Sends `eth_sendUserOperation`, returning the user operation hash.
+    pub async fn send_user_operation(
+        &self,
+        user_operation: &UserOperation,
+        entry_point: Address,
+    ) -> Result<B256, BundlerError> {
+        self.call("eth_sendUserOperation", (user_operation, entry_point))
+            .await?
+            .ok_or(BundlerError::EmptyResult)
+    }
+
+    /// Polls `eth_getUserOperationReceipt` until the user operation is included or `timeout` elapses.
+    pub async fn wait_for_receipt(
+        &self,
+        user_operation_hash: B256,
+        timeout: Duration,
+    ) -> Result<UserOperationReceipt, BundlerError> {
+        let poll = async {
+            loop {
+                let receipt = self
+                    .call("eth_getUserOperationReceipt", (user_operation_hash,))
+                    .await?;
+                if let Some(receipt) = receipt {
+                    return Ok(receipt);
+                }
+                tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+            }
+        };
+        tokio::time::timeout(timeout, poll)
+            .await
+            .map_err(|_| BundlerError::Timeout(user_operation_hash))?
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BundlerError {
+    #[error("Bundler request failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("Bundler returned error {code}: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("Bundler returned an empty result")]
+    EmptyResult,
+    #[error("Timed out waiting for user operation {0}")]
+    Timeout(B256),
+}
+
+impl BundlerError {
+    /// Whether the bundler rejected the user operation itself, e.g. during validation,
+    /// as opposed to failing to process the request.
+    pub fn is_rejection(&self) -> bool {
+        // ERC-7769 reserves -32500 to -32507 for user operation validation failures.
+        matches!(self, BundlerError::Rpc { code, .. } if (-32507..=-32500).contains(code))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Bytes, U256, b256};
+    use axum::{Json, Router, routing::post};
+    use serde_json::{Value, json};
+
+    const USER_OPERATION_HASH: B256 =
+        b256!("0x1111111111111111111111111111111111111111111111111111111111111111");
+    const TRANSACTION_HASH: B256 =
+        b256!("0x2222222222222222222222222222222222222222222222222222222222222222");
+
+    /// Local stand-in for a bundler: accepts operations with a non-zero nonce and
+    /// reports them as included right away.
+    async fn bundler(Json(request): Json<Value>) -> Json<Value> {
+        let id = request["id"].clone();
+        let response = match request["method"].as_str() {
+            Some("eth_sendUserOperation") if request["params"][0]["nonce"] == "0x0" => json!({
+                "jsonrpc": "2.0", "id": id,
+                "error": { "code": -32500, "message": "AA25 invalid account nonce" },
+            }),
+            Some("eth_sendUserOperation") => json!({
+                "jsonrpc": "2.0", "id": id, "result": USER_OPERATION_HASH,
+            }),
+            Some("eth_getUserOperationReceipt") => json!({
+                "jsonrpc": "2.0", "id": id,
+                "result": {
+                    "userOpHash": request["params"][0],
+                    "success": true,
+                    "receipt": { "transactionHash": TRANSACTION_HASH },
+                },
+            }),
+            _ => json!({
+                "jsonrpc": "2.0", "id": id,
+                "error": { "code": -32601, "message": "Method not found" },
+            }),
+        };
+        Json(response)
+    }
+
+    fn user_operation(nonce: u64) -> UserOperation {
+        UserOperation {
+            sender: Address::repeat_byte(0x01),
+            nonce: U256::from(nonce),
+            factory: None,
+            factory_data: None,
+            call_data: Bytes::new(),
+            call_gas_limit: U256::from(100_000),
+            verification_gas_limit: U256::from(100_000),
+            pre_verification_gas: U256::from(50_000),
+            max_fee_per_gas: U256::from(1_000_000_000),
+            max_priority_fee_per_gas: U256::from(1_000_000),
+            paymaster: None,
+            paymaster_verification_gas_limit: None,
+            paymaster_post_op_gas_limit: None,
+            paymaster_data: None,
+            signature: Bytes::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bundler_client() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, Router::new().route("/", post(bundler)))
+                .await
+                .unwrap();
+        });
+        let client = BundlerClient::new(url);
+        let entry_point = Address::repeat_byte(0x02);
+
+        let hash = client
+            .send_user_operation(&user_operation(1), entry_point)
+            .await
+            .unwrap();
+        assert_eq!(hash, USER_OPERATION_HASH);
+        let receipt = client
+            .wait_for_receipt(hash, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(receipt.success);
+        assert_eq!(receipt.receipt.transaction_hash, TRANSACTION_HASH);
+
+        let error = client
+            .send_user_operation(&user_operation(0), entry_point)
+            .await
+            .unwrap_err();
+        assert!(error.is_rejection());
+    }
+}
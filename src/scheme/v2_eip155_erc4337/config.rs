@@ -0,0 +1,123 @@
+//! Typed configuration for the `v2-eip155-erc4337` scheme.
+
+use alloy_primitives::{Address, address};
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+use crate::scheme::v1_eip155_exact::{Eip155ExactConfig, Eip155ExactConfigError};
+
+/// Canonical ERC-4337 v0.7 EntryPoint.
+pub const ENTRY_POINT_V07_ADDRESS: Address = address!("0x0000000071727De22E5E9d8BAf0edAc6f37da032");
+
+/// Configuration of the `v2-eip155-erc4337` scheme, read from the `config` field of a scheme entry.
+///
+/// Asset and amount bounds, confirmations and clock skew are shared with the EVM `exact` schemes.
+///
+/// Example JSON:
+/// ```json
+/// {
+///   "bundler_url": "https://bundler.example.com/rpc",
+///   "allowed_assets": ["0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913"]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Erc4337Config {
+    /// EntryPoint user operations are submitted to.
+    #[serde(default = "erc4337_config_defaults::default_entry_point")]
+    pub entry_point: Address,
+    /// Bundler to submit user operations to, required unless `direct_settlement` is set.
+    #[serde(default)]
+    pub bundler_url: Option<Url>,
+    /// Settle by calling `handleOps` from a facilitator signer when no `bundler_url` is set.
+    ///
+    /// The facilitator then fronts the gas of user operations without the ERC-7562 validation
+    /// rules a bundler enforces: an account or paymaster whose validation passes in simulation
+    /// but fails on chain makes the facilitator pay for the reverted `handleOps`.
+    #[serde(default)]
+    pub direct_settlement: bool,
+    /// How long to wait for a user operation receipt from the bundler.
+    #[serde(default = "erc4337_config_defaults::default_bundler_timeout_secs")]
+    pub bundler_timeout_secs: u64,
+    #[serde(flatten)]
+    pub exact: Eip155ExactConfig,
+}
+
+mod erc4337_config_defaults {
+    use super::*;
+
+    pub fn default_entry_point() -> Address {
+        ENTRY_POINT_V07_ADDRESS
+    }
+
+    pub fn default_bundler_timeout_secs() -> u64 {
+        60
+    }
+}
+
+impl Default for Erc4337Config {
+    fn default() -> Self {
+        Self {
+            entry_point: erc4337_config_defaults::default_entry_point(),
+            bundler_url: None,
+            direct_settlement: false,
+            bundler_timeout_secs: erc4337_config_defaults::default_bundler_timeout_secs(),
+            exact: Eip155ExactConfig::default(),
+        }
+    }
+}
+
+impl Erc4337Config {
+    /// Parses and validates the scheme entry `config`, falling back to defaults if absent.
+    pub fn from_json(config: Option<serde_json::Value>) -> Result<Self, Erc4337ConfigError> {
+        let config = match config {
+            Some(config) => {
+                serde_json::from_value::<Self>(config).map_err(Eip155ExactConfigError::from)?
+            }
+            None => Self::default(),
+        };
+        config.exact.validate()?;
+        if config.bundler_url.is_none() && !config.direct_settlement {
+            return Err(Erc4337ConfigError::MissingBundlerUrl);
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum Erc4337ConfigError {
+    #[error(transparent)]
+    Exact(#[from] Eip155ExactConfigError),
+    #[error("bundler_url is required unless direct_settlement is enabled")]
+    MissingBundlerUrl,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_config() {
+        let config = Erc4337Config::from_json(Some(json!({
+            "bundler_url": "http://localhost:4337",
+            "confirmations": 2,
+        })))
+        .unwrap();
+        assert_eq!(config.entry_point, ENTRY_POINT_V07_ADDRESS);
+        assert_eq!(config.exact.confirmations, 2);
+        assert!(config.bundler_url.is_some());
+        assert!(!config.direct_settlement);
+        assert!(Erc4337Config::from_json(Some(json!({ "direct_settlement": true }))).is_ok());
+        assert!(matches!(
+            Erc4337Config::from_json(None),
+            Err(Erc4337ConfigError::MissingBundlerUrl)
+        ));
+        assert!(matches!(
+            Erc4337Config::from_json(Some(
+                json!({ "direct_settlement": true, "confirmations": 0 })
+            )),
+            Err(Erc4337ConfigError::Exact(_))
+        ));
+    }
+}
@@ -0,0 +1,510 @@
+//! Payments from ERC-4337 smart accounts, as signed user operations.
+//!
+//! The payer signs a v0.7 [`UserOperation`] whose execution transfers the required
+//! amount of the asset to `payTo`. The facilitator verifies it by simulating
+//! `EntryPoint.handleOps` with `eth_simulateV1`, which runs the account (and paymaster)
+//! validation like `simulateValidation` would, and then checks the logs of the
+//! simulated execution for a successful `UserOperationEvent` and the expected ERC-20
+//! `Transfer`s. This makes the check independent of how the account encodes its calls.
+//!
+//! Settlement submits the user operation to the bundler configured as `bundler_url`.
+//! With `direct_settlement` instead, it calls `handleOps` directly from a facilitator signer,
+//! which is refunded by the account or paymaster as beneficiary. That signer then fronts the
+//! gas without the ERC-7562 validation rules bundlers enforce, and pays for any operation whose
+//! validation passes in simulation but fails on chain: only enable it for trusted payers.
+
+pub mod bundler;
+pub mod config;
+pub mod types;
+
+use alloy_primitives::{Address, TxHash, U256};
+use alloy_provider::Provider;
+use alloy_rpc_types_eth::simulate::{SimBlock, SimulatePayload};
+use alloy_rpc_types_eth::{Log, TransactionRequest};
+use alloy_sol_types::{SolCall, sol};
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{Instrument, instrument};
+use tracing_core::Level;
+
+use crate::chain::eip155::{Eip155ChainProvider, Eip155MetaTransactionProvider, MetaTransaction};
+use crate::chain::{ChainId, ChainProvider, ChainProviderOps, Finality, FinalityError};
+use crate::proto;
+use crate::proto::PaymentVerificationError;
+use crate::proto::v2;
+use crate::scheme::v1_eip155_exact::{Eip155ExactError, IEIP3009, assert_enough_balance};
+use crate::scheme::v2_eip155_erc4337::bundler::{BundlerClient, BundlerError};
+use crate::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError, X402SchemeId,
+};
+
+pub use config::*;
+#[allow(unused)]
+pub use types::*;
+
+sol! {
+    #[allow(missing_docs)]
+    #[derive(Debug)]
+    #[sol(rpc)]
+    interface IEntryPoint {
+        struct PackedUserOperation {
+            address sender;
+            uint256 nonce;
+            bytes initCode;
+            bytes callData;
+            bytes32 accountGasLimits;
+            uint256 preVerificationGas;
+            bytes32 gasFees;
+            bytes paymasterAndData;
+            bytes signature;
+        }
+
+        event UserOperationEvent(
+            bytes32 indexed userOpHash,
+            address indexed sender,
+            address indexed paymaster,
+            uint256 nonce,
+            bool success,
+            uint256 actualGasCost,
+            uint256 actualGasUsed
+        );
+
+        function handleOps(PackedUserOperation[] calldata ops, address payable beneficiary) external;
+    }
+
+    #[allow(missing_docs)]
+    #[derive(Debug)]
+    interface IERC20Events {
+        event Transfer(address indexed from, address indexed to, uint256 value);
+    }
+}
+
+pub struct V2Eip155Erc4337;
+
+impl X402SchemeId for V2Eip155Erc4337 {
+    fn namespace(&self) -> &str {
+        "eip155"
+    }
+
+    fn scheme(&self) -> &str {
+        types::Erc4337Scheme.as_ref()
+    }
+}
+
+impl X402SchemeFacilitatorBuilder for V2Eip155Erc4337 {
+    fn build(
+        &self,
+        provider: ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn Error>> {
+        let provider = if let ChainProvider::Eip155(provider) = provider {
+            provider
+        } else {
+            return Err("V2Eip155Erc4337::build: provider must be an Eip155ChainProvider".into());
+        };
+        let config =
+            Erc4337Config::from_json(config).map_err(|e| format!("V2Eip155Erc4337::build: {e}"))?;
+        let bundler = config.bundler_url.clone().map(BundlerClient::new);
+        if let Some(bundler_url) = &config.bundler_url {
+            tracing::info!(chain_id = %provider.chain_id(), bundler_url = %bundler_url, "Using ERC-4337 bundler");
+        } else {
+            tracing::warn!(chain_id = %provider.chain_id(), "Settling ERC-4337 user operations with handleOps, without bundler validation rules");
+        }
+        Ok(Box::new(V2Eip155Erc4337Facilitator {
+            provider,
+            config,
+            bundler,
+        }))
+    }
+}
+
+pub struct V2Eip155Erc4337Facilitator {
+    provider: Arc<Eip155ChainProvider>,
+    config: Erc4337Config,
+    bundler: Option<BundlerClient>,
+}
+
+#[async_trait::async_trait]
+impl X402SchemeFacilitator for V2Eip155Erc4337Facilitator {
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request.clone())?;
+        let payment = assert_valid_payment(
+            &self.provider,
+            &self.config,
+            &request.payment_payload,
+            &request.payment_requirements,
+        )
+        .await?;
+        Ok(v2::VerifyResponse::valid(payment.user_operation.sender.to_string()).into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let (finality, confirmations) = self
+            .provider
+            .settlement_finality(request.finality()?, self.config.exact.confirmations)?;
+        assert_settlement_finality(finality)?;
+        let request = types::SettleRequest::from_proto(request.clone())?;
+        let payload = &request.payment_payload;
+        let payment = assert_valid_payment(
+            &self.provider,
+            &self.config,
+            payload,
+            &request.payment_requirements,
+        )
+        .await?;
+
+        let tx_hash = match &self.bundler {
+            // The bundler reports the user operation once it is included, further confirmations are awaited below
+            Some(bundler) => {
+                settle_with_bundler(
+                    bundler,
+                    &payment,
+                    self.config.entry_point,
+                    Duration::from_secs(self.config.bundler_timeout_secs),
+                )
+                .await?
+            }
+            None => {
+                settle_payment(
+                    self.provider.as_ref(),
+                    &payment,
+                    self.config.entry_point,
+                    self.provider.next_signer_address(),
+                    confirmations,
+                )
+                .await?
+            }
+        };
+        self.provider
+            .wait_for_finality(tx_hash, finality)
+            .await
+            .map_err(Eip155ExactError::from)?;
+
+        Ok(v2::SettleResponse::Success {
+            payer: payment.user_operation.sender.to_string(),
+            transaction: tx_hash.to_string(),
+            network: payload.accepted.network.to_string(),
+            finality: Some(finality),
+        }
+        .into())
+    }
+
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        let chain_id = self.provider.chain_id();
+        let kinds = {
+            let extra = PaymentRequirementsExtra {
+                entry_point: self.config.entry_point.into(),
+            };
+            vec![proto::SupportedPaymentKind {
+                x402_version: proto::X402Version::v2().into(),
+                scheme: types::Erc4337Scheme.to_string(),
+                network: chain_id.clone().into(),
+                extra: Some(serde_json::to_value(extra).unwrap()),
+            }]
+        };
+        let signers = {
+            let mut signers = HashMap::with_capacity(1);
+            signers.insert(chain_id, self.provider.signer_addresses());
+            signers
+        };
+        Ok(proto::SupportedResponse {
+            kinds,
+            extensions: Vec::new(),
+            signers,
+        })
+    }
+}
+
+/// A user operation whose simulated execution pays the requirements.
+#[derive(Debug)]
+pub struct Erc4337EvmPayment {
+    pub user_operation: UserOperation,
+    pub packed: IEntryPoint::PackedUserOperation,
+    pub asset: Address,
+    pub pay_to: Address,
+    pub amount: U256,
+}
+
+/// Runs all preconditions needed for a successful payment:
+/// - Valid scheme, network, asset and EntryPoint.
+/// - Sufficient on-chain balance of the smart account.
+/// - Successful `handleOps` simulation, transferring the required amount to `payTo`.
+#[instrument(skip_all, err)]
+async fn assert_valid_payment(
+    provider: &Eip155ChainProvider,
+    config: &Erc4337Config,
+    payload: &types::PaymentPayload,
+    requirements: &types::PaymentRequirements,
+) -> Result<Erc4337EvmPayment, Erc4337Error> {
+    let accepted = &payload.accepted;
+    if accepted != requirements {
+        return Err(PaymentVerificationError::AcceptedRequirementsMismatch.into());
+    }
+    let chain_id: ChainId = provider.chain().into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+    if let Some(extra) = &accepted.extra
+        && config.entry_point != extra.entry_point
+    {
+        return Err(
+            PaymentVerificationError::InvalidFormat("Unsupported EntryPoint".to_string()).into(),
+        );
+    }
+    let asset = accepted.asset.into();
+    config.exact.assert_asset(&asset)?;
+    let amount = accepted.amount.into();
+    config.exact.assert_amount(&amount)?;
+
+    let user_operation = payload.payload.user_operation.clone();
+    let contract = IEIP3009::new(asset, provider.inner());
+    assert_enough_balance(&contract, &user_operation.sender, amount).await?;
+
+    let payment = Erc4337EvmPayment {
+        packed: user_operation.pack()?,
+        user_operation,
+        asset,
+        pay_to: accepted.pay_to.into(),
+        amount,
+    };
+    let logs = simulate_handle_ops(
+        provider.inner(),
+        &payment,
+        config.entry_point,
+        provider.next_signer_address(),
+    )
+    .await?;
+    assert_payment_logs(&logs, &payment, config.entry_point)?;
+    Ok(payment)
+}
+
+/// Simulates `handleOps` for the user operation, returning the emitted logs.
+#[instrument(skip_all, err, fields(
+    sender = %payment.user_operation.sender,
+    entry_point = %entry_point
+))]
+async fn simulate_handle_ops<P: Provider>(
+    provider: P,
+    payment: &Erc4337EvmPayment,
+    entry_point: Address,
+    beneficiary: Address,
+) -> Result<Vec<Log>, Erc4337Error> {
+    let call = handle_ops_call(payment, beneficiary);
+    let payload = SimulatePayload {
+        block_state_calls: vec![SimBlock {
+            block_overrides: None,
+            state_overrides: None,
+            calls: vec![
+                TransactionRequest::default()
+                    .from(beneficiary)
+                    .to(entry_point)
+                    .input(call.abi_encode().into()),
+            ],
+        }],
+        trace_transfers: false,
+        validation: false,
+        return_full_transactions: false,
+    };
+    let blocks = provider
+        .simulate(&payload)
+        .into_future()
+        .instrument(tracing::info_span!(
+            "simulate_handleOps",
+            otel.kind = "client"
+        ))
+        .await
+        .map_err(Eip155ExactError::from)?;
+    let result = blocks
+        .into_iter()
+        .flat_map(|block| block.calls)
+        .next()
+        .ok_or_else(|| {
+            PaymentVerificationError::TransactionSimulation("Empty simulation result".to_string())
+        })?;
+    if !result.status {
+        let reason = result
+            .error
+            .map(|error| error.message)
+            .unwrap_or_else(|| "handleOps reverted".to_string());
+        return Err(PaymentVerificationError::TransactionSimulation(reason).into());
+    }
+    Ok(result.logs)
+}
+
+fn handle_ops_call(
+    payment: &Erc4337EvmPayment,
+    beneficiary: Address,
+) -> IEntryPoint::handleOpsCall {
+    IEntryPoint::handleOpsCall {
+        ops: vec![payment.packed.clone()],
+        beneficiary,
+    }
+}
+
+/// Checks that the user operation executed successfully and transferred at least
+/// the required amount of the asset from the smart account to `payTo`.
+fn assert_payment_logs(
+    logs: &[Log],
+    payment: &Erc4337EvmPayment,
+    entry_point: Address,
+) -> Result<(), PaymentVerificationError> {
+    let sender = payment.user_operation.sender;
+    let executed = logs
+        .iter()
+        .filter(|log| log.address() == entry_point)
+        .filter_map(|log| log.log_decode::<IEntryPoint::UserOperationEvent>().ok())
+        .find(|event| event.inner.sender == sender)
+        .map(|event| event.inner.success);
+    match executed {
+        Some(true) => {}
+        Some(false) => {
+            return Err(PaymentVerificationError::TransactionSimulation(
+                "User operation execution reverted".to_string(),
+            ));
+        }
+        None => {
+            return Err(PaymentVerificationError::TransactionSimulation(
+                "User operation was not executed".to_string(),
+            ));
+        }
+    }
+    let transferred = logs
+        .iter()
+        .filter(|log| log.address() == payment.asset)
+        .filter_map(|log| log.log_decode::<IERC20Events::Transfer>().ok())
+        .filter(|transfer| transfer.inner.from == sender && transfer.inner.to == payment.pay_to)
+        .fold(U256::ZERO, |total, transfer| {
+            total.saturating_add(transfer.inner.value)
+        });
+    if transferred < payment.amount {
+        return Err(PaymentVerificationError::InvalidPaymentAmount);
+    }
+    Ok(())
+}
+
+/// Checks that settling with `finality` reports whether the user operation succeeded.
+///
+/// `handleOps` does not revert when the user operation fails, which only its `UserOperationEvent`
+/// tells, so a settlement that is only submitted, without a receipt, cannot be reported as paid.
+pub fn assert_settlement_finality(finality: Finality) -> Result<(), FinalityError> {
+    match finality {
+        Finality::Submitted => Err(FinalityError::Unsupported(finality)),
+        _ => Ok(()),
+    }
+}
+
+/// Calls `handleOps` from `beneficiary`, which is refunded by the account or paymaster.
+pub async fn settle_payment<P, E>(
+    provider: P,
+    payment: &Erc4337EvmPayment,
+    entry_point: Address,
+    beneficiary: Address,
+    confirmations: u64,
+) -> Result<TxHash, Erc4337Error>
+where
+    P: Eip155MetaTransactionProvider<Error = E>,
+    Eip155ExactError: From<E>,
+{
+    let call = handle_ops_call(payment, beneficiary);
+    let receipt = Eip155MetaTransactionProvider::send_transaction(
+        &provider,
+        MetaTransaction {
+            to: entry_point,
+            calldata: call.abi_encode().into(),
+            confirmations,
+            from: Some(beneficiary),
+        },
+    )
+    .instrument(tracing::info_span!("call_handleOps",
+        sender = %payment.user_operation.sender,
+        nonce = %payment.user_operation.nonce,
+        beneficiary = %beneficiary,
+        entry_point = %entry_point,
+        otel.kind = "client",
+    ))
+    .await
+    .map_err(Eip155ExactError::from)?;
+    let tx_hash = receipt.transaction_hash;
+    // handleOps does not revert when the user operation execution fails,
+    // so its outcome is read from the logs of the receipt, see [`assert_settlement_finality`]
+    let paid = receipt.status()
+        && receipt.receipt.as_ref().is_some_and(|receipt| {
+            assert_payment_logs(receipt.inner.logs(), payment, entry_point).is_ok()
+        });
+    if paid {
+        tracing::event!(Level::INFO, status = "ok", tx = %tx_hash, "handleOps succeeded");
+        Ok(tx_hash)
+    } else {
+        tracing::event!(Level::WARN, status = "failed", tx = %tx_hash, "handleOps failed");
+        Err(Erc4337Error::UserOperationFailed(tx_hash))
+    }
+}
+
+/// Submits the user operation to the bundler and waits for its inclusion.
+pub async fn settle_with_bundler(
+    bundler: &BundlerClient,
+    payment: &Erc4337EvmPayment,
+    entry_point: Address,
+    timeout: Duration,
+) -> Result<TxHash, Erc4337Error> {
+    let user_operation_hash = bundler
+        .send_user_operation(&payment.user_operation, entry_point)
+        .instrument(tracing::info_span!("eth_sendUserOperation",
+            sender = %payment.user_operation.sender,
+            nonce = %payment.user_operation.nonce,
+            otel.kind = "client",
+        ))
+        .await?;
+    let receipt = bundler
+        .wait_for_receipt(user_operation_hash, timeout)
+        .instrument(tracing::info_span!("eth_getUserOperationReceipt",
+            user_operation_hash = %user_operation_hash,
+            otel.kind = "client",
+        ))
+        .await?;
+    let tx_hash = receipt.receipt.transaction_hash;
+    if receipt.success {
+        tracing::event!(Level::INFO, status = "ok", tx = %tx_hash, user_operation_hash = %user_operation_hash, "User operation succeeded");
+        Ok(tx_hash)
+    } else {
+        tracing::event!(Level::WARN, status = "failed", tx = %tx_hash, reason = ?receipt.reason, "User operation failed");
+        Err(Erc4337Error::UserOperationFailed(tx_hash))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Erc4337Error {
+    #[error(transparent)]
+    Exact(#[from] Eip155ExactError),
+    #[error(transparent)]
+    Bundler(#[from] BundlerError),
+    #[error("User operation in transaction {0} failed")]
+    UserOperationFailed(TxHash),
+}
+
+impl From<PaymentVerificationError> for Erc4337Error {
+    fn from(value: PaymentVerificationError) -> Self {
+        Self::Exact(value.into())
+    }
+}
+
+impl From<Erc4337Error> for X402SchemeFacilitatorError {
+    fn from(value: Erc4337Error) -> Self {
+        match value {
+            Erc4337Error::Exact(e) => e.into(),
+            Erc4337Error::Bundler(e) if e.is_rejection() => Self::PaymentVerification(
+                PaymentVerificationError::TransactionSimulation(e.to_string()),
+            ),
+            Erc4337Error::Bundler(_) => Self::OnchainFailure(value.to_string()),
+            Erc4337Error::UserOperationFailed(_) => Self::OnchainFailure(value.to_string()),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, U256};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+use crate::chain::eip155::{ChecksummedAddress, TokenAmount};
+use crate::lit_str;
+use crate::proto::PaymentVerificationError;
+use crate::proto::v2;
+use crate::scheme::v2_eip155_erc4337::IEntryPoint;
+
+lit_str!(Erc4337Scheme, "erc4337");
+
+pub type VerifyRequest = v2::VerifyRequest<PaymentPayload, PaymentRequirements>;
+pub type SettleRequest = VerifyRequest;
+pub type PaymentPayload = v2::PaymentPayload<PaymentRequirements, Erc4337EvmPayload>;
+pub type PaymentRequirements = v2::PaymentRequirements<
+    Erc4337Scheme,
+    TokenAmount,
+    ChecksummedAddress,
+    PaymentRequirementsExtra,
+>;
+
+/// Signed user operation transferring the payment from the payer smart account.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Erc4337EvmPayload {
+    pub user_operation: UserOperation,
+}
+
+/// ERC-4337 v0.7 user operation, in the unpacked form used by bundler RPC methods.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub factory_data: Option<Bytes>,
+    pub call_data: Bytes,
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paymaster_data: Option<Bytes>,
+    pub signature: Bytes,
+}
+
+impl UserOperation {
+    /// Packs the user operation into the struct passed to `EntryPoint.handleOps`.
+    pub fn pack(&self) -> Result<IEntryPoint::PackedUserOperation, PaymentVerificationError> {
+        let init_code = match self.factory {
+            Some(factory) => {
+                let factory_data = self.factory_data.clone().unwrap_or_default();
+                [factory.as_slice(), factory_data.as_ref()].concat().into()
+            }
+            None => Bytes::new(),
+        };
+        let paymaster_and_data = match self.paymaster {
+            Some(paymaster) => {
+                let verification_gas_limit =
+                    to_u128(self.paymaster_verification_gas_limit.unwrap_or_default())?;
+                let post_op_gas_limit =
+                    to_u128(self.paymaster_post_op_gas_limit.unwrap_or_default())?;
+                let paymaster_data = self.paymaster_data.clone().unwrap_or_default();
+                [
+                    paymaster.as_slice(),
+                    &verification_gas_limit.to_be_bytes(),
+                    &post_op_gas_limit.to_be_bytes(),
+                    paymaster_data.as_ref(),
+                ]
+                .concat()
+                .into()
+            }
+            None => Bytes::new(),
+        };
+        Ok(IEntryPoint::PackedUserOperation {
+            sender: self.sender,
+            nonce: self.nonce,
+            initCode: init_code,
+            callData: self.call_data.clone(),
+            accountGasLimits: pack_u128_pair(
+                to_u128(self.verification_gas_limit)?,
+                to_u128(self.call_gas_limit)?,
+            ),
+            preVerificationGas: self.pre_verification_gas,
+            gasFees: pack_u128_pair(
+                to_u128(self.max_priority_fee_per_gas)?,
+                to_u128(self.max_fee_per_gas)?,
+            ),
+            paymasterAndData: paymaster_and_data,
+            signature: self.signature.clone(),
+        })
+    }
+}
+
+fn to_u128(value: U256) -> Result<u128, PaymentVerificationError> {
+    u128::try_from(value).map_err(|_| {
+        PaymentVerificationError::InvalidFormat("User operation gas value overflows".to_string())
+    })
+}
+
+/// Packs two 128-bit values into a 32-byte word, `high` first.
+fn pack_u128_pair(high: u128, low: u128) -> B256 {
+    let mut word = FixedBytes::<32>::ZERO;
+    word[..16].copy_from_slice(&high.to_be_bytes());
+    word[16..].copy_from_slice(&low.to_be_bytes());
+    word
+}
+
+/// Extra data of `erc4337` payment requirements, as advertised in `/supported`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequirementsExtra {
+    /// EntryPoint the user operation must be signed for.
+    pub entry_point: ChecksummedAddress,
+}
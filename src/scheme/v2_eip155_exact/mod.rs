@@ -9,15 +9,16 @@ use std::sync::Arc;
 use tracing::instrument;
 
 use crate::chain::eip155::{
-    Eip155ChainProvider, Eip155ChainReference, Eip155MetaTransactionProvider,
+    Eip155ChainProvider, Eip155ChainReference, Eip155MetaTransactionProvider, TokenMetadataCache,
 };
 use crate::chain::{ChainId, ChainProvider, ChainProviderOps};
 use crate::proto;
 use crate::proto::PaymentVerificationError;
 use crate::proto::v2;
 use crate::scheme::v1_eip155_exact::{
-    Eip155ExactError, ExactEvmPayment, IEIP3009, assert_domain, assert_enough_balance,
-    assert_enough_value, assert_time, settle_payment, verify_payment,
+    Eip155ExactConfig, Eip155ExactError, ExactEvmPayment, IEIP3009, assert_domain,
+    assert_enough_balance, assert_enough_value, assert_payee, assert_time, settle_payment,
+    verify_payment,
 };
 use crate::scheme::{
     X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError, X402SchemeId,
@@ -42,19 +43,22 @@ impl X402SchemeFacilitatorBuilder for V2Eip155Exact {
     fn build(
         &self,
         provider: ChainProvider,
-        _config: Option<serde_json::Value>,
+        config: Option<serde_json::Value>,
     ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn Error>> {
         let provider = if let ChainProvider::Eip155(provider) = provider {
             provider
         } else {
             return Err("V2Eip155Exact::build: provider must be an Eip155ChainProvider".into());
         };
-        Ok(Box::new(V2Eip155ExactFacilitator { provider }))
+        let config = Eip155ExactConfig::from_json(config)
+            .map_err(|e| format!("V2Eip155Exact::build: {e}"))?;
+        Ok(Box::new(V2Eip155ExactFacilitator { provider, config }))
     }
 }
 
 pub struct V2Eip155ExactFacilitator {
     provider: Arc<Eip155ChainProvider>,
+    config: Eip155ExactConfig,
 }
 
 #[async_trait::async_trait]
@@ -69,13 +73,22 @@ impl X402SchemeFacilitator for V2Eip155ExactFacilitator {
         let (contract, payment, eip712_domain) = assert_valid_payment(
             self.provider.inner(),
             self.provider.chain(),
+            self.provider.token_metadata(),
+            &self.config,
             payload,
             requirements,
         )
         .await?;
+        assert_payee(self.provider.inner(), &payment).await?;
 
-        let payer =
-            verify_payment(self.provider.inner(), &contract, &payment, &eip712_domain).await?;
+        let payer = verify_payment(
+            self.provider.inner(),
+            &contract,
+            &payment,
+            &eip712_domain,
+            self.provider.eip6492_validation(),
+        )
+        .await?;
         Ok(v2::VerifyResponse::valid(payer.to_string()).into())
     }
 
@@ -83,24 +96,41 @@ impl X402SchemeFacilitator for V2Eip155ExactFacilitator {
         &self,
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let (finality, confirmations) = self
+            .provider
+            .settlement_finality(request.finality()?, self.config.confirmations)?;
         let request = types::SettleRequest::from_proto(request.clone())?;
         let payload = &request.payment_payload;
         let requirements = &request.payment_requirements;
         let (contract, payment, eip712_domain) = assert_valid_payment(
             self.provider.inner(),
             self.provider.chain(),
+            self.provider.token_metadata(),
+            &self.config,
             payload,
             requirements,
         )
         .await?;
-
-        let tx_hash =
-            settle_payment(self.provider.as_ref(), &contract, &payment, &eip712_domain).await?;
+        assert_payee(self.provider.inner(), &payment).await?;
+
+        let tx_hash = settle_payment(
+            self.provider.as_ref(),
+            &contract,
+            &payment,
+            &eip712_domain,
+            confirmations,
+        )
+        .await?;
+        self.provider
+            .wait_for_finality(tx_hash, finality)
+            .await
+            .map_err(Eip155ExactError::from)?;
 
         Ok(v2::SettleResponse::Success {
             payer: payment.from.to_string(),
             transaction: tx_hash.to_string(),
             network: payload.accepted.network.to_string(),
+            finality: Some(finality),
         }
         .into())
     }
@@ -136,6 +166,8 @@ impl X402SchemeFacilitator for V2Eip155ExactFacilitator {
 async fn assert_valid_payment<P: Provider>(
     provider: P,
     chain: &Eip155ChainReference,
+    token_metadata: &TokenMetadataCache,
+    config: &Eip155ExactConfig,
     payload: &types::PaymentPayload,
     requirements: &types::PaymentRequirements,
 ) -> Result<(IEIP3009::IEIP3009Instance<P>, ExactEvmPayment, Eip712Domain), Eip155ExactError> {
@@ -156,13 +188,22 @@ async fn assert_valid_payment<P: Provider>(
     }
     let valid_after = authorization.valid_after;
     let valid_before = authorization.valid_before;
-    assert_time(valid_after, valid_before)?;
+    assert_time(valid_after, valid_before, config.clock_skew_secs)?;
     let asset_address = accepted.asset;
+    config.assert_asset(&asset_address.into())?;
+    let amount_required = accepted.amount;
+    config.assert_amount(&amount_required.into())?;
     let contract = IEIP3009::new(asset_address.into(), provider);
 
-    let domain = assert_domain(chain, &contract, &asset_address.into(), &accepted.extra).await?;
+    let domain = assert_domain(
+        chain,
+        &contract,
+        &asset_address.into(),
+        &accepted.extra,
+        token_metadata,
+    )
+    .await?;
 
-    let amount_required = accepted.amount;
     assert_enough_balance(&contract, &authorization.from, amount_required.into()).await?;
     assert_enough_value(&authorization.value, &amount_required.into())?;
 
@@ -174,6 +215,11 @@ async fn assert_valid_payment<P: Provider>(
         valid_before: authorization.valid_before,
         nonce: authorization.nonce,
         signature: payload.signature.clone(),
+        method: accepted
+            .extra
+            .as_ref()
+            .map(|extra| extra.authorization_method)
+            .unwrap_or_default(),
     };
 
     Ok((contract, payment, domain))
@@ -0,0 +1,37 @@
+use alloy_primitives::B256;
+
+use crate::chain::eip155::Eip155ChainReference;
+use crate::proto::v2::ResourceInfo;
+use crate::scheme::permit2::Permit2Authorization;
+use crate::scheme::permit2::client::{Permit2Client, Permit2Witness};
+use crate::scheme::v2_eip155_permit2::types::{Permit2EvmPermit, Permit2Scheme};
+use crate::scheme::v2_eip155_permit2::{V2Eip155Permit2, permit_signing_hash};
+
+/// Client for the `permit2` scheme: signs a Permit2 witness transfer of the required amount,
+/// bound to the recipient and the resource URL.
+///
+/// Works with any ERC-20 token, provided the payer has approved the Permit2 contract on it beforehand.
+pub type V2Eip155Permit2Client<S> = Permit2Client<S, V2Eip155Permit2>;
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+impl<S> V2Eip155Permit2Client<S> {
+    pub fn new(signer: S) -> Self {
+        Self::with_scheme(signer, V2Eip155Permit2)
+    }
+}
+
+impl Permit2Witness for V2Eip155Permit2 {
+    type Scheme = Permit2Scheme;
+    type Permit = Permit2EvmPermit;
+
+    fn permit(authorization: Permit2Authorization, resource: &ResourceInfo) -> Permit2EvmPermit {
+        Permit2EvmPermit {
+            authorization,
+            resource: resource.url.clone(),
+        }
+    }
+
+    fn signing_hash(chain: &Eip155ChainReference, permit: &Permit2EvmPermit) -> B256 {
+        permit_signing_hash(chain, permit)
+    }
+}
@@ -0,0 +1,299 @@
+//! Exact payments in any ERC-20 token through Permit2.
+//!
+//! Unlike [`crate::scheme::v2_eip155_exact`], this scheme does not require the token to
+//! implement ERC-3009, so it works with tokens such as USDT, DAI or WETH. The payer signs
+//! a Permit2 `permitWitnessTransferFrom` authorizing a facilitator signer (the spender,
+//! advertised as `extra.spender` in `/supported`) to transfer the required amount. The
+//! witness binds the recipient and the resource URL, so the signature cannot be reused
+//! to pay anyone else or for another resource.
+//!
+//! The payer must have approved the canonical Permit2 contract on the token. The Permit2
+//! transfer itself is shared with other schemes, see [`crate::scheme::permit2`].
+
+pub mod client;
+pub mod types;
+
+use alloy_primitives::{B256, Bytes, U256};
+use alloy_sol_types::{SolStruct, sol};
+use std::error::Error;
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::chain::eip155::{
+    Eip155ChainProvider, Eip155ChainReference, Eip155MetaTransactionProvider,
+};
+use crate::chain::{ChainId, ChainProvider};
+use crate::proto;
+use crate::proto::PaymentVerificationError;
+use crate::proto::v2;
+use crate::scheme::permit2::{
+    Permit2Transfer, PermitWitnessTransferFrom, assert_valid_authorization, settle_transfer,
+    simulate_transfer,
+};
+use crate::scheme::v1_eip155_exact::{Eip155ExactConfig, Eip155ExactError};
+use crate::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError, X402SchemeId,
+};
+
+#[allow(unused)]
+pub use types::*;
+
+/// Witness type string appended by Permit2 to the `PermitWitnessTransferFrom` type.
+pub const WITNESS_TYPE_STRING: &str = "PaymentWitness witness)PaymentWitness(address to,string resource)TokenPermissions(address token,uint256 amount)";
+
+sol! {
+    /// Witness binding the recipient and the resource of a payment.
+    struct PaymentWitness {
+        address to;
+        string resource;
+    }
+}
+
+pub struct V2Eip155Permit2;
+
+impl X402SchemeId for V2Eip155Permit2 {
+    fn namespace(&self) -> &str {
+        "eip155"
+    }
+
+    fn scheme(&self) -> &str {
+        types::Permit2Scheme.as_ref()
+    }
+}
+
+impl X402SchemeFacilitatorBuilder for V2Eip155Permit2 {
+    fn build(
+        &self,
+        provider: ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn Error>> {
+        let provider = if let ChainProvider::Eip155(provider) = provider {
+            provider
+        } else {
+            return Err("V2Eip155Permit2::build: provider must be an Eip155ChainProvider".into());
+        };
+        let config = Eip155ExactConfig::from_json(config)
+            .map_err(|e| format!("V2Eip155Permit2::build: {e}"))?;
+        Ok(Box::new(V2Eip155Permit2Facilitator { provider, config }))
+    }
+}
+
+pub struct V2Eip155Permit2Facilitator {
+    provider: Arc<Eip155ChainProvider>,
+    config: Eip155ExactConfig,
+}
+
+#[async_trait::async_trait]
+impl X402SchemeFacilitator for V2Eip155Permit2Facilitator {
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request.clone())?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let payment =
+            assert_valid_payment(&self.provider, &self.config, payload, requirements).await?;
+        simulate_transfer(self.provider.inner(), &payment.transfer(), payment.amount).await?;
+        Ok(v2::VerifyResponse::valid(payment.permit.authorization.from.to_string()).into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let (finality, confirmations) = self
+            .provider
+            .settlement_finality(request.finality()?, self.config.confirmations)?;
+        let request = types::SettleRequest::from_proto(request.clone())?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let payment =
+            assert_valid_payment(&self.provider, &self.config, payload, requirements).await?;
+
+        let tx_hash = settle_transfer(
+            self.provider.as_ref(),
+            &payment.transfer(),
+            payment.amount,
+            confirmations,
+        )
+        .await?;
+        self.provider
+            .wait_for_finality(tx_hash, finality)
+            .await
+            .map_err(Eip155ExactError::from)?;
+
+        Ok(v2::SettleResponse::Success {
+            payer: payment.permit.authorization.from.to_string(),
+            transaction: tx_hash.to_string(),
+            network: payload.accepted.network.to_string(),
+            finality: Some(finality),
+        }
+        .into())
+    }
+
+    /// Advertises the next facilitator signer as the Permit2 spender to authorize.
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        Ok(crate::scheme::permit2::supported(
+            &self.provider,
+            types::Permit2Scheme.as_ref(),
+        ))
+    }
+}
+
+/// A signed Permit2 witness transfer that passed the static and funds checks.
+#[derive(Debug)]
+pub struct Permit2EvmPayment {
+    pub permit: Permit2EvmPermit,
+    pub signature: Bytes,
+    /// Amount to transfer, as required by the payment requirements.
+    pub amount: U256,
+}
+
+impl Permit2EvmPayment {
+    fn transfer(&self) -> Permit2Transfer<'_> {
+        let witness = PaymentWitness {
+            to: self.permit.authorization.to,
+            resource: self.permit.resource.clone(),
+        };
+        Permit2Transfer {
+            authorization: &self.permit.authorization,
+            witness: witness.eip712_hash_struct(),
+            witness_type_string: WITNESS_TYPE_STRING,
+            signature: &self.signature,
+        }
+    }
+}
+
+/// Runs all preconditions needed for a successful payment:
+/// - Valid scheme, network and resource.
+/// - Valid authorization, see [`assert_valid_authorization`].
+#[instrument(skip_all, err)]
+async fn assert_valid_payment(
+    provider: &Eip155ChainProvider,
+    config: &Eip155ExactConfig,
+    payload: &types::PaymentPayload,
+    requirements: &types::PaymentRequirements,
+) -> Result<Permit2EvmPayment, Eip155ExactError> {
+    let accepted = &payload.accepted;
+    if accepted != requirements {
+        return Err(PaymentVerificationError::AcceptedRequirementsMismatch.into());
+    }
+    let chain_id: ChainId = provider.chain().into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+    let permit = &payload.payload.permit;
+    if permit.resource != payload.resource.url {
+        return Err(PaymentVerificationError::InvalidFormat(
+            "Permit resource does not match the paid resource".to_string(),
+        )
+        .into());
+    }
+    let amount_required = accepted.amount.into();
+    assert_valid_authorization(
+        provider,
+        config,
+        &permit.authorization,
+        accepted.pay_to.into(),
+        accepted.asset.into(),
+        amount_required,
+    )
+    .await?;
+
+    Ok(Permit2EvmPayment {
+        permit: permit.clone(),
+        signature: payload.payload.signature.clone(),
+        amount: amount_required,
+    })
+}
+
+/// Hash of the message a payer signs to authorize `permit`.
+pub fn permit_signing_hash(chain: &Eip155ChainReference, permit: &Permit2EvmPermit) -> B256 {
+    let authorization = &permit.authorization;
+    let witness = PaymentWitness {
+        to: authorization.to,
+        resource: permit.resource.clone(),
+    };
+    PermitWitnessTransferFrom::new(authorization, witness).eip712_signing_hash(chain.inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_witness_type_string_matches_eip712_type() {
+        assert_eq!(
+            PermitWitnessTransferFrom::<PaymentWitness>::witness_type_string(),
+            WITNESS_TYPE_STRING
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_permit_recovers_payer() {
+        use crate::proto::client::X402SchemeClient;
+        use crate::util::Base64Bytes;
+        use alloy_primitives::Signature;
+        use alloy_signer_local::PrivateKeySigner;
+
+        let signer = PrivateKeySigner::random();
+        let spender = alloy_primitives::Address::repeat_byte(3);
+        let payment_required = proto::PaymentRequired::V2(v2::PaymentRequired {
+            x402_version: v2::X402Version2,
+            resource: v2::ResourceInfo {
+                description: String::new(),
+                mime_type: "application/json".to_string(),
+                url: "https://example.com/resource".to_string(),
+            },
+            accepts: vec![serde_json::json!({
+                "scheme": "permit2",
+                "network": "eip155:8453",
+                "amount": "1000",
+                "payTo": alloy_primitives::Address::repeat_byte(4),
+                "maxTimeoutSeconds": 60,
+                "asset": alloy_primitives::Address::repeat_byte(2),
+                "extra": { "spender": spender },
+            })],
+        });
+        let candidates =
+            client::V2Eip155Permit2Client::with_scheme(signer.clone(), V2Eip155Permit2)
+                .accept(&payment_required);
+        assert_eq!(candidates.len(), 1);
+        let header = candidates[0].signer.sign_payment().await.unwrap();
+
+        let json = Base64Bytes::from(header.as_bytes()).decode().unwrap();
+        let payload: PaymentPayload = serde_json::from_slice(&json).unwrap();
+        let permit = &payload.payload.permit;
+        assert_eq!(permit.authorization.spender, spender);
+        assert_eq!(permit.resource, "https://example.com/resource");
+        let chain = Eip155ChainReference::try_from(&payload.accepted.network).unwrap();
+        let signature = Signature::try_from(payload.payload.signature.as_ref()).unwrap();
+        let recovered = signature
+            .recover_address_from_prehash(&permit_signing_hash(&chain, permit))
+            .unwrap();
+        assert_eq!(recovered, signer.address());
+    }
+
+    #[test]
+    fn test_permit_wire_format_is_flat() {
+        let permit = Permit2EvmPermit {
+            authorization: crate::scheme::permit2::Permit2Authorization {
+                from: alloy_primitives::Address::repeat_byte(1),
+                token: alloy_primitives::Address::repeat_byte(2),
+                amount: U256::from(1000),
+                spender: alloy_primitives::Address::repeat_byte(3),
+                nonce: U256::from(7),
+                deadline: crate::timestamp::UnixTimestamp::from_secs(1_700_000_000),
+                to: alloy_primitives::Address::repeat_byte(4),
+            },
+            resource: "https://example.com/resource".to_string(),
+        };
+        let json = serde_json::to_value(&permit).unwrap();
+        assert_eq!(json["spender"], json!(permit.authorization.spender));
+        assert_eq!(json["resource"], "https://example.com/resource");
+        let decoded: Permit2EvmPermit = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.authorization.nonce, U256::from(7));
+    }
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+use crate::chain::eip155::{ChecksummedAddress, TokenAmount};
+use crate::lit_str;
+use crate::proto::v2;
+pub use crate::scheme::permit2::PaymentRequirementsExtra;
+use crate::scheme::permit2::{Permit2Authorization, Permit2Payload};
+
+lit_str!(Permit2Scheme, "permit2");
+
+pub type VerifyRequest = v2::VerifyRequest<PaymentPayload, PaymentRequirements>;
+pub type SettleRequest = VerifyRequest;
+pub type PaymentPayload = v2::PaymentPayload<PaymentRequirements, Permit2EvmPayload>;
+pub type PaymentRequirements = v2::PaymentRequirements<
+    Permit2Scheme,
+    TokenAmount,
+    ChecksummedAddress,
+    PaymentRequirementsExtra,
+>;
+
+/// Signed Permit2 witness transfer of exactly the required amount.
+pub type Permit2EvmPayload = Permit2Payload<Permit2EvmPermit>;
+
+/// Fields of the Permit2 `PermitWitnessTransferFrom` message, with the witness inlined
+/// as `to` and `resource`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Permit2EvmPermit {
+    #[serde(flatten)]
+    pub authorization: Permit2Authorization,
+    /// URL of the resource paid for.
+    pub resource: String,
+}
@@ -0,0 +1,33 @@
+use alloy_primitives::B256;
+
+use crate::chain::eip155::Eip155ChainReference;
+use crate::proto::v2::ResourceInfo;
+use crate::scheme::permit2::Permit2Authorization;
+use crate::scheme::permit2::client::{Permit2Client, Permit2Witness};
+use crate::scheme::v2_eip155_upto::types::{UptoEvmPermit, UptoScheme};
+use crate::scheme::v2_eip155_upto::{V2Eip155Upto, permit_signing_hash};
+
+/// Client for the `upto` scheme: signs a Permit2 witness transfer of up to the required amount.
+///
+/// The payer must have approved the Permit2 contract on the token beforehand.
+pub type V2Eip155UptoClient<S> = Permit2Client<S, V2Eip155Upto>;
+
+#[allow(dead_code)] // Public for consumption by downstream crates.
+impl<S> V2Eip155UptoClient<S> {
+    pub fn new(signer: S) -> Self {
+        Self::with_scheme(signer, V2Eip155Upto)
+    }
+}
+
+impl Permit2Witness for V2Eip155Upto {
+    type Scheme = UptoScheme;
+    type Permit = UptoEvmPermit;
+
+    fn permit(authorization: Permit2Authorization, _resource: &ResourceInfo) -> UptoEvmPermit {
+        authorization
+    }
+
+    fn signing_hash(chain: &Eip155ChainReference, permit: &UptoEvmPermit) -> B256 {
+        permit_signing_hash(chain, permit)
+    }
+}
@@ -0,0 +1,268 @@
+//! The `upto` scheme for metered payments on EVM chains.
+//!
+//! The payer signs a Permit2 `permitWitnessTransferFrom` authorizing a facilitator signer
+//! (the spender, advertised as `extra.spender` in `/supported`) to transfer up to the
+//! required amount to `payTo`. The recipient is bound by the witness, so the spender cannot
+//! redirect funds. Once the resource is served, the seller settles the amount actually
+//! used, passed as `amount` in the settle request.
+//!
+//! The Permit2 transfer itself is shared with other schemes, see [`crate::scheme::permit2`].
+
+pub mod client;
+pub mod types;
+
+use alloy_primitives::{B256, Bytes, U256};
+use alloy_sol_types::{SolStruct, sol};
+use std::error::Error;
+use std::sync::Arc;
+use tracing::instrument;
+
+use crate::chain::eip155::{
+    Eip155ChainProvider, Eip155ChainReference, Eip155MetaTransactionProvider,
+};
+use crate::chain::{ChainId, ChainProvider};
+use crate::proto;
+use crate::proto::PaymentVerificationError;
+use crate::proto::v2;
+use crate::scheme::permit2::{
+    Permit2Transfer, PermitWitnessTransferFrom, assert_valid_authorization, settle_transfer,
+    simulate_transfer,
+};
+use crate::scheme::v1_eip155_exact::{Eip155ExactConfig, Eip155ExactError};
+use crate::scheme::{
+    X402SchemeFacilitator, X402SchemeFacilitatorBuilder, X402SchemeFacilitatorError, X402SchemeId,
+};
+
+#[allow(unused)]
+pub use types::*;
+
+/// Witness type string appended by Permit2 to the `PermitWitnessTransferFrom` type.
+pub const WITNESS_TYPE_STRING: &str =
+    "Witness witness)TokenPermissions(address token,uint256 amount)Witness(address to)";
+
+sol! {
+    /// Witness binding the recipient of an `upto` transfer.
+    struct Witness {
+        address to;
+    }
+}
+
+pub struct V2Eip155Upto;
+
+impl X402SchemeId for V2Eip155Upto {
+    fn namespace(&self) -> &str {
+        "eip155"
+    }
+
+    fn scheme(&self) -> &str {
+        types::UptoScheme.as_ref()
+    }
+}
+
+impl X402SchemeFacilitatorBuilder for V2Eip155Upto {
+    fn build(
+        &self,
+        provider: ChainProvider,
+        config: Option<serde_json::Value>,
+    ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn Error>> {
+        let provider = if let ChainProvider::Eip155(provider) = provider {
+            provider
+        } else {
+            return Err("V2Eip155Upto::build: provider must be an Eip155ChainProvider".into());
+        };
+        let config = Eip155ExactConfig::from_json(config)
+            .map_err(|e| format!("V2Eip155Upto::build: {e}"))?;
+        Ok(Box::new(V2Eip155UptoFacilitator { provider, config }))
+    }
+}
+
+pub struct V2Eip155UptoFacilitator {
+    provider: Arc<Eip155ChainProvider>,
+    config: Eip155ExactConfig,
+}
+
+#[async_trait::async_trait]
+impl X402SchemeFacilitator for V2Eip155UptoFacilitator {
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
+        let request = types::VerifyRequest::from_proto(request.clone())?;
+        let payload = &request.payment_payload;
+        let requirements = &request.payment_requirements;
+        let payment =
+            assert_valid_payment(&self.provider, &self.config, payload, requirements).await?;
+        simulate_transfer(
+            self.provider.inner(),
+            &payment.transfer(),
+            requirements.amount.into(),
+        )
+        .await?;
+        Ok(v2::VerifyResponse::valid(payment.permit.from.to_string()).into())
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let (finality, confirmations) = self
+            .provider
+            .settlement_finality(request.finality()?, self.config.confirmations)?;
+        let request = types::SettleRequest::from_proto(request.clone())?;
+        let payload = &request.request.payment_payload;
+        let requirements = &request.request.payment_requirements;
+        let payment =
+            assert_valid_payment(&self.provider, &self.config, payload, requirements).await?;
+        let amount_required: U256 = requirements.amount.into();
+        let amount = request
+            .amount
+            .map(|amount| amount.0)
+            .unwrap_or(amount_required);
+        if amount > amount_required {
+            return Err(PaymentVerificationError::InvalidPaymentAmount.into());
+        }
+
+        let tx_hash = settle_transfer(
+            self.provider.as_ref(),
+            &payment.transfer(),
+            amount,
+            confirmations,
+        )
+        .await?;
+        self.provider
+            .wait_for_finality(tx_hash, finality)
+            .await
+            .map_err(Eip155ExactError::from)?;
+
+        Ok(v2::SettleResponse::Success {
+            payer: payment.permit.from.to_string(),
+            transaction: tx_hash.to_string(),
+            network: payload.accepted.network.to_string(),
+            finality: Some(finality),
+        }
+        .into())
+    }
+
+    /// Advertises the next facilitator signer as the Permit2 spender to authorize.
+    async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
+        Ok(crate::scheme::permit2::supported(
+            &self.provider,
+            types::UptoScheme.as_ref(),
+        ))
+    }
+}
+
+/// A signed Permit2 witness transfer that passed the static and funds checks.
+#[derive(Debug)]
+pub struct UptoEvmPayment {
+    pub permit: UptoEvmPermit,
+    pub signature: Bytes,
+}
+
+impl UptoEvmPayment {
+    fn transfer(&self) -> Permit2Transfer<'_> {
+        Permit2Transfer {
+            authorization: &self.permit,
+            witness: Witness { to: self.permit.to }.eip712_hash_struct(),
+            witness_type_string: WITNESS_TYPE_STRING,
+            signature: &self.signature,
+        }
+    }
+}
+
+/// Runs all preconditions needed for a successful payment:
+/// - Valid scheme and network.
+/// - Valid authorization, see [`assert_valid_authorization`].
+#[instrument(skip_all, err)]
+async fn assert_valid_payment(
+    provider: &Eip155ChainProvider,
+    config: &Eip155ExactConfig,
+    payload: &types::PaymentPayload,
+    requirements: &types::PaymentRequirements,
+) -> Result<UptoEvmPayment, Eip155ExactError> {
+    let accepted = &payload.accepted;
+    if accepted != requirements {
+        return Err(PaymentVerificationError::AcceptedRequirementsMismatch.into());
+    }
+    let chain_id: ChainId = provider.chain().into();
+    if accepted.network != chain_id {
+        return Err(PaymentVerificationError::ChainIdMismatch.into());
+    }
+    let permit = payload.payload.permit;
+    assert_valid_authorization(
+        provider,
+        config,
+        &permit,
+        accepted.pay_to.into(),
+        accepted.asset.into(),
+        accepted.amount.into(),
+    )
+    .await?;
+
+    Ok(UptoEvmPayment {
+        permit,
+        signature: payload.payload.signature.clone(),
+    })
+}
+
+/// Hash of the message a payer signs to authorize `permit`.
+pub fn permit_signing_hash(chain: &Eip155ChainReference, permit: &UptoEvmPermit) -> B256 {
+    PermitWitnessTransferFrom::new(permit, Witness { to: permit.to })
+        .eip712_signing_hash(chain.inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_witness_type_string_matches_eip712_type() {
+        assert_eq!(
+            PermitWitnessTransferFrom::<Witness>::witness_type_string(),
+            WITNESS_TYPE_STRING
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_permit_recovers_payer() {
+        use crate::proto::client::X402SchemeClient;
+        use crate::util::Base64Bytes;
+        use alloy_primitives::Signature;
+        use alloy_signer_local::PrivateKeySigner;
+
+        let signer = PrivateKeySigner::random();
+        let spender = alloy_primitives::Address::repeat_byte(3);
+        let payment_required = proto::PaymentRequired::V2(v2::PaymentRequired {
+            x402_version: v2::X402Version2,
+            resource: v2::ResourceInfo {
+                description: String::new(),
+                mime_type: "application/json".to_string(),
+                url: "https://example.com/resource".to_string(),
+            },
+            accepts: vec![serde_json::json!({
+                "scheme": "upto",
+                "network": "eip155:8453",
+                "amount": "1000",
+                "payTo": alloy_primitives::Address::repeat_byte(4),
+                "maxTimeoutSeconds": 60,
+                "asset": alloy_primitives::Address::repeat_byte(2),
+                "extra": { "spender": spender },
+            })],
+        });
+        let candidates = client::V2Eip155UptoClient::with_scheme(signer.clone(), V2Eip155Upto)
+            .accept(&payment_required);
+        assert_eq!(candidates.len(), 1);
+        let header = candidates[0].signer.sign_payment().await.unwrap();
+
+        let json = Base64Bytes::from(header.as_bytes()).decode().unwrap();
+        let payload: PaymentPayload = serde_json::from_slice(&json).unwrap();
+        let permit = &payload.payload.permit;
+        assert_eq!(permit.spender, spender);
+        let chain = Eip155ChainReference::try_from(&payload.accepted.network).unwrap();
+        let signature = Signature::try_from(payload.payload.signature.as_ref()).unwrap();
+        let recovered = signature
+            .recover_address_from_prehash(&permit_signing_hash(&chain, permit))
+            .unwrap();
+        assert_eq!(recovered, signer.address());
+    }
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+use crate::chain::eip155::{ChecksummedAddress, TokenAmount};
+use crate::lit_str;
+use crate::proto;
+use crate::proto::v2;
+pub use crate::scheme::permit2::PaymentRequirementsExtra;
+use crate::scheme::permit2::{Permit2Authorization, Permit2Payload};
+
+lit_str!(UptoScheme, "upto");
+
+pub type VerifyRequest = v2::VerifyRequest<PaymentPayload, PaymentRequirements>;
+pub type PaymentPayload = v2::PaymentPayload<PaymentRequirements, UptoEvmPayload>;
+pub type PaymentRequirements =
+    v2::PaymentRequirements<UptoScheme, TokenAmount, ChecksummedAddress, PaymentRequirementsExtra>;
+
+/// Settle request of the `upto` scheme: a verify request plus the amount actually charged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettleRequest {
+    #[serde(flatten)]
+    pub request: VerifyRequest,
+    /// Final amount to transfer, at most the required amount. Defaults to the required amount.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount: Option<TokenAmount>,
+}
+
+impl SettleRequest {
+    pub fn from_proto(
+        request: proto::SettleRequest,
+    ) -> Result<Self, proto::PaymentVerificationError> {
+        let deserialized: Self = serde_json::from_value(request.into_json())?;
+        Ok(deserialized)
+    }
+}
+
+/// Signed Permit2 witness transfer, authorizing `spender` to move up to `amount` of `token`
+/// from `from` to `to`.
+pub type UptoEvmPayload = Permit2Payload<UptoEvmPermit>;
+
+/// Fields of the Permit2 `PermitWitnessTransferFrom` message, with the witness inlined as `to`.
+pub type UptoEvmPermit = Permit2Authorization;
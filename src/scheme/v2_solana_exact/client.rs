@@ -6,6 +6,7 @@ use solana_signer::Signer;
 
 use crate::proto::PaymentRequired;
 use crate::proto::client::{PaymentCandidate, PaymentCandidateSigner, X402Error, X402SchemeClient};
+use crate::proto::util::U64String;
 use crate::proto::v2;
 use crate::proto::v2::ResourceInfo;
 use crate::proto::v2::X402Version2;
@@ -22,12 +23,24 @@ use crate::util::Base64Bytes;
 pub struct V2SolanaExactClient<S, R> {
     signer: S,
     rpc_client: R,
+    durable_nonce: Option<Pubkey>,
 }
 
 #[allow(dead_code)] // Public for consumption by downstream crates.
 impl<S, R> V2SolanaExactClient<S, R> {
     pub fn new(signer: S, rpc_client: R) -> Self {
-        Self { signer, rpc_client }
+        Self {
+            signer,
+            rpc_client,
+            durable_nonce: None,
+        }
+    }
+
+    /// Builds payment transactions on the durable nonce of `nonce_account`, whose authority is the signer,
+    /// so that they stay valid until settled.
+    pub fn with_durable_nonce(mut self, nonce_account: Pubkey) -> Self {
+        self.durable_nonce = Some(nonce_account);
+        self
     }
 }
 
@@ -79,6 +92,7 @@ where
                         rpc_client: self.rpc_client.clone(),
                         requirements,
                         resource: payment_required.resource.clone(),
+                        durable_nonce: self.durable_nonce,
                     }),
                 };
                 Some(candidate)
@@ -94,6 +108,7 @@ struct PayloadSigner<S, R> {
     rpc_client: R,
     requirements: PaymentRequirements,
     resource: ResourceInfo,
+    durable_nonce: Option<Pubkey>,
 }
 
 #[allow(dead_code)] // Public for consumption by downstream crates.
@@ -109,6 +124,12 @@ impl<S: Signer + Sync, R: RpcClientLike + Sync> PaymentCandidateSigner for Paylo
                 "missing fee_payer in extra".to_string(),
             ))?;
         let fee_payer_pubkey: Pubkey = fee_payer.into();
+        let max_compute_unit_price = self
+            .requirements
+            .extra
+            .as_ref()
+            .and_then(|extra| extra.max_compute_unit_price.as_ref())
+            .map(U64String::inner);
 
         let amount = self.requirements.amount.inner();
         let tx_b64 = build_signed_transfer_transaction(
@@ -118,6 +139,8 @@ impl<S: Signer + Sync, R: RpcClientLike + Sync> PaymentCandidateSigner for Paylo
             &self.requirements.pay_to,
             &self.requirements.asset,
             amount,
+            self.durable_nonce.as_ref(),
+            max_compute_unit_price,
         )
         .await?;
 
@@ -9,6 +9,7 @@ use crate::chain::solana::SolanaChainProvider;
 use crate::chain::{ChainProvider, ChainProviderOps};
 use crate::proto;
 use crate::proto::PaymentVerificationError;
+use crate::scheme::v1_solana_exact::config::SolanaExactConfig;
 use crate::scheme::v1_solana_exact::types::SupportedPaymentKindExtra;
 use crate::scheme::v1_solana_exact::{
     TransferRequirement, VerifyTransferResult, settle_transaction, verify_transaction,
@@ -33,19 +34,22 @@ impl X402SchemeFacilitatorBuilder for V2SolanaExact {
     fn build(
         &self,
         provider: ChainProvider,
-        _config: Option<serde_json::Value>,
+        config: Option<serde_json::Value>,
     ) -> Result<Box<dyn X402SchemeFacilitator>, Box<dyn Error>> {
         let provider = if let ChainProvider::Solana(provider) = provider {
             provider
         } else {
             return Err("V2SolanaExact::build: provider must be a SolanaChainProvider".into());
         };
-        Ok(Box::new(V2SolanaExactFacilitator { provider }))
+        let config = SolanaExactConfig::from_json(config)
+            .map_err(|e| format!("V2SolanaExact::build: {e}"))?;
+        Ok(Box::new(V2SolanaExactFacilitator { provider, config }))
     }
 }
 
 pub struct V2SolanaExactFacilitator {
     provider: Arc<SolanaChainProvider>,
+    config: SolanaExactConfig,
 }
 
 #[async_trait::async_trait]
@@ -55,39 +59,55 @@ impl X402SchemeFacilitator for V2SolanaExactFacilitator {
         request: &proto::VerifyRequest,
     ) -> Result<proto::VerifyResponse, X402SchemeFacilitatorError> {
         let request = types::VerifyRequest::from_proto(request.clone())?;
-        let verification = verify_transfer(&self.provider, &request).await?;
-        Ok(proto::v2::VerifyResponse::valid(verification.payer.to_string()).into())
+        let verification = verify_transfer(&self.provider, &self.config, &request).await?;
+        Ok(
+            proto::v2::VerifyResponse::valid(verification.payer.to_string())
+                .with_memos(verification.memos)
+                .into(),
+        )
     }
 
     async fn settle(
         &self,
         request: &proto::SettleRequest,
     ) -> Result<proto::SettleResponse, X402SchemeFacilitatorError> {
+        let finality = self
+            .provider
+            .settlement_finality(request.finality()?, self.config.commitment())?;
         let request = types::SettleRequest::from_proto(request.clone())?;
-        let verification = verify_transfer(&self.provider, &request).await?;
+        let verification = verify_transfer(&self.provider, &self.config, &request).await?;
         let payer = verification.payer.to_string();
-        let tx_sig = settle_transaction(&self.provider, verification).await?;
+        let tx_sig = settle_transaction(&self.provider, verification, finality).await?;
         Ok(proto::v2::SettleResponse::Success {
             payer,
             transaction: tx_sig.to_string(),
             network: self.provider.chain_id().to_string(),
+            finality: Some(finality),
         }
         .into())
     }
 
     async fn supported(&self) -> Result<proto::SupportedResponse, X402SchemeFacilitatorError> {
         let chain_id = self.provider.chain_id();
-        let kinds: Vec<proto::SupportedPaymentKind> = {
-            let fee_payer = self.provider.fee_payer();
-            let extra =
-                Some(serde_json::to_value(SupportedPaymentKindExtra { fee_payer }).unwrap());
-            vec![proto::SupportedPaymentKind {
+        // One kind per fee payer, see `SolanaChainProvider::advertised_fee_payers`
+        let max_compute_unit_price = self.provider.max_compute_unit_price();
+        let kinds: Vec<proto::SupportedPaymentKind> = self
+            .provider
+            .advertised_fee_payers()
+            .into_iter()
+            .map(|fee_payer| proto::SupportedPaymentKind {
                 x402_version: proto::v2::X402Version2.into(),
                 scheme: types::ExactScheme.to_string(),
                 network: chain_id.to_string(),
-                extra,
-            }]
-        };
+                extra: Some(
+                    serde_json::to_value(SupportedPaymentKindExtra {
+                        fee_payer,
+                        max_compute_unit_price: Some(max_compute_unit_price.into()),
+                    })
+                    .unwrap(),
+                ),
+            })
+            .collect();
         let signers = {
             let mut signers = HashMap::with_capacity(1);
             signers.insert(chain_id, self.provider.signer_addresses());
@@ -103,6 +123,7 @@ impl X402SchemeFacilitator for V2SolanaExactFacilitator {
 
 pub async fn verify_transfer(
     provider: &SolanaChainProvider,
+    config: &SolanaExactConfig,
     request: &types::VerifyRequest,
 ) -> Result<VerifyTransferResult, PaymentVerificationError> {
     let payload = &request.payment_payload;
@@ -118,11 +139,19 @@ pub async fn verify_transfer(
     if payload_chain_id != &chain_id {
         return Err(PaymentVerificationError::UnsupportedChain);
     }
+    config.assert_mint(&requirements.asset)?;
+    config.assert_amount(requirements.amount.inner())?;
     let transaction_b64_string = payload.payload.transaction.clone();
     let transfer_requirement = TransferRequirement {
         pay_to: &requirements.pay_to,
         asset: &requirements.asset,
         amount: requirements.amount.inner(),
     };
-    verify_transaction(provider, transaction_b64_string, &transfer_requirement).await
+    verify_transaction(
+        provider,
+        config,
+        transaction_b64_string,
+        &transfer_requirement,
+    )
+    .await
 }
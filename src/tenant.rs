@@ -0,0 +1,378 @@
+//! Multi-tenant facilitator support.
+//!
+//! A single facilitator process can serve several tenants, each with its own chains
+//! (and thus its own signers paying for gas) and its own schemes. Every tenant owns a
+//! separate [`ChainRegistry`] and [`SchemeRegistry`], wrapped into a [`FacilitatorLocal`].
+//!
+//! Tenants are resolved per request by [`ResolvedTenant`]:
+//! - from the `/t/{tenant}/...` path prefix, or
+//! - from an API key passed as `X-API-Key: <key>` or `Authorization: Bearer <key>`.
+//!
+//! A tenant with API keys configured also requires one of them under its path prefix.
+//!
+//! Requests carrying neither go to the default tenant, built from the top-level
+//! `chains` and `schemes` of the [`Config`].
+
+use axum::extract::{FromRequestParts, Path};
+use axum::http::StatusCode;
+use axum::http::request::Parts;
+use axum::response::{IntoResponse, Response};
+use axum::{Json, RequestPartsExt};
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::Counter;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::Instrument;
+
+use crate::chain::ChainRegistry;
+use crate::config::{ChainConfig, Config, SchemeConfig, UpstreamConfig};
+use crate::facilitator::Facilitator;
+use crate::facilitator_local::{FacilitatorLocal, FacilitatorLocalError};
+use crate::facilitator_upstream::FacilitatorUpstream;
+use crate::proto;
+use crate::scheme::{SchemeBlueprints, SchemeRegistry};
+
+/// Name of the tenant built from the top-level `chains` and `schemes` configuration.
+pub const DEFAULT_TENANT: &str = "default";
+
+/// Header carrying a tenant API key.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// A single tenant: a named [`FacilitatorLocal`] with its own chains and schemes.
+///
+/// Every call is run within a `tenant` span and counted in the `x402.facilitator.requests`
+/// metric, tagged with the tenant name, the operation and its outcome.
+pub struct Tenant {
+    name: String,
+    /// Whether requests must carry one of the tenant API keys.
+    requires_api_key: bool,
+    facilitator: FacilitatorLocal<SchemeRegistry>,
+    requests: Counter<u64>,
+}
+
+impl Tenant {
+    async fn from_config(
+        name: &str,
+        chains: &[ChainConfig],
+        schemes: &[SchemeConfig],
+        upstreams: &[UpstreamConfig],
+        requires_api_key: bool,
+        requests: Counter<u64>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let chain_registry = ChainRegistry::from_config(chains).await?;
+        let scheme_registry =
+            SchemeRegistry::build(chain_registry, SchemeBlueprints::full(), schemes);
+        let upstreams = upstreams
+            .iter()
+            .map(FacilitatorUpstream::from_config)
+            .collect();
+        Ok(Self {
+            name: name.to_string(),
+            requires_api_key,
+            facilitator: FacilitatorLocal::new(scheme_registry).with_upstreams(upstreams),
+            requests,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn record<T, E>(&self, operation: &'static str, result: &Result<T, E>) {
+        let outcome = if result.is_ok() { "success" } else { "error" };
+        self.requests.add(
+            1,
+            &[
+                KeyValue::new("tenant", self.name.clone()),
+                KeyValue::new("operation", operation),
+                KeyValue::new("outcome", outcome),
+            ],
+        );
+    }
+}
+
+impl Facilitator for Tenant {
+    type Error = FacilitatorLocalError;
+
+    async fn verify(
+        &self,
+        request: &proto::VerifyRequest,
+    ) -> Result<proto::VerifyResponse, Self::Error> {
+        let span = tracing::info_span!("tenant", tenant = %self.name);
+        let result = self.facilitator.verify(request).instrument(span).await;
+        self.record("verify", &result);
+        result
+    }
+
+    async fn settle(
+        &self,
+        request: &proto::SettleRequest,
+    ) -> Result<proto::SettleResponse, Self::Error> {
+        let span = tracing::info_span!("tenant", tenant = %self.name);
+        let result = self.facilitator.settle(request).instrument(span).await;
+        self.record("settle", &result);
+        result
+    }
+
+    async fn supported(&self) -> Result<proto::SupportedResponse, Self::Error> {
+        let span = tracing::info_span!("tenant", tenant = %self.name);
+        let result = self.facilitator.supported().instrument(span).await;
+        self.record("supported", &result);
+        result
+    }
+}
+
+/// All tenants served by this facilitator, indexed by name and by API key.
+pub struct TenantRegistry {
+    default: Arc<Tenant>,
+    by_name: HashMap<String, Arc<Tenant>>,
+    by_api_key: HashMap<String, Arc<Tenant>>,
+}
+
+impl TenantRegistry {
+    /// Builds the default tenant and every configured tenant, initializing their chain providers.
+    ///
+    /// Fails if two tenants share a name or an API key.
+    pub async fn from_config(config: &Config) -> Result<Self, Box<dyn std::error::Error>> {
+        let requests = opentelemetry::global::meter(env!("CARGO_PKG_NAME"))
+            .u64_counter("x402.facilitator.requests")
+            .with_description("Facilitator requests by tenant, operation and outcome")
+            .build();
+        let default = Tenant::from_config(
+            DEFAULT_TENANT,
+            config.chains(),
+            config.schemes(),
+            config.upstreams(),
+            false,
+            requests.clone(),
+        )
+        .await?;
+        let mut by_name = HashMap::new();
+        let mut by_api_key = HashMap::new();
+        for tenant_config in config.tenants() {
+            let name = tenant_config.name.clone();
+            if name == DEFAULT_TENANT || by_name.contains_key(&name) {
+                return Err(TenantError::DuplicateName(name).into());
+            }
+            let tenant = Tenant::from_config(
+                &name,
+                &tenant_config.chains,
+                &tenant_config.schemes,
+                &tenant_config.upstreams,
+                !tenant_config.api_keys.is_empty(),
+                requests.clone(),
+            )
+            .await?;
+            let tenant = Arc::new(tenant);
+            for api_key in &tenant_config.api_keys {
+                let api_key = api_key.inner().clone();
+                if by_api_key.insert(api_key, tenant.clone()).is_some() {
+                    return Err(TenantError::DuplicateApiKey(name).into());
+                }
+            }
+            tracing::info!(tenant = %name, "Initialized tenant");
+            by_name.insert(name, tenant);
+        }
+        Ok(Self {
+            default: Arc::new(default),
+            by_name,
+            by_api_key,
+        })
+    }
+
+    /// The tenant built from the top-level configuration.
+    pub fn default_tenant(&self) -> Arc<Tenant> {
+        self.default.clone()
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<Arc<Tenant>> {
+        self.by_name.get(name).cloned()
+    }
+
+    pub fn by_api_key(&self, api_key: &str) -> Option<Arc<Tenant>> {
+        self.by_api_key.get(api_key).cloned()
+    }
+}
+
+/// Errors raised while building a [`TenantRegistry`].
+#[derive(Debug, thiserror::Error)]
+pub enum TenantError {
+    #[error("Tenant name {0} is used more than once")]
+    DuplicateName(String),
+    #[error("Tenant {0} reuses an API key of another tenant")]
+    DuplicateApiKey(String),
+}
+
+/// Axum extractor resolving the [`Tenant`] a request is addressed to.
+///
+/// The `{tenant}` path parameter takes precedence. If an API key is also present,
+/// it must belong to that same tenant, and it must be present if that tenant has API keys.
+/// Otherwise, the request goes to the tenant of its API key, if any, or to the default tenant.
+pub struct ResolvedTenant(pub Arc<Tenant>);
+
+impl FromRequestParts<Arc<TenantRegistry>> for ResolvedTenant {
+    type Rejection = TenantRejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        registry: &Arc<TenantRegistry>,
+    ) -> Result<Self, Self::Rejection> {
+        let by_api_key = match api_key(parts) {
+            Some(api_key) => Some(
+                registry
+                    .by_api_key(api_key)
+                    .ok_or(TenantRejection::InvalidApiKey)?,
+            ),
+            None => None,
+        };
+        let path = parts.extract::<Option<Path<String>>>().await.ok().flatten();
+        let by_path = match path {
+            Some(Path(name)) => Some(
+                registry
+                    .by_name(&name)
+                    .ok_or(TenantRejection::UnknownTenant(name))?,
+            ),
+            None => None,
+        };
+        let tenant = match (by_path, by_api_key) {
+            (Some(by_path), Some(by_api_key)) if !Arc::ptr_eq(&by_path, &by_api_key) => {
+                return Err(TenantRejection::ApiKeyMismatch);
+            }
+            (Some(by_path), None) if by_path.requires_api_key => {
+                return Err(TenantRejection::MissingApiKey);
+            }
+            (Some(tenant), _) | (None, Some(tenant)) => tenant,
+            (None, None) => registry.default_tenant(),
+        };
+        Ok(ResolvedTenant(tenant))
+    }
+}
+
+/// Reads the API key from `X-API-Key`, falling back to `Authorization: Bearer`.
+fn api_key(parts: &Parts) -> Option<&str> {
+    if let Some(value) = parts.headers.get(API_KEY_HEADER) {
+        return value.to_str().ok();
+    }
+    parts
+        .headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+}
+
+/// Reasons a request could not be resolved to a tenant.
+#[derive(Debug, thiserror::Error)]
+pub enum TenantRejection {
+    #[error("Unknown tenant {0}")]
+    UnknownTenant(String),
+    #[error("Invalid API key")]
+    InvalidApiKey,
+    #[error("Missing API key")]
+    MissingApiKey,
+    #[error("API key does not belong to the requested tenant")]
+    ApiKeyMismatch,
+}
+
+impl IntoResponse for TenantRejection {
+    fn into_response(self) -> Response {
+        let status_code = match self {
+            TenantRejection::UnknownTenant(_) => StatusCode::NOT_FOUND,
+            TenantRejection::InvalidApiKey | TenantRejection::MissingApiKey => {
+                StatusCode::UNAUTHORIZED
+            }
+            TenantRejection::ApiKeyMismatch => StatusCode::FORBIDDEN,
+        };
+        (status_code, Json(json!({ "error": self.to_string() }))).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::body::Body;
+    use axum::http::Request;
+    use axum::routing::get;
+    use tower::ServiceExt;
+
+    async fn tenant(name: &str, requires_api_key: bool) -> Arc<Tenant> {
+        let requests = opentelemetry::global::meter("test")
+            .u64_counter("requests")
+            .build();
+        let tenant = Tenant::from_config(name, &[], &[], &[], requires_api_key, requests);
+        Arc::new(tenant.await.unwrap())
+    }
+
+    /// Resolves the tenant of a request to `uri` with `api_key`, answering with its name.
+    async fn resolve(
+        registry: &Arc<TenantRegistry>,
+        uri: &str,
+        api_key: Option<&str>,
+    ) -> (StatusCode, String) {
+        let name =
+            |ResolvedTenant(tenant): ResolvedTenant| async move { tenant.name().to_string() };
+        let endpoints = Router::new().route("/supported", get(name));
+        let router = Router::new()
+            .merge(endpoints.clone())
+            .nest("/t/{tenant}", endpoints)
+            .with_state(registry.clone());
+        let mut request = Request::builder().uri(uri);
+        if let Some(api_key) = api_key {
+            request = request.header(API_KEY_HEADER, api_key);
+        }
+        let response = router
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_resolved_tenant() {
+        let acme = tenant("acme", true).await;
+        let open = tenant("open", false).await;
+        let registry = Arc::new(TenantRegistry {
+            default: tenant(DEFAULT_TENANT, false).await,
+            by_name: HashMap::from([
+                ("acme".to_string(), acme.clone()),
+                ("open".to_string(), open),
+            ]),
+            by_api_key: HashMap::from([("acme-key".to_string(), acme)]),
+        });
+        let ok = |name: &str| (StatusCode::OK, name.to_string());
+
+        assert_eq!(
+            resolve(&registry, "/supported", None).await,
+            ok(DEFAULT_TENANT)
+        );
+        // Key only
+        assert_eq!(
+            resolve(&registry, "/supported", Some("acme-key")).await,
+            ok("acme")
+        );
+        // Path only: allowed unless the tenant has API keys
+        assert_eq!(
+            resolve(&registry, "/t/open/supported", None).await,
+            ok("open")
+        );
+        let (status, _) = resolve(&registry, "/t/acme/supported", None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            resolve(&registry, "/t/acme/supported", Some("acme-key")).await,
+            ok("acme")
+        );
+        // Mismatch
+        let (status, _) = resolve(&registry, "/t/open/supported", Some("acme-key")).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = resolve(&registry, "/supported", Some("other-key")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        // Unknown tenant
+        let (status, _) = resolve(&registry, "/t/unknown/supported", None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -2,7 +2,9 @@ pub mod b64;
 pub mod lit_str;
 pub mod sig_down;
 pub mod telemetry;
+pub mod ttl_cache;
 
 pub use b64::*;
 pub use sig_down::*;
 pub use telemetry::*;
+pub use ttl_cache::*;
@@ -0,0 +1,78 @@
+//! Concurrent cache whose entries expire after a TTL, holding a bounded number of them.
+//!
+//! Once the cache is full, inserting a new key first drops the expired entries,
+//! then the oldest one if that is not enough.
+
+use dashmap::DashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+pub struct TtlCache<K: Eq + Hash, V> {
+    /// Values by key, with the time they were inserted.
+    entries: DashMap<K, (Instant, V)>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    /// Creates an empty cache of at most `capacity` entries, each expiring after `ttl`.
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            entries: DashMap::new(),
+            ttl,
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Value of `key`, if inserted less than the TTL ago.
+    pub fn get(&self, key: &K) -> Option<V> {
+        let entry = self.entries.get(key)?;
+        let (inserted_at, value) = entry.value();
+        (inserted_at.elapsed() < self.ttl).then(|| value.clone())
+    }
+
+    /// Inserts or refreshes the value of `key`, evicting another entry if the cache is full.
+    pub fn insert(&self, key: K, value: V) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            self.entries
+                .retain(|_, (inserted_at, _)| inserted_at.elapsed() < self.ttl);
+            if self.entries.len() >= self.capacity {
+                let oldest = self
+                    .entries
+                    .iter()
+                    .min_by_key(|entry| entry.value().0)
+                    .map(|entry| entry.key().clone());
+                if let Some(oldest) = oldest {
+                    self.entries.remove(&oldest);
+                }
+            }
+        }
+        self.entries.insert(key, (Instant::now(), value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ttl_cache_is_bounded() {
+        let cache = TtlCache::new(Duration::from_secs(60), 2);
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+        // Refreshing a present key evicts nothing
+        cache.insert(1, "c");
+        assert_eq!(cache.entries.len(), 2);
+        // The oldest entry makes room for a new key
+        cache.insert(3, "d");
+        assert_eq!(cache.entries.len(), 2);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1), Some("c"));
+        assert_eq!(cache.get(&3), Some("d"));
+
+        let cache = TtlCache::new(Duration::ZERO, 2);
+        cache.insert(1, "a");
+        assert_eq!(cache.get(&1), None);
+    }
+}